@@ -0,0 +1,312 @@
+use super::{BackupManifest, BackupTable, BACKUP_FORMAT_VERSION, BACKUP_TABLES};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+fn table_entry_name(table: &str) -> String {
+    format!("tables/{}.jsonl", table)
+}
+
+/// Table currently being written
+struct OpenTable {
+    name: String,
+    rows: usize,
+    hasher: Sha256,
+    columns: BTreeSet<String>,
+}
+
+/// Zip archive of JSON Lines tables, written one row at a time
+pub struct BackupWriter {
+    zip: ZipWriter<File>,
+    tables: Vec<BackupTable>,
+    current: Option<OpenTable>,
+}
+
+impl BackupWriter {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
+        Ok(Self {
+            zip: ZipWriter::new(file),
+            tables: Vec::new(),
+            current: None,
+        })
+    }
+
+    fn options() -> SimpleFileOptions {
+        SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(true)
+    }
+
+    pub fn start_table(&mut self, name: &str) -> Result<(), String> {
+        self.close_table();
+        self.zip
+            .start_file(table_entry_name(name), Self::options())
+            .map_err(|e| format!("Failed to write backup: {}", e))?;
+        self.current = Some(OpenTable {
+            name: name.to_string(),
+            rows: 0,
+            hasher: Sha256::new(),
+            columns: BTreeSet::new(),
+        });
+        Ok(())
+    }
+
+    pub fn write_row(&mut self, row: &Value) -> Result<(), String> {
+        let table = self.current.as_mut().ok_or_else(|| "No table started".to_string())?;
+        let mut line = serde_json::to_vec(row).map_err(|e| format!("Failed to serialize row: {}", e))?;
+        line.push(b'\n');
+
+        table.hasher.update(&line);
+        table.rows += 1;
+        if let Value::Object(map) = row {
+            table.columns.extend(map.keys().cloned());
+        }
+        self.zip
+            .write_all(&line)
+            .map_err(|e| format!("Failed to write backup: {}", e))
+    }
+
+    fn close_table(&mut self) {
+        if let Some(table) = self.current.take() {
+            self.tables.push(BackupTable {
+                name: table.name,
+                rows: table.rows,
+                sha256: hex::encode(table.hasher.finalize()),
+                columns: table.columns.into_iter().collect(),
+            });
+        }
+    }
+
+    /// Write the manifest and close the archive
+    pub fn finish(mut self, created_at: String, includes_secrets: bool) -> Result<BackupManifest, String> {
+        self.close_table();
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            created_at,
+            includes_secrets,
+            tables: self.tables,
+        };
+
+        self.zip
+            .start_file(MANIFEST_NAME, Self::options())
+            .map_err(|e| format!("Failed to write backup: {}", e))?;
+        let content = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        self.zip
+            .write_all(&content)
+            .map_err(|e| format!("Failed to write backup: {}", e))?;
+        self.zip
+            .finish()
+            .map_err(|e| format!("Failed to save backup: {}", e))?;
+
+        Ok(manifest)
+    }
+}
+
+/// Tables of a verified backup, extracted to a working directory
+pub struct ExtractedBackup {
+    pub manifest: BackupManifest,
+    dir: PathBuf,
+}
+
+impl ExtractedBackup {
+    /// Check every table against the manifest checksums and extract it.
+    /// Nothing is restored from an archive that fails verification.
+    pub fn open(path: &str, dir: &Path, mut progress: impl FnMut(&str, usize)) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("无法打开备份文件: {}", e))?;
+        let mut archive = ZipArchive::new(file).map_err(|e| format!("不是有效的备份文件: {}", e))?;
+
+        let manifest: BackupManifest = {
+            let entry = archive
+                .by_name(MANIFEST_NAME)
+                .map_err(|_| "备份文件缺少 manifest.json".to_string())?;
+            serde_json::from_reader(entry).map_err(|e| format!("无法解析备份清单: {}", e))?
+        };
+        if manifest.format_version > BACKUP_FORMAT_VERSION {
+            return Err(format!(
+                "备份文件版本 {} 高于当前支持的版本 {}，请先升级应用",
+                manifest.format_version, BACKUP_FORMAT_VERSION
+            ));
+        }
+        // 表名用于拼接解压路径和建表语句，只接受已知的数据表
+        let mut seen = HashSet::new();
+        for table in &manifest.tables {
+            if !BACKUP_TABLES.contains(&table.name.as_str()) {
+                return Err(format!("备份文件包含未知的数据表: {}", table.name));
+            }
+            if !seen.insert(table.name.as_str()) {
+                return Err(format!("备份文件中的数据表 {} 重复", table.name));
+            }
+        }
+
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+        // 从这里起由 Drop 负责清理解压目录，校验失败时也不会留下临时文件
+        let extracted = Self {
+            manifest,
+            dir: dir.to_path_buf(),
+        };
+        for table in &extracted.manifest.tables {
+            let mut entry = archive
+                .by_name(&table_entry_name(&table.name))
+                .map_err(|_| format!("备份文件缺少数据表 {}", table.name))?;
+            let target = File::create(dir.join(format!("{}.jsonl", table.name)))
+                .map_err(|e| format!("Failed to create file: {}", e))?;
+            let mut target = BufWriter::new(target);
+
+            let mut hasher = Sha256::new();
+            let mut buffer = [0u8; 64 * 1024];
+            loop {
+                let read = entry
+                    .read(&mut buffer)
+                    .map_err(|e| format!("读取备份数据表 {} 失败: {}", table.name, e))?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+                target
+                    .write_all(&buffer[..read])
+                    .map_err(|e| format!("Failed to write file: {}", e))?;
+            }
+            target.flush().map_err(|e| format!("Failed to write file: {}", e))?;
+
+            if hex::encode(hasher.finalize()) != table.sha256 {
+                return Err(format!("备份数据表 {} 校验失败，文件可能已损坏", table.name));
+            }
+            progress(&table.name, table.rows);
+        }
+
+        Ok(extracted)
+    }
+
+    /// Rows of one table, in the order they were backed up
+    pub fn rows(&self, table: &str) -> Result<impl Iterator<Item = Result<Value, String>>, String> {
+        let file = File::open(self.dir.join(format!("{}.jsonl", table)))
+            .map_err(|e| format!("Failed to open file: {}", e))?;
+        Ok(BufReader::new(file).lines().map(|line| {
+            let line = line.map_err(|e| format!("Failed to read backup: {}", e))?;
+            serde_json::from_str(&line).map_err(|e| format!("无法解析备份数据: {}", e))
+        }))
+    }
+}
+
+impl Drop for ExtractedBackup {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("checkin-backup-test-{}-{}", std::process::id(), name))
+    }
+
+    /// Archive with the given manifest and entries, bypassing BackupWriter
+    fn write_archive(path: &Path, manifest: &BackupManifest, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.start_file(MANIFEST_NAME, SimpleFileOptions::default()).unwrap();
+        zip.write_all(&serde_json::to_vec(manifest).unwrap()).unwrap();
+        zip.finish().unwrap();
+    }
+
+    fn manifest(tables: Vec<BackupTable>) -> BackupManifest {
+        BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            created_at: "2026-10-19T00:00:00Z".to_string(),
+            includes_secrets: false,
+            tables,
+        }
+    }
+
+    #[test]
+    fn written_backup_verifies_and_reads_back() {
+        let path = temp_path("roundtrip.zip");
+        let dir = temp_path("roundtrip");
+        let departments = vec![
+            json!({"id": 1, "name": "总部", "parent_id": null}),
+            json!({"id": 2, "name": "研发部", "parent_id": 1, "manager_id": 3}),
+        ];
+
+        let mut writer = BackupWriter::create(path.to_str().unwrap()).unwrap();
+        writer.start_table("departments").unwrap();
+        for row in &departments {
+            writer.write_row(row).unwrap();
+        }
+        writer.start_table("users").unwrap();
+        let written = writer.finish("2026-10-19T00:00:00Z".to_string(), false).unwrap();
+        assert_eq!(written.tables[0].rows, 2);
+        assert_eq!(written.tables[0].columns, ["id", "manager_id", "name", "parent_id"]);
+        assert_eq!(written.tables[1].rows, 0);
+
+        let mut verified = Vec::new();
+        let backup = ExtractedBackup::open(path.to_str().unwrap(), &dir, |table, rows| {
+            verified.push((table.to_string(), rows))
+        })
+        .unwrap();
+        assert_eq!(verified, [("departments".to_string(), 2), ("users".to_string(), 0)]);
+        assert_eq!(backup.manifest.tables[0].sha256, written.tables[0].sha256);
+        let rows: Vec<Value> = backup.rows("departments").unwrap().map(Result::unwrap).collect();
+        assert_eq!(rows, departments);
+        assert_eq!(backup.rows("users").unwrap().count(), 0);
+
+        drop(backup);
+        assert!(!dir.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn checksum_mismatch_is_refused() {
+        let path = temp_path("mismatch.zip");
+        let dir = temp_path("mismatch");
+        let table = BackupTable {
+            name: "departments".to_string(),
+            rows: 1,
+            sha256: hex::encode(Sha256::digest(b"{\"id\":1}\n")),
+            columns: vec!["id".to_string()],
+        };
+        write_archive(&path, &manifest(vec![table]), &[("tables/departments.jsonl", b"{\"id\":2}\n")]);
+
+        let error = ExtractedBackup::open(path.to_str().unwrap(), &dir, |_, _| {})
+            .err()
+            .unwrap();
+        assert!(error.contains("校验失败"), "{}", error);
+        assert!(!dir.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unknown_tables_are_refused_before_extracting() {
+        let path = temp_path("unknown.zip");
+        let dir = temp_path("unknown");
+        let content = b"{\"id\":1}\n";
+        let table = |name: &str| BackupTable {
+            name: name.to_string(),
+            rows: 1,
+            sha256: hex::encode(Sha256::digest(content)),
+            columns: vec!["id".to_string()],
+        };
+
+        for tables in [vec![table("../escape")], vec![table("users"), table("users")]] {
+            let entries: &[(&str, &[u8])] = &[("tables/../escape.jsonl", content), ("tables/users.jsonl", content)];
+            write_archive(&path, &manifest(tables), entries);
+            assert!(ExtractedBackup::open(path.to_str().unwrap(), &dir, |_, _| {}).is_err());
+            assert!(!dir.exists());
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
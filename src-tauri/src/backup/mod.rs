@@ -0,0 +1,46 @@
+// Backup module
+pub mod archive;
+pub mod sqlite;
+
+use serde::{Deserialize, Serialize};
+
+/// Version written into backup manifests; newer archives are refused on restore
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Tables in a backup, in the order they are restored so references always exist first
+pub const BACKUP_TABLES: &[&str] = &[
+    "departments",
+    "action_types",
+    "time_rules",
+    "users",
+    "holidays",
+    "leaves",
+    "system_config",
+    "check_ins",
+    "audit_log",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupTable {
+    pub name: String,
+    pub rows: usize,
+    /// SHA-256 of the uncompressed JSON Lines data
+    pub sha256: String,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub created_at: String,
+    /// Whether password hashes and two-factor secrets were kept
+    #[serde(alias = "includes_password_hashes")]
+    pub includes_secrets: bool,
+    pub tables: Vec<BackupTable>,
+}
+
+impl BackupManifest {
+    pub fn table(&self, name: &str) -> Option<&BackupTable> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+}
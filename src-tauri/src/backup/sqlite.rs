@@ -0,0 +1,152 @@
+use super::archive::ExtractedBackup;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection};
+use serde_json::Value;
+use std::path::Path;
+
+/// Rows inserted per SQLite transaction
+const SQLITE_BATCH_SIZE: usize = 5000;
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// JSON values map onto SQLite's dynamic types; arrays and objects are stored as JSON text
+fn to_sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+/// Load a verified backup into a new SQLite database file.
+///
+/// Each table keeps the columns recorded in the manifest and its original ids,
+/// so the file can be queried or used as a local backend directly.
+pub fn restore_to_sqlite(
+    backup: &ExtractedBackup,
+    path: &str,
+    mut progress: impl FnMut(&str, usize, usize),
+) -> Result<(), String> {
+    if Path::new(path).exists() {
+        return Err("目标 SQLite 文件已存在，请选择新的文件".to_string());
+    }
+    let mut conn = Connection::open(path).map_err(|e| format!("Failed to open SQLite database: {}", e))?;
+
+    for table in &backup.manifest.tables {
+        let columns: Vec<&str> = table
+            .columns
+            .iter()
+            .map(String::as_str)
+            .filter(|c| *c != "id")
+            .collect();
+        let mut definitions = vec!["\"id\" INTEGER PRIMARY KEY".to_string()];
+        definitions.extend(columns.iter().map(|c| quote_identifier(c)));
+        conn.execute(
+            &format!("CREATE TABLE {} ({})", quote_identifier(&table.name), definitions.join(", ")),
+            [],
+        )
+        .map_err(|e| format!("Failed to create table {}: {}", table.name, e))?;
+
+        let mut all_columns = vec!["id"];
+        all_columns.extend(&columns);
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote_identifier(&table.name),
+            all_columns.iter().map(|c| quote_identifier(c)).collect::<Vec<_>>().join(", "),
+            vec!["?"; all_columns.len()].join(", ")
+        );
+
+        let mut rows = backup.rows(&table.name)?.peekable();
+        let mut restored = 0;
+        while rows.peek().is_some() {
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start transaction: {}", e))?;
+            {
+                let mut statement = tx
+                    .prepare(&insert)
+                    .map_err(|e| format!("Failed to prepare insert: {}", e))?;
+                for row in rows.by_ref().take(SQLITE_BATCH_SIZE) {
+                    let row = row?;
+                    let values = all_columns
+                        .iter()
+                        .map(|c| to_sql_value(row.get(*c).unwrap_or(&Value::Null)));
+                    statement
+                        .execute(params_from_iter(values))
+                        .map_err(|e| format!("Failed to insert into {}: {}", table.name, e))?;
+                    restored += 1;
+                }
+            }
+            tx.commit().map_err(|e| format!("Failed to commit: {}", e))?;
+            progress(&table.name, restored, table.rows);
+        }
+        if restored == 0 {
+            progress(&table.name, 0, table.rows);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::archive::BackupWriter;
+    use serde_json::json;
+
+    #[test]
+    fn restores_rows_with_their_ids() {
+        let base = std::env::temp_dir().join(format!("checkin-sqlite-test-{}", std::process::id()));
+        let archive = base.with_extension("zip");
+        let database = base.with_extension("db");
+
+        let mut writer = BackupWriter::create(archive.to_str().unwrap()).unwrap();
+        writer.start_table("departments").unwrap();
+        writer.write_row(&json!({"id": 5, "name": "研发部", "parent_id": null})).unwrap();
+        writer.write_row(&json!({"id": 9, "name": "测试组", "parent_id": 5})).unwrap();
+        writer.start_table("system_config").unwrap();
+        writer
+            .write_row(&json!({"id": 1, "config_key": "work_days", "config_value": {"days": [1, 2, 3]}}))
+            .unwrap();
+        writer.finish("2026-10-19T00:00:00Z".to_string(), false).unwrap();
+
+        let backup = ExtractedBackup::open(archive.to_str().unwrap(), &base, |_, _| {}).unwrap();
+        let mut progress = Vec::new();
+        restore_to_sqlite(&backup, database.to_str().unwrap(), |table, rows, total| {
+            progress.push((table.to_string(), rows, total))
+        })
+        .unwrap();
+        assert_eq!(progress, [("departments".to_string(), 2, 2), ("system_config".to_string(), 1, 1)]);
+        // 目标文件已存在时不覆盖
+        assert!(restore_to_sqlite(&backup, database.to_str().unwrap(), |_, _, _| {}).is_err());
+
+        let conn = Connection::open(&database).unwrap();
+        let departments: Vec<(i64, String, Option<i64>)> = conn
+            .prepare("SELECT id, name, parent_id FROM departments ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            departments,
+            [(5, "研发部".to_string(), None), (9, "测试组".to_string(), Some(5))]
+        );
+        let value: String = conn
+            .query_row("SELECT config_value FROM system_config WHERE config_key = 'work_days'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(value, r#"{"days":[1,2,3]}"#);
+
+        drop(conn);
+        drop(backup);
+        std::fs::remove_file(&archive).unwrap();
+        std::fs::remove_file(&database).unwrap();
+    }
+}
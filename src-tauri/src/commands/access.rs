@@ -0,0 +1,79 @@
+use crate::database::SupabaseClient;
+use crate::models::{Role, User};
+use crate::session::Session;
+use crate::utils::department::managed_departments;
+use crate::utils::permissions::{role_allows, AttendanceScope, Permission};
+use super::admin::load_departments;
+
+/// User signed in to this app instance. Accounts that must change their
+/// password or enroll in two-factor authentication can do nothing else until they have.
+pub(crate) fn current_user(session: &Session) -> Result<User, String> {
+    let user = session.current().ok_or_else(|| "请先登录".to_string())?;
+    if user.must_change_password {
+        return Err("请先修改密码".to_string());
+    }
+    if user.two_factor_setup_required {
+        return Err("请先启用两步验证".to_string());
+    }
+    Ok(user)
+}
+
+/// Signed-in user, if the role grants the permission
+pub(crate) fn require(session: &Session, permission: Permission) -> Result<User, String> {
+    let user = current_user(session)?;
+    if !role_allows(user.role, permission) {
+        return Err("没有权限执行此操作".to_string());
+    }
+    Ok(user)
+}
+
+/// Members of the departments a manager is responsible for
+async fn team_member_ids(db: &SupabaseClient, user: &User) -> Result<Vec<i32>, String> {
+    if user.role != Role::Manager {
+        return Ok(Vec::new());
+    }
+    let departments = load_departments(db).await?;
+    let managed = managed_departments(&departments, user.id);
+    if managed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let filter = format!(
+        "in.({})",
+        managed.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+    );
+    let members: Vec<User> = db
+        .get("users", Some(vec![("department_id", &filter)]))
+        .await
+        .map_err(|e| format!("Failed to get users: {}", e))?;
+
+    Ok(members.into_iter().map(|u| u.id).collect())
+}
+
+/// Employees whose records the signed-in user may see
+pub(crate) async fn view_scope(db: &SupabaseClient, session: &Session) -> Result<AttendanceScope, String> {
+    let user = current_user(session)?;
+    let team = team_member_ids(db, &user).await?;
+    Ok(AttendanceScope::view(user.role, user.id, team))
+}
+
+/// Employees whose records the signed-in user may correct or approve
+pub(crate) async fn edit_scope(db: &SupabaseClient, session: &Session) -> Result<AttendanceScope, String> {
+    let user = current_user(session)?;
+    let team = team_member_ids(db, &user).await?;
+    Ok(AttendanceScope::edit(user.role, user.id, team))
+}
+
+pub(crate) async fn ensure_can_view(db: &SupabaseClient, session: &Session, user_id: i32) -> Result<(), String> {
+    if !view_scope(db, session).await?.allows(user_id) {
+        return Err("没有权限查看该员工的记录".to_string());
+    }
+    Ok(())
+}
+
+pub(crate) async fn ensure_can_edit(db: &SupabaseClient, session: &Session, user_id: i32) -> Result<(), String> {
+    if !edit_scope(db, session).await?.allows(user_id) {
+        return Err("没有权限修改该员工的记录".to_string());
+    }
+    Ok(())
+}
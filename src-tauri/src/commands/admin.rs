@@ -1,16 +1,36 @@
 use crate::database::SupabaseClient;
-use crate::models::{User, ActionType, TimeRule, CreateActionTypeRequest, CreateTimeRuleRequest, UpdateActionTypeRequest, UpdateTimeRuleRequest};
+use crate::models::{User, Role, ActionType, Department, CreateDepartmentRequest, UpdateDepartmentRequest, MergeActionTypesResult, TimeRule, Holiday, Leave, SystemConfig, CreateActionTypeRequest, CreateTimeRuleRequest, CreateHolidayRequest, CreateLeaveRequest, UpdateActionTypeRequest, UpdateTimeRuleRequest, UpdateSystemConfigRequest, PasswordResetResult};
+use crate::session::Session;
+use crate::utils::crypto::{generate_password, hash_password};
+use crate::utils::department::{creates_cycle, department_subtree};
+use crate::utils::config::{find_config_issues, introduced_issues, paired_role, role_name, ConfigIssue, NEW_ENTITY_ID};
+use crate::utils::permissions::Permission;
+use super::access::{current_user, edit_scope, ensure_can_edit, require, view_scope};
+use super::audit::{record_audit, snapshot};
+use super::auth::load_password_policy;
 use tauri::State;
 use serde_json::json;
+use std::collections::HashMap;
+
+const TEMPORARY_PASSWORD_LENGTH: usize = 10;
 
 // User management
 
+/// 只返回当前用户有权查看的员工
 #[tauri::command]
 pub async fn get_all_users(
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<Vec<User>, String> {
+    let scope = view_scope(&db, &session).await?;
+    let user_filter = scope.user_filter();
+    let mut params = vec![("order", "id.asc")];
+    if let Some(filter) = &user_filter {
+        params.push(("id", filter));
+    }
+
     let users: Vec<User> = db
-        .get("users", Some(vec![("order", "id.asc")]))
+        .get("users", Some(params))
         .await
         .map_err(|e| format!("Failed to get users: {}", e))?;
 
@@ -18,15 +38,24 @@ pub async fn get_all_users(
 }
 
 #[tauri::command]
-pub async fn update_user_admin_status(
+pub async fn set_user_role(
     user_id: i32,
-    is_admin: bool,
+    role: Role,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<(), String> {
+    let actor = require(&session, Permission::ManageUsers)?;
+    // 避免唯一的超级管理员把自己降级后无人能管理
+    if actor.id == user_id {
+        return Err("不能修改自己的角色".to_string());
+    }
+
     let update_data = json!({
-        "is_admin": is_admin,
+        "role": role,
+        "is_admin": role == Role::SuperAdmin,
     });
 
+    let before = snapshot(&db, "users", user_id).await?;
     db.patch(
         "users",
         vec![("id", &format!("eq.{}", user_id))],
@@ -35,27 +64,171 @@ pub async fn update_user_admin_status(
     .await
     .map_err(|e| format!("Failed to update user: {}", e))?;
 
+    let after = snapshot(&db, "users", user_id).await?;
+    record_audit(&db, &session, "set_user_role", "users", Some(user_id), before, after).await?;
+
     Ok(())
 }
 
+/// 停用用户（代替删除）：不能再登录，历史打卡仍保留在报表和导出中
 #[tauri::command]
-pub async fn delete_user(
+pub async fn deactivate_user(
     user_id: i32,
+    reason: Option<String>,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<(), String> {
-    db.delete("users", vec![("id", &format!("eq.{}", user_id))])
-        .await
-        .map_err(|e| format!("Failed to delete user: {}", e))?;
+    let actor = require(&session, Permission::ManageUsers)?;
+    if actor.id == user_id {
+        return Err("不能停用自己的账号".to_string());
+    }
+    let reason = reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    let update_data = json!({
+        "is_active": false,
+        "deactivated_at": chrono::Utc::now().to_rfc3339(),
+        "deactivation_reason": reason,
+    });
+
+    let before = snapshot(&db, "users", user_id).await?;
+    db.patch(
+        "users",
+        vec![("id", &format!("eq.{}", user_id))],
+        &update_data,
+    )
+    .await
+    .map_err(|e| format!("Failed to deactivate user: {}", e))?;
+
+    let after = snapshot(&db, "users", user_id).await?;
+    record_audit(&db, &session, "deactivate_user", "users", Some(user_id), before, after).await?;
 
     Ok(())
 }
 
+#[tauri::command]
+pub async fn restore_user(
+    user_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageUsers)?;
+    let update_data = json!({
+        "is_active": true,
+        "deactivated_at": null,
+        "deactivation_reason": null,
+    });
+
+    let before = snapshot(&db, "users", user_id).await?;
+    db.patch(
+        "users",
+        vec![("id", &format!("eq.{}", user_id))],
+        &update_data,
+    )
+    .await
+    .map_err(|e| format!("Failed to restore user: {}", e))?;
+
+    let after = snapshot(&db, "users", user_id).await?;
+    record_audit(&db, &session, "restore_user", "users", Some(user_id), before, after).await?;
+
+    Ok(())
+}
+
+/// 重置员工密码，生成一次性临时密码，员工登录后须立即修改
+#[tauri::command]
+pub async fn reset_user_password(
+    user_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<PasswordResetResult, String> {
+    let admin = require(&session, Permission::ManageUsers)?;
+    if admin.id == user_id {
+        return Err("请使用修改密码功能修改自己的密码".to_string());
+    }
+    let users: Vec<User> = db
+        .get("users", Some(vec![("id", &format!("eq.{}", user_id))]))
+        .await
+        .map_err(|e| format!("Failed to get user: {}", e))?;
+    let user = users.into_iter().next().ok_or_else(|| "用户不存在".to_string())?;
+
+    let policy = load_password_policy(&db).await;
+    let temporary_password = generate_password(TEMPORARY_PASSWORD_LENGTH);
+    let expires_at = (chrono::Utc::now() + chrono::Duration::hours(policy.temporary_password_hours)).to_rfc3339();
+
+    let before = snapshot(&db, "users", user_id).await?;
+    db.patch(
+        "users",
+        vec![("id", &format!("eq.{}", user_id))],
+        &json!({
+            "password_hash": hash_password(&temporary_password),
+            "must_change_password": true,
+            "temporary_password_expires_at": expires_at,
+        }),
+    )
+    .await
+    .map_err(|e| format!("Failed to reset password: {}", e))?;
+
+    let after = snapshot(&db, "users", user_id).await?;
+    record_audit(&db, &session, "reset_user_password", "users", Some(user_id), before, after).await?;
+
+    Ok(PasswordResetResult {
+        user_id,
+        username: user.username,
+        temporary_password,
+        expires_at,
+    })
+}
+
+// Configuration consistency
+
+/// Current action types and time rules, to check a change against the whole configuration
+async fn load_configuration(db: &SupabaseClient) -> Result<(Vec<ActionType>, Vec<TimeRule>), String> {
+    let action_types: Vec<ActionType> = db
+        .get("action_types", Some(vec![("order", "id.asc")]))
+        .await
+        .map_err(|e| format!("Failed to get action types: {}", e))?;
+    let rules: Vec<TimeRule> = db
+        .get("time_rules", Some(vec![("order", "id.asc")]))
+        .await
+        .map_err(|e| format!("Failed to get time rules: {}", e))?;
+
+    Ok((action_types, rules))
+}
+
+/// Refuse a change that introduces inconsistencies; issues that already exist do not block it
+fn ensure_consistent(
+    action_types: &[ActionType],
+    rules: &[TimeRule],
+    changed_types: &[ActionType],
+    changed_rules: &[TimeRule],
+) -> Result<(), String> {
+    let before = find_config_issues(action_types, rules);
+    let introduced = introduced_issues(&before, find_config_issues(changed_types, changed_rules));
+    if introduced.is_empty() {
+        return Ok(());
+    }
+
+    let details: Vec<String> = introduced.iter().map(|issue| issue.describe()).collect();
+    Err(format!("配置不一致，未保存:\n{}", details.join("\n")))
+}
+
+/// 检查打卡类型与时间规则的一致性，返回全部问题
+#[tauri::command]
+pub async fn validate_configuration(
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<ConfigIssue>, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let (action_types, rules) = load_configuration(&db).await?;
+    Ok(find_config_issues(&action_types, &rules))
+}
+
 // Action type management
 
 #[tauri::command]
 pub async fn get_all_action_types(
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<Vec<ActionType>, String> {
+    current_user(&session)?;
     let action_types: Vec<ActionType> = db
         .get("action_types", Some(vec![("order", "display_order.asc")]))
         .await
@@ -68,15 +241,37 @@ pub async fn get_all_action_types(
 pub async fn create_action_type(
     request: CreateActionTypeRequest,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<ActionType, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let (action_types, rules) = load_configuration(&db).await?;
+    let candidate = ActionType {
+        id: NEW_ENTITY_ID,
+        name: request.name.trim().to_string(),
+        button_text: request.button_text.clone(),
+        button_color: request.button_color.clone(),
+        display_order: request.display_order.unwrap_or(100),
+        action_role: request.action_role,
+        requires_pair: request.requires_pair,
+        pair_action_id: request.pair_action_id,
+        is_paid_break: request.is_paid_break.unwrap_or(false),
+        is_active: true,
+        archived_at: None,
+        created_at: String::new(),
+    };
+    let mut changed_types = action_types.clone();
+    changed_types.push(candidate);
+    ensure_consistent(&action_types, &rules, &changed_types, &rules)?;
+
     let new_action_type = json!({
-        "name": request.name,
+        "name": request.name.trim(),
         "button_text": request.button_text,
         "button_color": request.button_color,
         "display_order": request.display_order.unwrap_or(100),
         "action_role": request.action_role,
         "requires_pair": request.requires_pair,
         "pair_action_id": request.pair_action_id,
+        "is_paid_break": request.is_paid_break.unwrap_or(false),
         "is_active": true,
     });
 
@@ -85,6 +280,9 @@ pub async fn create_action_type(
         .await
         .map_err(|e| format!("Failed to create action type: {}", e))?;
 
+    let after = serde_json::to_value(&action_type).ok();
+    record_audit(&db, &session, "create_action_type", "action_types", Some(action_type.id), None, after).await?;
+
     Ok(action_type)
 }
 
@@ -92,9 +290,98 @@ pub async fn create_action_type(
 pub async fn update_action_type(
     request: UpdateActionTypeRequest,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let (all_action_types, rules) = load_configuration(&db).await?;
+    let current = all_action_types
+        .iter()
+        .find(|at| at.id == request.id)
+        .ok_or_else(|| "打卡类型不存在".to_string())?;
+    let others = || all_action_types.iter().filter(|at| at.id != request.id);
+
+    let mut updated = current.clone();
+    if let Some(name) = &request.name {
+        updated.name = name.trim().to_string();
+    }
+    if let Some(action_role) = request.action_role {
+        updated.action_role = action_role;
+    }
+    if let Some(pair_action_id) = request.pair_action_id {
+        updated.pair_action_id = pair_action_id;
+    }
+    if let Some(is_paid) = request.is_paid_break {
+        updated.is_paid_break = is_paid;
+    }
+    if let Some(active) = request.is_active {
+        updated.is_active = active;
+    }
+    let changed_types: Vec<ActionType> = all_action_types
+        .iter()
+        .map(|at| if at.id == request.id { updated.clone() } else { at.clone() })
+        .collect();
+    ensure_consistent(&all_action_types, &rules, &changed_types, &rules)?;
+
     let mut update_data = json!({});
+    let action_role = updated.action_role;
+
+    if action_role != current.action_role {
+        // 进行中的打卡按原角色配对，改角色后将无法结束
+        let ongoing_count = db
+            .count(
+                "check_ins",
+                &[("action_type_id", &format!("eq.{}", request.id)), ("status", "eq.ongoing")],
+            )
+            .await
+            .map_err(|e| format!("Failed to count check-ins: {}", e))?;
+        if ongoing_count > 0 {
+            return Err(format!("该类型有 {} 条进行中的打卡，结束后才能修改角色", ongoing_count));
+        }
 
+        // 原为结束类型时，若它是唯一可用的结束类型，进行中的开始打卡将无法结束
+        if current.action_role == 2 || current.action_role == 4 {
+            let has_other_end = others().any(|at| at.is_active && at.action_role == current.action_role);
+            if !has_other_end {
+                let start_ids: Vec<String> = all_action_types
+                    .iter()
+                    .filter(|at| Some(at.action_role) == paired_role(current.action_role))
+                    .map(|at| at.id.to_string())
+                    .collect();
+                let ongoing_starts = if start_ids.is_empty() {
+                    0
+                } else {
+                    db.count(
+                        "check_ins",
+                        &[
+                            ("action_type_id", &format!("in.({})", start_ids.join(","))),
+                            ("status", "eq.ongoing"),
+                        ],
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to count check-ins: {}", e))?
+                };
+                if ongoing_starts > 0 {
+                    return Err(format!(
+                        "该类型是唯一的{}类型，仍有 {} 条进行中的打卡需要它结束，无法修改角色",
+                        role_name(current.action_role),
+                        ongoing_starts
+                    ));
+                }
+            }
+        }
+
+        update_data["action_role"] = json!(action_role);
+    }
+
+    if let Some(name) = request.name {
+        update_data["name"] = json!(name.trim());
+    }
+    if let Some(pair) = request.pair_action_id {
+        update_data["pair_action_id"] = json!(pair);
+    }
+    if let Some(requires_pair) = request.requires_pair {
+        update_data["requires_pair"] = json!(requires_pair);
+    }
     if let Some(text) = request.button_text {
         update_data["button_text"] = json!(text);
     }
@@ -104,10 +391,14 @@ pub async fn update_action_type(
     if let Some(order) = request.display_order {
         update_data["display_order"] = json!(order);
     }
+    if let Some(is_paid) = request.is_paid_break {
+        update_data["is_paid_break"] = json!(is_paid);
+    }
     if let Some(active) = request.is_active {
         update_data["is_active"] = json!(active);
     }
 
+    let before = snapshot(&db, "action_types", request.id).await?;
     db.patch(
         "action_types",
         vec![("id", &format!("eq.{}", request.id))],
@@ -116,27 +407,189 @@ pub async fn update_action_type(
     .await
     .map_err(|e| format!("Failed to update action type: {}", e))?;
 
+    let after = snapshot(&db, "action_types", request.id).await?;
+    record_audit(&db, &session, "update_action_type", "action_types", Some(request.id), before, after).await?;
+
     Ok(())
 }
 
+/// 仅在没有打卡记录、时间规则或其他打卡类型引用时允许删除，否则应归档
 #[tauri::command]
 pub async fn delete_action_type(
     action_type_id: i32,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<(), String> {
-    db.delete("action_types", vec![("id", &format!("eq.{}", action_type_id))])
+    require(&session, Permission::ManageConfiguration)?;
+    let id_filter = format!("eq.{}", action_type_id);
+    let check_in_count = db
+        .count("check_ins", &[("action_type_id", &id_filter)])
+        .await
+        .map_err(|e| format!("Failed to count check-ins: {}", e))?;
+    let rule_count = db
+        .count("time_rules", &[("action_type_id", &id_filter)])
+        .await
+        .map_err(|e| format!("Failed to count time rules: {}", e))?;
+    let paired_count = db
+        .count("action_types", &[("pair_action_id", &id_filter)])
+        .await
+        .map_err(|e| format!("Failed to count action types: {}", e))?;
+
+    let mut references = Vec::new();
+    if check_in_count > 0 {
+        references.push(format!("{} 条打卡记录", check_in_count));
+    }
+    if rule_count > 0 {
+        references.push(format!("{} 条时间规则", rule_count));
+    }
+    if paired_count > 0 {
+        references.push(format!("{} 个配对的打卡类型", paired_count));
+    }
+    if !references.is_empty() {
+        return Err(format!(
+            "该打卡类型仍被 {} 引用，无法删除，请改为归档或合并到其他类型",
+            references.join("、")
+        ));
+    }
+
+    let before = snapshot(&db, "action_types", action_type_id).await?;
+    db.delete("action_types", vec![("id", &id_filter)])
         .await
         .map_err(|e| format!("Failed to delete action type: {}", e))?;
 
+    record_audit(&db, &session, "delete_action_type", "action_types", Some(action_type_id), before, None).await?;
+
     Ok(())
 }
 
+/// 归档打卡类型：停用该类型及其时间规则，历史打卡记录保持不变
+#[tauri::command]
+pub async fn archive_action_type(
+    action_type_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let id_filter = format!("eq.{}", action_type_id);
+
+    // 仍在使用的开始类型配对到此类型时，归档后其临时事件将无法结束
+    let paired: Vec<ActionType> = db
+        .get(
+            "action_types",
+            Some(vec![
+                ("pair_action_id", &id_filter),
+                ("id", &format!("neq.{}", action_type_id)),
+                ("is_active", "eq.true"),
+            ]),
+        )
+        .await
+        .map_err(|e| format!("Failed to get action types: {}", e))?;
+    if !paired.is_empty() {
+        let names: Vec<&str> = paired.iter().map(|at| at.name.as_str()).collect();
+        return Err(format!("请先归档与其配对的打卡类型: {}", names.join("、")));
+    }
+
+    let before = snapshot(&db, "action_types", action_type_id).await?;
+    let archived_at = chrono::Utc::now().to_rfc3339();
+    db.patch(
+        "action_types",
+        vec![("id", &id_filter)],
+        &json!({ "is_active": false, "archived_at": archived_at }),
+    )
+    .await
+    .map_err(|e| format!("Failed to archive action type: {}", e))?;
+
+    db.patch(
+        "time_rules",
+        vec![("action_type_id", &id_filter), ("archived_at", "is.null")],
+        &json!({ "is_active": false, "archived_at": archived_at }),
+    )
+    .await
+    .map_err(|e| format!("Failed to archive time rules: {}", e))?;
+
+    let after = snapshot(&db, "action_types", action_type_id).await?;
+    record_audit(&db, &session, "archive_action_type", "action_types", Some(action_type_id), before, after).await?;
+
+    Ok(())
+}
+
+/// 取消归档并重新启用打卡类型；其时间规则需单独恢复
+#[tauri::command]
+pub async fn restore_action_type(
+    action_type_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let before = snapshot(&db, "action_types", action_type_id).await?;
+    db.patch(
+        "action_types",
+        vec![("id", &format!("eq.{}", action_type_id))],
+        &json!({ "is_active": true, "archived_at": null }),
+    )
+    .await
+    .map_err(|e| format!("Failed to restore action type: {}", e))?;
+
+    let after = snapshot(&db, "action_types", action_type_id).await?;
+    record_audit(&db, &session, "restore_action_type", "action_types", Some(action_type_id), before, after).await?;
+
+    Ok(())
+}
+
+/// 将打卡类型 source 合并到 target：历史打卡、配对关系和时间规则改为指向 target，然后删除 source
+#[tauri::command]
+pub async fn merge_action_types(
+    source_id: i32,
+    target_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<MergeActionTypesResult, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    if source_id == target_id {
+        return Err("不能将打卡类型合并到自身".to_string());
+    }
+
+    let action_types: Vec<ActionType> = db
+        .get("action_types", Some(vec![("id", &format!("in.({},{})", source_id, target_id))]))
+        .await
+        .map_err(|e| format!("Failed to get action types: {}", e))?;
+    let source = action_types.iter().find(|at| at.id == source_id);
+    let target = action_types.iter().find(|at| at.id == target_id);
+    let (Some(source), Some(target)) = (source, target) else {
+        return Err("打卡类型不存在".to_string());
+    };
+    if source.action_role != target.action_role {
+        return Err(format!(
+            "只能合并角色相同的打卡类型（{} 与 {} 角色不同）",
+            source.name, target.name
+        ));
+    }
+
+    let before = json!({ "source": source, "target": target });
+
+    // 在数据库函数中一次完成，避免中途失败留下一半已迁移的记录
+    let result: MergeActionTypesResult = db
+        .rpc(
+            "merge_action_types",
+            &json!({ "source_id": source_id, "target_id": target_id }),
+        )
+        .await
+        .map_err(|e| format!("Failed to merge action types: {}", e))?;
+
+    let after = json!({ "target_id": target_id, "result": result });
+    record_audit(&db, &session, "merge_action_types", "action_types", Some(source_id), Some(before), Some(after)).await?;
+
+    Ok(result)
+}
+
 // Time rule management
 
 #[tauri::command]
 pub async fn get_all_time_rules(
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<Vec<TimeRule>, String> {
+    current_user(&session)?;
     let rules: Vec<TimeRule> = db
         .get("time_rules", Some(vec![("order", "id.asc")]))
         .await
@@ -149,12 +602,37 @@ pub async fn get_all_time_rules(
 pub async fn create_time_rule(
     request: CreateTimeRuleRequest,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<TimeRule, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let timezone = request.timezone.clone().unwrap_or_else(|| "Asia/Phnom_Penh".to_string());
+    let (action_types, rules) = load_configuration(&db).await?;
+    let candidate = TimeRule {
+        id: NEW_ENTITY_ID,
+        rule_name: request.rule_name.clone(),
+        action_type_id: request.action_type_id,
+        expected_start_time: request.expected_start_time.clone(),
+        expected_end_time: request.expected_end_time.clone(),
+        max_duration_minutes: request.max_duration_minutes,
+        daily_max_total_minutes: request.daily_max_total_minutes,
+        daily_max_count: request.daily_max_count,
+        rest_days: request.rest_days.clone(),
+        timezone: timezone.clone(),
+        is_active: true,
+        archived_at: None,
+        department_id: request.department_id,
+        created_at: String::new(),
+    };
+    let mut changed_rules = rules.clone();
+    changed_rules.push(candidate);
+    ensure_consistent(&action_types, &rules, &action_types, &changed_rules)?;
+
     let mut new_rule = json!({
         "rule_name": request.rule_name,
         "action_type_id": request.action_type_id,
-        "timezone": request.timezone.unwrap_or_else(|| "Asia/Phnom_Penh".to_string()),
+        "timezone": timezone,
         "is_active": true,
+        "department_id": request.department_id,
     });
 
     // 添加可选字段
@@ -167,12 +645,24 @@ pub async fn create_time_rule(
     if let Some(max_duration) = request.max_duration_minutes {
         new_rule["max_duration_minutes"] = json!(max_duration);
     }
+    if let Some(max_total) = request.daily_max_total_minutes {
+        new_rule["daily_max_total_minutes"] = json!(max_total);
+    }
+    if let Some(max_count) = request.daily_max_count {
+        new_rule["daily_max_count"] = json!(max_count);
+    }
+    if let Some(rest_days) = request.rest_days {
+        new_rule["rest_days"] = json!(rest_days);
+    }
 
     let rule: TimeRule = db
         .post("time_rules", &new_rule)
         .await
         .map_err(|e| format!("Failed to create time rule: {}", e))?;
 
+    let after = serde_json::to_value(&rule).ok();
+    record_audit(&db, &session, "create_time_rule", "time_rules", Some(rule.id), None, after).await?;
+
     Ok(rule)
 }
 
@@ -180,7 +670,48 @@ pub async fn create_time_rule(
 pub async fn update_time_rule(
     request: UpdateTimeRuleRequest,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let (action_types, rules) = load_configuration(&db).await?;
+    let mut updated = rules
+        .iter()
+        .find(|r| r.id == request.id)
+        .cloned()
+        .ok_or_else(|| "时间规则不存在".to_string())?;
+    if let Some(name) = &request.rule_name {
+        updated.rule_name = name.clone();
+    }
+    if let Some(start_time) = &request.expected_start_time {
+        updated.expected_start_time = start_time.clone();
+    }
+    if let Some(end_time) = &request.expected_end_time {
+        updated.expected_end_time = end_time.clone();
+    }
+    if let Some(max_duration) = request.max_duration_minutes {
+        updated.max_duration_minutes = max_duration;
+    }
+    if let Some(max_total) = request.daily_max_total_minutes {
+        updated.daily_max_total_minutes = max_total;
+    }
+    if let Some(max_count) = request.daily_max_count {
+        updated.daily_max_count = max_count;
+    }
+    if let Some(rest_days) = &request.rest_days {
+        updated.rest_days = rest_days.clone();
+    }
+    if let Some(active) = request.is_active {
+        updated.is_active = active;
+    }
+    if let Some(department_id) = request.department_id {
+        updated.department_id = department_id;
+    }
+    let changed_rules: Vec<TimeRule> = rules
+        .iter()
+        .map(|r| if r.id == request.id { updated.clone() } else { r.clone() })
+        .collect();
+    ensure_consistent(&action_types, &rules, &action_types, &changed_rules)?;
+
     let mut update_data = json!({});
 
     if let Some(name) = request.rule_name {
@@ -195,10 +726,23 @@ pub async fn update_time_rule(
     if let Some(max_duration) = request.max_duration_minutes {
         update_data["max_duration_minutes"] = json!(max_duration);
     }
+    if let Some(max_total) = request.daily_max_total_minutes {
+        update_data["daily_max_total_minutes"] = json!(max_total);
+    }
+    if let Some(max_count) = request.daily_max_count {
+        update_data["daily_max_count"] = json!(max_count);
+    }
+    if let Some(rest_days) = request.rest_days {
+        update_data["rest_days"] = json!(rest_days);
+    }
     if let Some(active) = request.is_active {
         update_data["is_active"] = json!(active);
     }
+    if let Some(department_id) = request.department_id {
+        update_data["department_id"] = json!(department_id);
+    }
 
+    let before = snapshot(&db, "time_rules", request.id).await?;
     db.patch(
         "time_rules",
         vec![("id", &format!("eq.{}", request.id))],
@@ -207,17 +751,558 @@ pub async fn update_time_rule(
     .await
     .map_err(|e| format!("Failed to update time rule: {}", e))?;
 
+    let after = snapshot(&db, "time_rules", request.id).await?;
+    record_audit(&db, &session, "update_time_rule", "time_rules", Some(request.id), before, after).await?;
+
     Ok(())
 }
 
+/// 规则所属打卡类型已有打卡记录时，历史考勤依赖该规则，只能归档
 #[tauri::command]
 pub async fn delete_time_rule(
     rule_id: i32,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let rules: Vec<TimeRule> = db
+        .get("time_rules", Some(vec![("id", &format!("eq.{}", rule_id))]))
+        .await
+        .map_err(|e| format!("Failed to get time rule: {}", e))?;
+    let rule = rules.first().ok_or_else(|| "时间规则不存在".to_string())?;
+
+    let check_in_count = db
+        .count("check_ins", &[("action_type_id", &format!("eq.{}", rule.action_type_id))])
+        .await
+        .map_err(|e| format!("Failed to count check-ins: {}", e))?;
+    if check_in_count > 0 {
+        return Err(format!(
+            "该规则适用的打卡类型已有 {} 条打卡记录，无法删除，请改为归档",
+            check_in_count
+        ));
+    }
+
+    let before = serde_json::to_value(rule).ok();
     db.delete("time_rules", vec![("id", &format!("eq.{}", rule_id))])
         .await
         .map_err(|e| format!("Failed to delete time rule: {}", e))?;
 
+    record_audit(&db, &session, "delete_time_rule", "time_rules", Some(rule_id), before, None).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn archive_time_rule(
+    rule_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let before = snapshot(&db, "time_rules", rule_id).await?;
+    db.patch(
+        "time_rules",
+        vec![("id", &format!("eq.{}", rule_id))],
+        &json!({ "is_active": false, "archived_at": chrono::Utc::now().to_rfc3339() }),
+    )
+    .await
+    .map_err(|e| format!("Failed to archive time rule: {}", e))?;
+
+    let after = snapshot(&db, "time_rules", rule_id).await?;
+    record_audit(&db, &session, "archive_time_rule", "time_rules", Some(rule_id), before, after).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn restore_time_rule(
+    rule_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let before = snapshot(&db, "time_rules", rule_id).await?;
+    db.patch(
+        "time_rules",
+        vec![("id", &format!("eq.{}", rule_id))],
+        &json!({ "is_active": true, "archived_at": null }),
+    )
+    .await
+    .map_err(|e| format!("Failed to restore time rule: {}", e))?;
+
+    let after = snapshot(&db, "time_rules", rule_id).await?;
+    record_audit(&db, &session, "restore_time_rule", "time_rules", Some(rule_id), before, after).await?;
+
+    Ok(())
+}
+
+// Department management
+
+pub(crate) async fn load_departments(db: &SupabaseClient) -> Result<Vec<Department>, String> {
+    db.get("departments", Some(vec![("order", "name.asc")]))
+        .await
+        .map_err(|e| format!("Failed to get departments: {}", e))
+}
+
+/// Members of a department and of all teams below it
+pub(crate) async fn department_user_ids(db: &SupabaseClient, department_id: i32) -> Result<Vec<i32>, String> {
+    let departments = load_departments(db).await?;
+    let subtree = department_subtree(&departments, department_id);
+    let filter = format!(
+        "in.({})",
+        subtree.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+    );
+    let users: Vec<User> = db
+        .get("users", Some(vec![("department_id", &filter)]))
+        .await
+        .map_err(|e| format!("Failed to get users: {}", e))?;
+
+    Ok(users.into_iter().map(|u| u.id).collect())
+}
+
+async fn ensure_user_exists(db: &SupabaseClient, user_id: i32) -> Result<(), String> {
+    let count = db
+        .count("users", &[("id", &format!("eq.{}", user_id))])
+        .await
+        .map_err(|e| format!("Failed to count users: {}", e))?;
+    if count == 0 {
+        return Err("用户不存在".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_departments(
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<Department>, String> {
+    current_user(&session)?;
+    load_departments(&db).await
+}
+
+#[tauri::command]
+pub async fn create_department(
+    request: CreateDepartmentRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Department, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err("部门名称不能为空".to_string());
+    }
+    let departments = load_departments(&db).await?;
+    if departments.iter().any(|d| d.name.eq_ignore_ascii_case(name)) {
+        return Err(format!("部门「{}」已存在", name));
+    }
+    if let Some(parent_id) = request.parent_id {
+        if !departments.iter().any(|d| d.id == parent_id) {
+            return Err("上级部门不存在".to_string());
+        }
+    }
+    if let Some(manager_id) = request.manager_id {
+        ensure_user_exists(&db, manager_id).await?;
+    }
+
+    let new_department = json!({
+        "name": name,
+        "parent_id": request.parent_id,
+        "manager_id": request.manager_id,
+    });
+
+    let department: Department = db
+        .post("departments", &new_department)
+        .await
+        .map_err(|e| format!("Failed to create department: {}", e))?;
+
+    let after = serde_json::to_value(&department).ok();
+    record_audit(&db, &session, "create_department", "departments", Some(department.id), None, after).await?;
+
+    Ok(department)
+}
+
+#[tauri::command]
+pub async fn update_department(
+    request: UpdateDepartmentRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let departments = load_departments(&db).await?;
+    if !departments.iter().any(|d| d.id == request.id) {
+        return Err("部门不存在".to_string());
+    }
+
+    let mut update_data = json!({});
+    if let Some(name) = &request.name {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("部门名称不能为空".to_string());
+        }
+        if departments.iter().any(|d| d.id != request.id && d.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("部门「{}」已存在", name));
+        }
+        update_data["name"] = json!(name);
+    }
+    if let Some(parent_id) = request.parent_id {
+        if let Some(parent_id) = parent_id {
+            if !departments.iter().any(|d| d.id == parent_id) {
+                return Err("上级部门不存在".to_string());
+            }
+            if creates_cycle(&departments, request.id, parent_id) {
+                return Err("不能将部门移动到它自己或下属团队之下".to_string());
+            }
+        }
+        update_data["parent_id"] = json!(parent_id);
+    }
+    if let Some(manager_id) = request.manager_id {
+        if let Some(manager_id) = manager_id {
+            ensure_user_exists(&db, manager_id).await?;
+        }
+        update_data["manager_id"] = json!(manager_id);
+    }
+
+    let before = snapshot(&db, "departments", request.id).await?;
+    db.patch(
+        "departments",
+        vec![("id", &format!("eq.{}", request.id))],
+        &update_data,
+    )
+    .await
+    .map_err(|e| format!("Failed to update department: {}", e))?;
+
+    let after = snapshot(&db, "departments", request.id).await?;
+    record_audit(&db, &session, "update_department", "departments", Some(request.id), before, after).await?;
+
     Ok(())
 }
+
+/// 仅在没有成员、下属团队和部门排班时允许删除
+#[tauri::command]
+pub async fn delete_department(
+    department_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let id_filter = format!("eq.{}", department_id);
+    let member_count = db
+        .count("users", &[("department_id", &id_filter)])
+        .await
+        .map_err(|e| format!("Failed to count users: {}", e))?;
+    let team_count = db
+        .count("departments", &[("parent_id", &id_filter)])
+        .await
+        .map_err(|e| format!("Failed to count departments: {}", e))?;
+    let rule_count = db
+        .count("time_rules", &[("department_id", &id_filter)])
+        .await
+        .map_err(|e| format!("Failed to count time rules: {}", e))?;
+
+    let mut references = Vec::new();
+    if member_count > 0 {
+        references.push(format!("{} 名成员", member_count));
+    }
+    if team_count > 0 {
+        references.push(format!("{} 个下属团队", team_count));
+    }
+    if rule_count > 0 {
+        references.push(format!("{} 条部门排班规则", rule_count));
+    }
+    if !references.is_empty() {
+        return Err(format!("该部门仍有 {}，无法删除", references.join("、")));
+    }
+
+    let before = snapshot(&db, "departments", department_id).await?;
+    db.delete("departments", vec![("id", &id_filter)])
+        .await
+        .map_err(|e| format!("Failed to delete department: {}", e))?;
+
+    record_audit(&db, &session, "delete_department", "departments", Some(department_id), before, None).await?;
+
+    Ok(())
+}
+
+/// 设置用户所属部门，传 None 表示移出部门
+#[tauri::command]
+pub async fn set_user_department(
+    user_id: i32,
+    department_id: Option<i32>,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageUsers)?;
+    if let Some(department_id) = department_id {
+        let departments = load_departments(&db).await?;
+        if !departments.iter().any(|d| d.id == department_id) {
+            return Err("部门不存在".to_string());
+        }
+    }
+
+    let before = snapshot(&db, "users", user_id).await?;
+    db.patch(
+        "users",
+        vec![("id", &format!("eq.{}", user_id))],
+        &json!({ "department_id": department_id }),
+    )
+    .await
+    .map_err(|e| format!("Failed to update user: {}", e))?;
+
+    let after = snapshot(&db, "users", user_id).await?;
+    record_audit(&db, &session, "set_user_department", "users", Some(user_id), before, after).await?;
+
+    Ok(())
+}
+
+// Holiday management
+
+/// Load holidays between two dates (inclusive, YYYY-MM-DD)
+pub(crate) async fn load_holidays(
+    db: &SupabaseClient,
+    start_date: &str,
+    end_date: &str,
+) -> Vec<Holiday> {
+    db.get(
+        "holidays",
+        Some(vec![
+            ("holiday_date", &format!("gte.{}", start_date)),
+            ("holiday_date", &format!("lte.{}", end_date)),
+        ]),
+    )
+    .await
+    .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_holidays(
+    year: Option<i32>,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<Holiday>, String> {
+    current_user(&session)?;
+    let mut params = vec![("order", "holiday_date.asc")];
+
+    let start_filter;
+    let end_filter;
+    if let Some(year) = year {
+        start_filter = format!("gte.{}-01-01", year);
+        end_filter = format!("lte.{}-12-31", year);
+        params.push(("holiday_date", &start_filter));
+        params.push(("holiday_date", &end_filter));
+    }
+
+    let holidays: Vec<Holiday> = db
+        .get("holidays", Some(params))
+        .await
+        .map_err(|e| format!("Failed to get holidays: {}", e))?;
+
+    Ok(holidays)
+}
+
+#[tauri::command]
+pub async fn create_holiday(
+    request: CreateHolidayRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Holiday, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    if chrono::NaiveDate::parse_from_str(&request.holiday_date, "%Y-%m-%d").is_err() {
+        return Err("日期格式错误，请使用 YYYY-MM-DD".to_string());
+    }
+
+    let new_holiday = json!({
+        "holiday_date": request.holiday_date,
+        "name": request.name,
+        "is_workday": request.is_workday.unwrap_or(false),
+    });
+
+    let holiday: Holiday = db
+        .post("holidays", &new_holiday)
+        .await
+        .map_err(|e| format!("Failed to create holiday: {}", e))?;
+
+    let after = serde_json::to_value(&holiday).ok();
+    record_audit(&db, &session, "create_holiday", "holidays", Some(holiday.id), None, after).await?;
+
+    Ok(holiday)
+}
+
+#[tauri::command]
+pub async fn delete_holiday(
+    holiday_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let before = snapshot(&db, "holidays", holiday_id).await?;
+    db.delete("holidays", vec![("id", &format!("eq.{}", holiday_id))])
+        .await
+        .map_err(|e| format!("Failed to delete holiday: {}", e))?;
+
+    record_audit(&db, &session, "delete_holiday", "holidays", Some(holiday_id), before, None).await?;
+
+    Ok(())
+}
+
+// Leave management
+
+/// Load leave records overlapping a date range, optionally for a single user
+pub(crate) async fn load_leaves(
+    db: &SupabaseClient,
+    user_id: Option<i32>,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<Leave>, String> {
+    let end_filter = format!("lte.{}", end_date);
+    let start_filter = format!("gte.{}", start_date);
+    let mut params = vec![
+        ("start_date", end_filter.as_str()),
+        ("end_date", start_filter.as_str()),
+        ("order", "start_date.asc"),
+    ];
+
+    let user_filter;
+    if let Some(uid) = user_id {
+        user_filter = format!("eq.{}", uid);
+        params.push(("user_id", &user_filter));
+    }
+
+    db.get_all("leaves", &params)
+        .await
+        .map_err(|e| format!("Failed to get leaves: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_leaves(
+    user_id: Option<i32>,
+    start_date: String,
+    end_date: String,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<Leave>, String> {
+    let scope = view_scope(&db, &session).await?;
+    if user_id.is_some_and(|id| !scope.allows(id)) {
+        return Err("没有权限查看该员工的记录".to_string());
+    }
+
+    let mut leaves = load_leaves(&db, user_id, &start_date, &end_date).await?;
+    leaves.retain(|leave| scope.allows(leave.user_id));
+    Ok(leaves)
+}
+
+#[tauri::command]
+pub async fn create_leave(
+    request: CreateLeaveRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Leave, String> {
+    ensure_can_edit(&db, &session, request.user_id).await?;
+    let start = chrono::NaiveDate::parse_from_str(&request.start_date, "%Y-%m-%d")
+        .map_err(|_| "开始日期格式错误，请使用 YYYY-MM-DD".to_string())?;
+    let end = chrono::NaiveDate::parse_from_str(&request.end_date, "%Y-%m-%d")
+        .map_err(|_| "结束日期格式错误，请使用 YYYY-MM-DD".to_string())?;
+    if end < start {
+        return Err("结束日期不能早于开始日期".to_string());
+    }
+
+    let new_leave = json!({
+        "user_id": request.user_id,
+        "start_date": request.start_date,
+        "end_date": request.end_date,
+        "leave_type": request.leave_type,
+        "note": request.note,
+    });
+
+    let leave: Leave = db
+        .post("leaves", &new_leave)
+        .await
+        .map_err(|e| format!("Failed to create leave: {}", e))?;
+
+    let after = serde_json::to_value(&leave).ok();
+    record_audit(&db, &session, "create_leave", "leaves", Some(leave.id), None, after).await?;
+
+    Ok(leave)
+}
+
+#[tauri::command]
+pub async fn delete_leave(
+    leave_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    let before = snapshot(&db, "leaves", leave_id).await?;
+    let owner = before.as_ref().and_then(|leave| leave["user_id"].as_i64());
+    let scope = edit_scope(&db, &session).await?;
+    if !owner.is_some_and(|id| scope.allows(id as i32)) {
+        return Err("没有权限修改该员工的记录".to_string());
+    }
+    db.delete("leaves", vec![("id", &format!("eq.{}", leave_id))])
+        .await
+        .map_err(|e| format!("Failed to delete leave: {}", e))?;
+
+    record_audit(&db, &session, "delete_leave", "leaves", Some(leave_id), before, None).await?;
+
+    Ok(())
+}
+
+// System config
+
+/// Load all system_config entries as a key/value map
+pub(crate) async fn load_system_config(db: &SupabaseClient) -> HashMap<String, String> {
+    let configs: Vec<SystemConfig> = db
+        .get("system_config", None)
+        .await
+        .unwrap_or_default();
+
+    configs
+        .into_iter()
+        .map(|c| (c.config_key, c.config_value))
+        .collect()
+}
+
+#[tauri::command]
+pub async fn get_system_configs(
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<SystemConfig>, String> {
+    current_user(&session)?;
+    let configs: Vec<SystemConfig> = db
+        .get("system_config", Some(vec![("order", "config_key.asc")]))
+        .await
+        .map_err(|e| format!("Failed to get system config: {}", e))?;
+
+    Ok(configs)
+}
+
+#[tauri::command]
+pub async fn update_system_config(
+    request: UpdateSystemConfigRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<SystemConfig, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let mut data = json!({
+        "config_key": request.config_key,
+        "config_value": request.config_value,
+        "updated_at": chrono::Utc::now().to_rfc3339(),
+    });
+    if let Some(desc) = request.config_desc {
+        data["config_desc"] = json!(desc);
+    }
+
+    let existing: Vec<SystemConfig> = db
+        .get("system_config", Some(vec![("config_key", &format!("eq.{}", request.config_key))]))
+        .await
+        .map_err(|e| format!("Failed to get system config: {}", e))?;
+    let before = existing.first().and_then(|c| serde_json::to_value(c).ok());
+
+    let config: SystemConfig = db
+        .upsert("system_config", "config_key", &data)
+        .await
+        .map_err(|e| format!("Failed to update system config: {}", e))?;
+
+    let after = serde_json::to_value(&config).ok();
+    record_audit(&db, &session, "update_system_config", "system_config", Some(config.id), before, after).await?;
+
+    Ok(config)
+}
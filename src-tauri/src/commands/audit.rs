@@ -0,0 +1,109 @@
+use crate::database::SupabaseClient;
+use crate::models::{AuditLogEntry, AuditLogQuery, PaginatedAuditLog, SECRET_USER_COLUMNS};
+use crate::session::Session;
+use crate::utils::permissions::Permission;
+use super::access::require;
+use tauri::State;
+use serde_json::{json, Value};
+
+const MAX_AUDIT_PAGE_SIZE: i32 = 200;
+
+fn redact(value: Option<Value>) -> Option<Value> {
+    value.map(|mut value| {
+        if let Some(fields) = value.as_object_mut() {
+            for field in SECRET_USER_COLUMNS {
+                fields.remove(*field);
+            }
+        }
+        value
+    })
+}
+
+/// Current state of one row, recorded as the before or after image of a change
+pub(crate) async fn snapshot(db: &SupabaseClient, table: &str, id: i32) -> Result<Option<Value>, String> {
+    let rows: Vec<Value> = db
+        .get(table, Some(vec![("id", &format!("eq.{}", id))]))
+        .await
+        .map_err(|e| format!("Failed to get {}: {}", table, e))?;
+    Ok(rows.into_iter().next())
+}
+
+/// Append an entry to the audit log for a change that has been saved
+pub(crate) async fn record_audit(
+    db: &SupabaseClient,
+    session: &Session,
+    action: &str,
+    target_table: &str,
+    target_id: Option<i32>,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> Result<(), String> {
+    let actor = session.current();
+    let entry = json!({
+        "actor_id": actor.as_ref().map(|u| u.id),
+        "actor_name": actor.as_ref().map(|u| u.username.clone()),
+        "action": action,
+        "target_table": target_table,
+        "target_id": target_id,
+        "before_data": redact(before),
+        "after_data": redact(after),
+    });
+
+    db.post::<_, Value>("audit_log", &entry)
+        .await
+        .map_err(|e| format!("操作已保存，但审计日志写入失败: {}", e))?;
+
+    Ok(())
+}
+
+/// 按执行人、操作、目标和日期筛选审计日志，按时间倒序分页
+#[tauri::command]
+pub async fn get_audit_log(
+    query: AuditLogQuery,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<PaginatedAuditLog, String> {
+    require(&session, Permission::ViewAuditLog)?;
+    let page = query.page.max(1);
+    let page_size = query.page_size.clamp(1, MAX_AUDIT_PAGE_SIZE);
+
+    let actor_filter = query.actor_id.map(|id| format!("eq.{}", id));
+    let action_filter = query.action.as_ref().map(|a| format!("eq.{}", a));
+    let table_filter = query.target_table.as_ref().map(|t| format!("eq.{}", t));
+    let target_filter = query.target_id.map(|id| format!("eq.{}", id));
+    let start_filter = query.start_date.as_ref().map(|d| format!("gte.{}T00:00:00", d));
+    let end_filter = query.end_date.as_ref().map(|d| format!("lte.{}T23:59:59", d));
+
+    let mut params: Vec<(&str, &str)> = Vec::new();
+    for (column, filter) in [
+        ("actor_id", &actor_filter),
+        ("action", &action_filter),
+        ("target_table", &table_filter),
+        ("target_id", &target_filter),
+        ("created_at", &start_filter),
+        ("created_at", &end_filter),
+    ] {
+        if let Some(filter) = filter {
+            params.push((column, filter));
+        }
+    }
+
+    let total = db
+        .count("audit_log", &params)
+        .await
+        .map_err(|e| format!("Failed to count audit log: {}", e))?;
+
+    params.push(("order", "created_at.desc,id.desc"));
+    let data: Vec<AuditLogEntry> = db
+        .get_page("audit_log", &params, ((page - 1) * page_size) as usize, page_size as usize)
+        .await
+        .map_err(|e| format!("Failed to get audit log: {}", e))?;
+
+    Ok(PaginatedAuditLog {
+        data,
+        total,
+        page,
+        page_size,
+        total_pages: ((total as f32) / (page_size as f32)).ceil() as i32,
+    })
+}
@@ -1,14 +1,53 @@
+use crate::database::realtime::RealtimeListener;
 use crate::database::SupabaseClient;
-use crate::models::{User, LoginRequest, RegisterRequest, LoginResponse};
+use crate::models::{Role, User, ApprovalStatus, LoginRequest, RegisterRequest, LoginResponse, LoginResult, ChangePasswordRequest, PasswordPolicy};
+use crate::session::Session;
 use crate::utils::crypto::{hash_password, verify_password};
-use tauri::State;
+use crate::utils::password::{check_password, password_expired};
+use crate::utils::profile::{normalize_text, validate_email};
+use crate::utils::registration::{check_registration, normalize_invite_code};
+use super::admin::load_system_config;
+use super::audit::{record_audit, snapshot};
+use super::registration::{consume_invite_code, load_registration_settings};
+use super::throttle::{check_login_throttle, clear_login_failures, record_login_failure};
+use super::two_factor::apply_two_factor_requirement;
+use tauri::{AppHandle, Manager, State};
 use serde_json::json;
 
+pub(crate) async fn load_password_policy(db: &SupabaseClient) -> PasswordPolicy {
+    PasswordPolicy::from_config(&load_system_config(db).await)
+}
+
+/// Open the session once every login check has passed
+pub(crate) async fn finish_login(
+    app: &AppHandle,
+    db: &SupabaseClient,
+    session: &Session,
+    mut user: User,
+) -> LoginResponse {
+    apply_two_factor_requirement(db, &mut user).await;
+
+    // 只有能查看其他员工考勤的角色需要实时推送
+    if user.role != Role::Employee {
+        app.state::<RealtimeListener>().ensure_started(app, db);
+    }
+
+    // Generate simple token (in production, use JWT)
+    let token = format!("{}_{}", user.id, user.username);
+    session.set(user.clone());
+
+    LoginResponse { user, token }
+}
+
 #[tauri::command]
 pub async fn login(
     credentials: LoginRequest,
+    app: AppHandle,
     db: State<'_, SupabaseClient>,
-) -> Result<LoginResponse, String> {
+    session: State<'_, Session>,
+) -> Result<LoginResult, String> {
+    let throttles = check_login_throttle(&db, &credentials).await?;
+
     // Get user by username
     let users: Vec<User> = db
         .get(
@@ -18,28 +57,82 @@ pub async fn login(
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
-    if users.is_empty() {
+    // Verify password
+    let Some(mut user) = users
+        .into_iter()
+        .next()
+        .filter(|u| verify_password(&credentials.password, &u.password_hash))
+    else {
+        record_login_failure(&db, &session, &throttles).await?;
         return Err("用户名或密码错误".to_string());
+    };
+
+    if !user.is_active {
+        return Err("账号已停用，请联系管理员".to_string());
+    }
+    match user.approval_status {
+        ApprovalStatus::Pending => return Err("注册申请正在等待管理员审批".to_string()),
+        ApprovalStatus::Rejected => return Err("注册申请未通过，请联系管理员".to_string()),
+        ApprovalStatus::Approved => {}
     }
 
-    let user = users[0].clone();
+    let now = chrono::Utc::now();
+    let temporary_expired = user
+        .temporary_password_expires_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .is_some_and(|expires_at| expires_at < now);
+    if user.must_change_password && temporary_expired {
+        return Err("临时密码已过期，请联系管理员重新重置".to_string());
+    }
 
-    // Verify password
-    if !verify_password(&credentials.password, &user.password_hash) {
-        return Err("用户名或密码错误".to_string());
+    // 密码超过有效期时要求登录后立即修改
+    let policy = load_password_policy(&db).await;
+    if !user.must_change_password && password_expired(&policy, user.password_changed_at.as_deref(), now) {
+        db.patch(
+            "users",
+            vec![("id", &format!("eq.{}", user.id))],
+            &json!({ "must_change_password": true }),
+        )
+        .await
+        .map_err(|e| format!("Failed to update user: {}", e))?;
+        user.must_change_password = true;
     }
 
-    // Generate simple token (in production, use JWT)
-    let token = format!("{}_{}", user.id, user.username);
+    // 两步验证通过后才清除失败计数，避免用正确的密码重置验证码的尝试次数
+    if user.totp_enabled {
+        session.start_pending_login(&user, credentials.device_id.clone());
+        return Ok(LoginResult::TwoFactorRequired);
+    }
+    clear_login_failures(&db, &throttles).await?;
 
-    Ok(LoginResponse { user, token })
+    Ok(LoginResult::SignedIn(Box::new(finish_login(&app, &db, &session, user).await)))
+}
+
+#[tauri::command]
+pub async fn logout(session: State<'_, Session>) -> Result<(), String> {
+    session.clear();
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn register(
     data: RegisterRequest,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<User, String> {
+    let settings = load_registration_settings(&db).await;
+    let invite_code = data
+        .invite_code
+        .as_deref()
+        .map(normalize_invite_code)
+        .filter(|c| !c.is_empty());
+    let email = normalize_text(data.email);
+    if let Some(email) = &email {
+        validate_email(email)?;
+    }
+    check_registration(&settings, email.as_deref(), invite_code.is_some())?;
+
     // Check if username exists
     let existing: Vec<User> = db
         .get(
@@ -53,6 +146,20 @@ pub async fn register(
         return Err("用户名已存在".to_string());
     }
 
+    check_password(&load_password_policy(&db).await, &data.password, &data.username)?;
+
+    // 其余检查都通过后才占用邀请码
+    let invite = match &invite_code {
+        Some(code) => Some(consume_invite_code(&db, code).await?),
+        None => None,
+    };
+    // 凭邀请码注册的账号无须审批
+    let approval_status = if invite.is_none() && settings.requires_approval {
+        ApprovalStatus::Pending
+    } else {
+        ApprovalStatus::Approved
+    };
+
     // Hash password
     let password_hash = hash_password(&data.password);
 
@@ -62,6 +169,9 @@ pub async fn register(
         "password_hash": password_hash,
         "full_name": data.full_name,
         "is_admin": false,
+        "email": email,
+        "department_id": invite.as_ref().and_then(|i| i.department_id),
+        "approval_status": approval_status,
     });
 
     let user: User = db
@@ -69,6 +179,9 @@ pub async fn register(
         .await
         .map_err(|e| format!("Failed to create user: {}", e))?;
 
+    let after = serde_json::to_value(&user).ok();
+    record_audit(&db, &session, "register", "users", Some(user.id), None, after).await?;
+
     Ok(user)
 }
 
@@ -76,14 +189,91 @@ pub async fn register(
 pub async fn get_current_user(
     user_id: i32,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<User, String> {
+    // 只能刷新本应用实例中已登录的账号，应用重启后需重新登录
+    if session.current().map(|u| u.id) != Some(user_id) {
+        return Err("登录已失效，请重新登录".to_string());
+    }
+
     let users: Vec<User> = db
         .get("users", Some(vec![("id", &format!("eq.{}", user_id))]))
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
-    users
+    let mut user = users
         .into_iter()
         .next()
-        .ok_or_else(|| "User not found".to_string())
+        .ok_or_else(|| "User not found".to_string())?;
+
+    // 停用后已登录的会话在下次校验时失效
+    if !user.is_active {
+        session.clear();
+        return Err("账号已停用，请联系管理员".to_string());
+    }
+
+    // 角色或部门变更后立即生效
+    apply_two_factor_requirement(&db, &mut user).await;
+    session.set(user.clone());
+
+    Ok(user)
+}
+
+/// 当前密码策略，用于修改密码和注册表单的提示
+#[tauri::command]
+pub async fn get_password_policy(
+    db: State<'_, SupabaseClient>,
+) -> Result<PasswordPolicy, String> {
+    Ok(load_password_policy(&db).await)
+}
+
+/// 修改自己的密码：校验旧密码和密码策略，完成后解除"须修改密码"状态
+#[tauri::command]
+pub async fn change_password(
+    request: ChangePasswordRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<User, String> {
+    // 须修改密码的账号也能调用，不经过 current_user 的检查
+    let user_id = session.current().map(|u| u.id).ok_or_else(|| "请先登录".to_string())?;
+    let users: Vec<User> = db
+        .get("users", Some(vec![("id", &format!("eq.{}", user_id))]))
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let user = users.into_iter().next().ok_or_else(|| "User not found".to_string())?;
+
+    if !verify_password(&request.old_password, &user.password_hash) {
+        return Err("原密码错误".to_string());
+    }
+    if request.new_password == request.old_password {
+        return Err("新密码不能与原密码相同".to_string());
+    }
+    check_password(&load_password_policy(&db).await, &request.new_password, &user.username)?;
+
+    let before = snapshot(&db, "users", user_id).await?;
+    db.patch(
+        "users",
+        vec![("id", &format!("eq.{}", user_id))],
+        &json!({
+            "password_hash": hash_password(&request.new_password),
+            "must_change_password": false,
+            "password_changed_at": chrono::Utc::now().to_rfc3339(),
+            "temporary_password_expires_at": null,
+        }),
+    )
+    .await
+    .map_err(|e| format!("Failed to change password: {}", e))?;
+
+    let after = snapshot(&db, "users", user_id).await?;
+    record_audit(&db, &session, "change_password", "users", Some(user_id), before, after).await?;
+
+    let users: Vec<User> = db
+        .get("users", Some(vec![("id", &format!("eq.{}", user_id))]))
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let mut user = users.into_iter().next().ok_or_else(|| "User not found".to_string())?;
+    apply_two_factor_requirement(&db, &mut user).await;
+    session.set(user.clone());
+
+    Ok(user)
 }
@@ -0,0 +1,324 @@
+use crate::backup::archive::{BackupWriter, ExtractedBackup};
+use crate::backup::sqlite::restore_to_sqlite;
+use crate::backup::{BackupTable, BACKUP_TABLES};
+use crate::database::SupabaseClient;
+use crate::models::SECRET_USER_COLUMNS;
+use crate::session::Session;
+use crate::utils::crypto::{generate_password, hash_password};
+use crate::utils::permissions::Permission;
+use super::access::require;
+use super::audit::record_audit;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{State, Window};
+
+/// Rows fetched per request while writing a backup
+const BACKUP_PAGE_SIZE: usize = 1000;
+
+/// Rows staged per request while restoring into Supabase
+const RESTORE_BATCH_SIZE: usize = 500;
+
+pub const BACKUP_PROGRESS_EVENT: &str = "backup-progress";
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBackupRequest {
+    pub path: String,
+    /// 默认不备份密码哈希和两步验证密钥，恢复后需要重置密码并重新绑定
+    #[serde(default, alias = "include_password_hashes")]
+    pub include_secrets: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RestoreTarget {
+    /// 当前连接的 Supabase 项目，必须没有用户和打卡数据
+    Supabase,
+    /// 新建的本地 SQLite 数据库文件
+    Sqlite { path: String },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreBackupRequest {
+    pub path: String,
+    pub target: RestoreTarget,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupPhase {
+    Backup,
+    Verify,
+    Restore,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupProgress {
+    pub phase: BackupPhase,
+    pub table: String,
+    pub rows: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupResult {
+    pub path: String,
+    pub tables: Vec<BackupTable>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreResult {
+    pub tables: Vec<BackupTable>,
+    /// 备份不含密码哈希时需要管理员重置密码的用户数
+    pub users_without_password: usize,
+    /// 数据已恢复但审计日志写入失败，不应重新恢复
+    pub audit_error: Option<String>,
+}
+
+fn emit_progress(window: &Window, phase: BackupPhase, table: &str, rows: usize, total: usize) {
+    let _ = window.emit(
+        BACKUP_PROGRESS_EVENT,
+        BackupProgress {
+            phase,
+            table: table.to_string(),
+            rows,
+            total,
+        },
+    );
+}
+
+/// Columns pointing at rows that may not be restored yet (the same table, or
+/// users for department managers); `apply_backup_restore` fills them in after all tables
+fn deferred_columns(table: &str) -> &'static [&'static str] {
+    match table {
+        "departments" => &["parent_id", "manager_id"],
+        "action_types" => &["pair_action_id"],
+        "check_ins" => &["pair_check_in_id"],
+        _ => &[],
+    }
+}
+
+/// 备份全部数据到本地压缩文件，每张表附带 SHA-256 校验
+#[tauri::command]
+pub async fn create_backup(
+    request: CreateBackupRequest,
+    window: Window,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<BackupResult, String> {
+    require(&session, Permission::ManageBackups)?;
+    let result = write_backup(&request, &window, &db).await;
+    if result.is_err() {
+        // 不留下不完整的备份文件
+        let _ = std::fs::remove_file(&request.path);
+    }
+    result
+}
+
+async fn write_backup(
+    request: &CreateBackupRequest,
+    window: &Window,
+    db: &SupabaseClient,
+) -> Result<BackupResult, String> {
+    let mut writer = BackupWriter::create(&request.path)?;
+    let limit = BACKUP_PAGE_SIZE.to_string();
+
+    for table in BACKUP_TABLES {
+        let total = db
+            .count(table, &[])
+            .await
+            .map_err(|e| format!("Failed to count {}: {}", table, e))?;
+        writer.start_table(table)?;
+        emit_progress(window, BackupPhase::Backup, table, 0, total);
+
+        // 按 id 递增翻页，备份过程中新增的记录不会导致重复或遗漏
+        let mut last_id: Option<i64> = None;
+        let mut written = 0;
+        loop {
+            let id_filter = last_id.map(|id| format!("gt.{}", id));
+            let mut params = vec![("select", "*"), ("order", "id.asc"), ("limit", limit.as_str())];
+            if let Some(filter) = &id_filter {
+                params.push(("id", filter));
+            }
+
+            let mut page: Vec<Value> = db
+                .get(table, Some(params))
+                .await
+                .map_err(|e| format!("Failed to get {}: {}", table, e))?;
+            for row in &mut page {
+                if *table == "users" && !request.include_secrets {
+                    if let Some(user) = row.as_object_mut() {
+                        for column in SECRET_USER_COLUMNS {
+                            user.remove(*column);
+                        }
+                    }
+                }
+                last_id = row.get("id").and_then(Value::as_i64);
+                writer.write_row(row)?;
+            }
+            written += page.len();
+            emit_progress(window, BackupPhase::Backup, table, written, total.max(written));
+
+            if page.len() < BACKUP_PAGE_SIZE || last_id.is_none() {
+                break;
+            }
+        }
+    }
+
+    let manifest = writer.finish(chrono::Utc::now().to_rfc3339(), request.include_secrets)?;
+    Ok(BackupResult {
+        path: request.path.clone(),
+        tables: manifest.tables,
+    })
+}
+
+/// 校验备份文件后恢复到空的 Supabase 项目或新的 SQLite 文件
+#[tauri::command]
+pub async fn restore_backup(
+    request: RestoreBackupRequest,
+    window: Window,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<RestoreResult, String> {
+    // 空项目还没有任何账号可以登录，此时允许直接恢复
+    let user_count = db
+        .count("users", &[])
+        .await
+        .map_err(|e| format!("Failed to count users: {}", e))?;
+    if user_count > 0 {
+        require(&session, Permission::ManageBackups)?;
+    }
+
+    let dir = std::env::temp_dir().join(format!(
+        "checkin-restore-{}",
+        chrono::Utc::now().timestamp_millis()
+    ));
+    // 先完整校验，校验失败时不写入任何数据
+    let backup = ExtractedBackup::open(&request.path, &dir, |table, rows| {
+        emit_progress(&window, BackupPhase::Verify, table, rows, rows)
+    })?;
+
+    let (target, users_without_password) = match &request.target {
+        RestoreTarget::Sqlite { path } => {
+            restore_to_sqlite(&backup, path, |table, rows, total| {
+                emit_progress(&window, BackupPhase::Restore, table, rows, total)
+            })?;
+            let users_without_password = match backup.manifest.table("users") {
+                Some(users) if !backup.manifest.includes_secrets => users.rows,
+                _ => 0,
+            };
+            ("sqlite", users_without_password)
+        }
+        RestoreTarget::Supabase => ("supabase", restore_to_supabase(&backup, &window, &db).await?),
+    };
+
+    // 恢复到空项目时还没有登录用户，执行人记为空
+    let summary = json!({
+        "target": target,
+        "created_at": backup.manifest.created_at,
+        "tables": backup
+            .manifest
+            .tables
+            .iter()
+            .map(|t| (t.name.clone(), json!(t.rows)))
+            .collect::<serde_json::Map<_, _>>(),
+        "users_without_password": users_without_password,
+    });
+    let audit_error = record_audit(&db, &session, "restore_backup", "backups", None, None, Some(summary))
+        .await
+        .err();
+
+    Ok(RestoreResult {
+        tables: backup.manifest.tables.clone(),
+        users_without_password,
+        audit_error,
+    })
+}
+
+/// Stages every table in `backup_restore_rows`, then writes them all in one
+/// transaction with `apply_backup_restore`. A failed restore leaves the project
+/// empty; restoring again clears whatever was staged before.
+/// Returns the number of users restored without a password hash
+async fn restore_to_supabase(
+    backup: &ExtractedBackup,
+    window: &Window,
+    db: &SupabaseClient,
+) -> Result<usize, String> {
+    for table in ["users", "check_ins"] {
+        let existing = db
+            .count(table, &[])
+            .await
+            .map_err(|e| format!("Failed to count {}: {}", table, e))?;
+        if existing > 0 {
+            return Err("目标项目已有用户或打卡数据，只能恢复到空项目".to_string());
+        }
+    }
+    db.rpc::<_, i32>("begin_backup_restore", &json!({}))
+        .await
+        .map_err(|e| format!("Failed to start restore: {}", e))?;
+
+    let mut users_without_password = 0;
+    let mut tables = Vec::new();
+    for name in BACKUP_TABLES {
+        let Some(table) = backup.manifest.table(name) else {
+            continue;
+        };
+        let mut batch = Vec::with_capacity(RESTORE_BATCH_SIZE);
+        let mut staged = 0;
+        emit_progress(window, BackupPhase::Restore, name, 0, table.rows);
+
+        for row in backup.rows(name)? {
+            let mut row = row?;
+            let Some(fields) = row.as_object_mut() else {
+                continue;
+            };
+            if *name == "system_config" {
+                fields.remove("id");
+            }
+            if *name == "users" && fields.get("password_hash").and_then(Value::as_str).is_none() {
+                // 随机密码无人知晓，需管理员重置后才能登录
+                fields.insert("password_hash".to_string(), json!(hash_password(&generate_password(32))));
+                fields.insert("must_change_password".to_string(), json!(true));
+                users_without_password += 1;
+            }
+            // 没有两步验证密钥时关闭两步验证，管理员登录后会被要求重新绑定
+            if *name == "users" && fields.get("totp_secret").and_then(Value::as_str).is_none() {
+                fields.insert("totp_enabled".to_string(), json!(false));
+                fields.insert("totp_recovery_codes".to_string(), json!([]));
+            }
+            // 旧版本备份没有角色，按管理员标记恢复
+            if *name == "users" && !fields.contains_key("role") {
+                let is_admin = fields.get("is_admin").and_then(Value::as_bool).unwrap_or(false);
+                fields.insert("role".to_string(), json!(if is_admin { "super_admin" } else { "employee" }));
+            }
+
+            batch.push(json!({ "table_name": name, "row_no": staged + batch.len(), "row_data": row }));
+            if batch.len() == RESTORE_BATCH_SIZE {
+                stage_batch(db, name, &mut batch).await?;
+                staged += RESTORE_BATCH_SIZE;
+                emit_progress(window, BackupPhase::Restore, name, staged, table.rows);
+            }
+        }
+        staged += batch.len();
+        stage_batch(db, name, &mut batch).await?;
+        emit_progress(window, BackupPhase::Restore, name, staged, table.rows);
+        tables.push(json!({ "name": name, "deferred": deferred_columns(name) }));
+    }
+
+    // 所有表在同一事务中写入，失败时整体回滚
+    db.rpc::<_, Value>("apply_backup_restore", &json!({ "tables": tables }))
+        .await
+        .map_err(|e| format!("Failed to restore backup: {}", e))?;
+
+    Ok(users_without_password)
+}
+
+async fn stage_batch(db: &SupabaseClient, table: &str, batch: &mut Vec<Value>) -> Result<(), String> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+    db.upsert_many("backup_restore_rows", "table_name,row_no", batch)
+        .await
+        .map_err(|e| format!("Failed to restore {}: {}", table, e))?;
+    batch.clear();
+    Ok(())
+}
@@ -1,6 +1,18 @@
 use crate::database::SupabaseClient;
-use crate::models::{CheckIn, CheckInRequest, ManualCheckInRequest, ActionType, TimeRule};
+use crate::models::{CheckIn, CheckInRequest, CheckInResponse, ManualCheckInRequest, ActionType, Department, TimeRule, OvertimeCategory, OvertimeSettings, User};
 use crate::utils::time::{get_company_time, format_time, is_late_strict, calculate_duration, parse_time, is_early_leave};
+use crate::utils::quota::{summarize_break_usage, break_quota_warnings, is_overtime_break, BreakUsage};
+use crate::utils::overtime::{classify_day, calculate_overtime};
+use crate::utils::worktime::{break_minutes, net_work_minutes};
+use crate::utils::attendance::employee_schedule;
+use crate::utils::department::employee_rule;
+use crate::session::Session;
+use super::admin::{load_departments, load_holidays, load_system_config};
+use super::access::{current_user, ensure_can_edit, ensure_can_view};
+use super::audit::{record_audit, snapshot};
+use super::profile::load_user;
+use chrono::DateTime;
+use chrono_tz::Tz;
 use tauri::State;
 use serde_json::json;
 use serde::{Deserialize, Serialize};
@@ -13,11 +25,171 @@ pub struct UpdateCheckInRequest {
     pub note: Option<String>,
 }
 
+/// 员工、启用的规则、打卡类型和部门，用于按员工解析时间规则
+async fn load_rule_reference(
+    db: &SupabaseClient,
+    user_id: i32,
+) -> Option<(User, Vec<TimeRule>, Vec<ActionType>, Vec<Department>)> {
+    let users: Vec<User> = db
+        .get("users", Some(vec![("id", &format!("eq.{}", user_id))]))
+        .await
+        .unwrap_or_default();
+    let user = users.into_iter().next()?;
+
+    let rules: Vec<TimeRule> = db
+        .get("time_rules", Some(vec![("is_active", "eq.true")]))
+        .await
+        .unwrap_or_default();
+    let action_types: Vec<ActionType> = db
+        .get("action_types", None)
+        .await
+        .unwrap_or_default();
+    let departments = if rules.iter().any(|rule| rule.department_id.is_some()) {
+        load_departments(db).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    Some((user, rules, action_types, departments))
+}
+
+/// 获取打卡类型对该员工生效的时间规则：员工的默认排班优先，其次是所在部门（含上级部门）的排班，最后是全公司规则
+async fn get_active_time_rule(db: &SupabaseClient, action_type_id: i32, user_id: i32) -> Option<TimeRule> {
+    let (user, rules, action_types, departments) = load_rule_reference(db, user_id).await?;
+    employee_rule(&rules, &action_types, &departments, &user, action_type_id).cloned()
+}
+
+/// 员工的作息规则（决定休息日和加班起算时间），与考勤统计使用同一规则
+async fn get_schedule_rule(db: &SupabaseClient, user_id: i32) -> Option<TimeRule> {
+    let (user, rules, action_types, departments) = load_rule_reference(db, user_id).await?;
+    employee_schedule(&rules, &action_types, &departments, &user).cloned()
+}
+
+/// 计算一个班次的加班时长及类别（工作日延时 / 休息日 / 节假日）
+async fn evaluate_overtime(
+    db: &SupabaseClient,
+    schedule_rule: Option<&TimeRule>,
+    start_time: &DateTime<Tz>,
+    end_time: &DateTime<Tz>,
+    worked_minutes: i32,
+) -> Option<(i32, OvertimeCategory)> {
+    let settings = OvertimeSettings::from_config(&load_system_config(db).await);
+    let work_date = start_time.date_naive().format("%Y-%m-%d").to_string();
+    let holidays = load_holidays(db, &work_date, &work_date).await;
+
+    let rest_days = schedule_rule
+        .and_then(|rule| rule.rest_days.clone())
+        .unwrap_or_default();
+    let day_kind = classify_day(start_time.date_naive(), &rest_days, &holidays);
+
+    calculate_overtime(
+        start_time,
+        end_time,
+        schedule_rule.and_then(|rule| rule.expected_start_time.as_deref()),
+        schedule_rule.and_then(|rule| rule.expected_end_time.as_deref()),
+        worked_minutes,
+        day_kind,
+        &settings,
+    )
+}
+
+/// 计算班次净工作时长：扣除班次内不带薪的临时事件
+async fn calculate_shift_net_minutes(
+    db: &SupabaseClient,
+    start_checkin: &CheckIn,
+    end_time_str: &str,
+    gross_minutes: i32,
+    all_action_types: &[ActionType],
+) -> i32 {
+    let break_action_ids: Vec<String> = all_action_types
+        .iter()
+        .filter(|at| at.action_role == 3)
+        .map(|at| at.id.to_string())
+        .collect();
+    if break_action_ids.is_empty() {
+        return gross_minutes;
+    }
+
+    let breaks: Vec<CheckIn> = db
+        .get(
+            "check_ins",
+            Some(vec![
+                ("user_id", &format!("eq.{}", start_checkin.user_id)),
+                ("action_type_id", &format!("in.({})", break_action_ids.join(","))),
+                ("status", "eq.completed"),
+                ("check_time", &format!("gte.{}", start_checkin.check_time)),
+                ("check_time", &format!("lte.{}", end_time_str)),
+            ]),
+        )
+        .await
+        .unwrap_or_default();
+
+    let (_, unpaid_minutes) = break_minutes(&breaks, all_action_types);
+    net_work_minutes(gross_minutes, unpaid_minutes)
+}
+
+/// 查询临时事件开始记录当天同类事件的使用情况（不含该记录本身）
+async fn get_break_usage(
+    db: &SupabaseClient,
+    start_checkin: &CheckIn,
+    timezone: &str,
+) -> BreakUsage {
+    let start_date = match parse_time(&start_checkin.check_time, timezone) {
+        Some(start_time) => start_time.date_naive(),
+        None => return BreakUsage::default(),
+    };
+    let day_start = format!("{}T00:00:00", start_date.format("%Y-%m-%d"));
+    let day_end = format!("{}T23:59:59", start_date.format("%Y-%m-%d"));
+
+    let same_day_breaks: Vec<CheckIn> = db
+        .get(
+            "check_ins",
+            Some(vec![
+                ("user_id", &format!("eq.{}", start_checkin.user_id)),
+                ("action_type_id", &format!("eq.{}", start_checkin.action_type_id)),
+                ("check_time", &format!("gte.{}", day_start)),
+                ("check_time", &format!("lte.{}", day_end)),
+            ]),
+        )
+        .await
+        .unwrap_or_default();
+
+    summarize_break_usage(&same_day_breaks, start_checkin.action_type_id, Some(start_checkin.id))
+}
+
+/// 检查临时事件是否超时：单次超过最大时长，或超出当日次数/累计时长额度
+async fn check_overtime_break(
+    db: &SupabaseClient,
+    start_checkin: &CheckIn,
+    duration: i32,
+    timezone: &str,
+) -> bool {
+    // 需要获取临时事件开始时的time_rule
+    match get_active_time_rule(db, start_checkin.action_type_id, start_checkin.user_id).await {
+        Some(temp_rule) => {
+            let usage = get_break_usage(db, start_checkin, timezone).await;
+            is_overtime_break(&temp_rule, &usage, duration)
+        }
+        None => false,
+    }
+}
+
 #[tauri::command]
 pub async fn create_check_in(
     request: CheckInRequest,
     db: State<'_, SupabaseClient>,
-) -> Result<CheckIn, String> {
+    session: State<'_, Session>,
+) -> Result<CheckInResponse, String> {
+    // 实时打卡只能为自己打，为他人补卡使用补打卡
+    if current_user(&session)?.id != request.user_id {
+        return Err("只能为自己打卡".to_string());
+    }
+    // 会话中的用户可能已过期，停用后已登录的会话不能继续打卡
+    if !load_user(&db, request.user_id).await?.is_active {
+        session.clear();
+        return Err("账号已停用，请联系管理员".to_string());
+    }
+
     // Get action type
     let action_types: Vec<ActionType> = db
         .get(
@@ -38,18 +210,8 @@ pub async fn create_check_in(
     let check_time_str = format_time(&check_time);
 
     // Get time rules for this action type
-    let time_rules: Vec<TimeRule> = db
-        .get(
-            "time_rules",
-            Some(vec![
-                ("action_type_id", &format!("eq.{}", request.action_type_id)),
-                ("is_active", "eq.true"),
-            ]),
-        )
-        .await
-        .unwrap_or_default();
-
-    let time_rule = time_rules.first();
+    let time_rule = get_active_time_rule(&db, request.action_type_id, request.user_id).await;
+    let time_rule = time_rule.as_ref();
 
     // Check for ongoing check-ins
     let ongoing: Vec<CheckIn> = db
@@ -209,6 +371,19 @@ pub async fn create_check_in(
         }
     }
 
+    // 临时事件开始：检查当日额度，即将超额时提醒
+    let mut warnings = Vec::new();
+    let mut is_overtime_break_flag = false;
+    if action_type.action_role == 3 {
+        if let Some(rule) = time_rule {
+            let usage = summarize_break_usage(&today_main_checkins, action_type.id, None);
+            warnings = break_quota_warnings(rule, &action_type.button_text, &usage);
+            if let Some(max_count) = rule.daily_max_count {
+                is_overtime_break_flag = usage.count >= max_count;
+            }
+        }
+    }
+
     // Determine status based on action_role
     let status = match action_type.action_role {
         1 | 3 => "ongoing",  // Start actions
@@ -237,6 +412,8 @@ pub async fn create_check_in(
     let mut pair_check_in_id = None;
     let mut duration_minutes_value = None;
     let mut is_early_leave_flag = false;
+    let mut net_minutes_value = None;
+    let mut overtime = None;
     
     if is_end_action {
         // 查找配对的开始记录
@@ -288,27 +465,26 @@ pub async fn create_check_in(
                             is_early_leave_flag = is_early_leave(&start_time, &check_time, expected_end, timezone);
                         }
                     }
+
+                    // 净工作时长：扣除不带薪的临时事件
+                    let net_minutes = calculate_shift_net_minutes(
+                        &db,
+                        start_checkin,
+                        &check_time_str,
+                        duration,
+                        &all_action_types,
+                    )
+                    .await;
+                    net_minutes_value = Some(net_minutes);
+
+                    // 计算加班：按员工的作息规则判断休息日，与考勤统计一致
+                    let schedule_rule = get_schedule_rule(&db, start_checkin.user_id).await;
+                    overtime = evaluate_overtime(&db, schedule_rule.as_ref(), &start_time, &check_time, net_minutes).await;
                 } else if action_type.action_role == 4 {
-                    // 临时事件结束（回座）：检查是否超时
-                    // 需要获取临时事件开始时的time_rule
-                    let temp_event_time_rules: Vec<TimeRule> = db
-                        .get(
-                            "time_rules",
-                            Some(vec![
-                                ("action_type_id", &format!("eq.{}", start_checkin.action_type_id)),
-                                ("is_active", "eq.true"),
-                            ]),
-                        )
-                        .await
-                        .unwrap_or_default();
-
-                    if let Some(temp_rule) = temp_event_time_rules.first() {
-                        if let Some(max_duration) = temp_rule.max_duration_minutes {
-                            // 超过最大允许时长，标记为迟到（超时）
-                            if duration > max_duration {
-                                is_late_flag = true;
-                            }
-                        }
+                    // 临时事件结束（回座）：检查是否超时或超出当日额度
+                    is_overtime_break_flag = check_overtime_break(&db, start_checkin, duration, timezone).await;
+                    if is_overtime_break_flag {
+                        warnings.push("本次临时事件已超时或超出今日额度".to_string());
                     }
                 }
             } else {
@@ -325,9 +501,13 @@ pub async fn create_check_in(
         "status": status,
         "is_late": is_late_flag,
         "is_early_leave": is_early_leave_flag,
+        "is_overtime_break": is_overtime_break_flag,
+        "overtime_minutes": overtime.map(|(minutes, _)| minutes),
+        "overtime_category": overtime.map(|(_, category)| category),
         "is_manual": false,
         "pair_check_in_id": pair_check_in_id,
         "duration_minutes": duration_minutes_value,
+        "net_minutes": net_minutes_value,
     });
 
     let checkin: CheckIn = db
@@ -341,6 +521,7 @@ pub async fn create_check_in(
             "status": "completed",
             "pair_check_in_id": checkin.id,
             "duration_minutes": duration_minutes_value,
+            "is_overtime_break": is_overtime_break_flag,
         });
 
         let _ = db
@@ -352,14 +533,19 @@ pub async fn create_check_in(
             .await;
     }
 
-    Ok(checkin)
+    let after = serde_json::to_value(&checkin).ok();
+    record_audit(&db, &session, "create_check_in", "check_ins", Some(checkin.id), None, after).await?;
+
+    Ok(CheckInResponse { check_in: checkin, warnings })
 }
 
 #[tauri::command]
 pub async fn get_today_check_ins(
     user_id: i32,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<Vec<CheckIn>, String> {
+    ensure_can_view(&db, &session, user_id).await?;
     let timezone = "Asia/Phnom_Penh";
     let today_start = get_company_time(timezone)
         .date_naive()
@@ -407,7 +593,9 @@ pub async fn get_today_check_ins(
 pub async fn create_manual_check_in(
     request: ManualCheckInRequest,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<CheckIn, String> {
+    ensure_can_edit(&db, &session, request.user_id).await?;
     // Get action type
     let action_types: Vec<ActionType> = db
         .get(
@@ -429,18 +617,8 @@ pub async fn create_manual_check_in(
         .ok_or_else(|| "Invalid check time format".to_string())?;
 
     // Get time rules
-    let time_rules: Vec<TimeRule> = db
-        .get(
-            "time_rules",
-            Some(vec![
-                ("action_type_id", &format!("eq.{}", request.action_type_id)),
-                ("is_active", "eq.true"),
-            ]),
-        )
-        .await
-        .unwrap_or_default();
-
-    let time_rule = time_rules.first();
+    let time_rule = get_active_time_rule(&db, request.action_type_id, request.user_id).await;
+    let time_rule = time_rule.as_ref();
 
     // Check for ongoing check-ins
     let ongoing: Vec<CheckIn> = db
@@ -546,6 +724,9 @@ pub async fn create_manual_check_in(
     let mut pair_check_in_id = None;
     let mut duration_minutes_value = None;
     let mut is_early_leave_flag = false;
+    let mut is_overtime_break_flag = false;
+    let mut net_minutes_value = None;
+    let mut overtime = None;
     
     if is_end_action && !ongoing.is_empty() {
         let start_checkin = &ongoing[0];
@@ -563,6 +744,22 @@ pub async fn create_manual_check_in(
                         is_early_leave_flag = is_early_leave(&start_time, &check_time, expected_end, timezone);
                     }
                 }
+
+                let net_minutes = calculate_shift_net_minutes(
+                    &db,
+                    start_checkin,
+                    &request.check_time,
+                    duration,
+                    &all_action_types,
+                )
+                .await;
+                net_minutes_value = Some(net_minutes);
+
+                let schedule_rule = get_schedule_rule(&db, start_checkin.user_id).await;
+                overtime = evaluate_overtime(&db, schedule_rule.as_ref(), &start_time, &check_time, net_minutes).await;
+            } else if action_type.action_role == 4 {
+                // 临时事件结束（回座）：检查是否超时或超出当日额度
+                is_overtime_break_flag = check_overtime_break(&db, start_checkin, duration, timezone).await;
             }
         }
     }
@@ -575,10 +772,14 @@ pub async fn create_manual_check_in(
         "status": status,
         "is_late": is_late_flag,
         "is_early_leave": is_early_leave_flag,
+        "is_overtime_break": is_overtime_break_flag,
+        "overtime_minutes": overtime.map(|(minutes, _)| minutes),
+        "overtime_category": overtime.map(|(_, category)| category),
         "is_manual": true,
         "note": request.note,
         "pair_check_in_id": pair_check_in_id,
         "duration_minutes": duration_minutes_value,
+        "net_minutes": net_minutes_value,
     });
 
     let checkin: CheckIn = db
@@ -592,6 +793,7 @@ pub async fn create_manual_check_in(
             "status": "completed",
             "pair_check_in_id": checkin.id,
             "duration_minutes": duration_minutes_value,
+            "is_overtime_break": is_overtime_break_flag,
         });
 
         let _ = db
@@ -603,13 +805,18 @@ pub async fn create_manual_check_in(
             .await;
     }
 
+    let after = serde_json::to_value(&checkin).ok();
+    record_audit(&db, &session, "create_manual_check_in", "check_ins", Some(checkin.id), None, after).await?;
+
     Ok(checkin)
 }
 
 #[tauri::command]
 pub async fn get_action_types(
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<Vec<ActionType>, String> {
+    current_user(&session)?;
     let action_types: Vec<ActionType> = db
         .get(
             "action_types",
@@ -627,7 +834,9 @@ pub async fn get_action_types(
 #[tauri::command]
 pub async fn get_time_rules(
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<Vec<TimeRule>, String> {
+    current_user(&session)?;
     let rules: Vec<TimeRule> = db
         .get("time_rules", Some(vec![("is_active", "eq.true")]))
         .await
@@ -640,6 +849,7 @@ pub async fn get_time_rules(
 pub async fn update_check_in(
     request: UpdateCheckInRequest,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<(), String> {
     let mut update_data = json!({});
 
@@ -653,6 +863,12 @@ pub async fn update_check_in(
         update_data["note"] = json!(note);
     }
 
+    let before = snapshot(&db, "check_ins", request.check_in_id).await?;
+    let owner = before
+        .as_ref()
+        .and_then(|check_in| check_in["user_id"].as_i64())
+        .ok_or_else(|| "打卡记录不存在".to_string())?;
+    ensure_can_edit(&db, &session, owner as i32).await?;
     db.patch(
         "check_ins",
         vec![("id", &format!("eq.{}", request.check_in_id))],
@@ -661,5 +877,8 @@ pub async fn update_check_in(
     .await
     .map_err(|e| format!("Failed to update check-in: {}", e))?;
 
+    let after = snapshot(&db, "check_ins", request.check_in_id).await?;
+    record_audit(&db, &session, "update_check_in", "check_ins", Some(request.check_in_id), before, after).await?;
+
     Ok(())
 }
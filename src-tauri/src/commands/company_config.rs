@@ -0,0 +1,152 @@
+use crate::database::SupabaseClient;
+use crate::models::{ActionType, Holiday, TimeRule};
+use crate::utils::company_config::{
+    build_company_config, parse_company_config, plan_company_config_import, serialize_company_config,
+    ConfigChange, ConfigFileFormat, CurrentConfig,
+};
+use crate::session::Session;
+use crate::utils::config::ConfigIssue;
+use crate::utils::permissions::Permission;
+use super::access::require;
+use super::admin::load_system_config;
+use super::audit::record_audit;
+use super::report::PDF_FONT_PATH_KEY;
+use tauri::State;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Settings that depend on the machine rather than the company, never exported or imported
+const LOCAL_SETTING_KEYS: &[&str] = &[PDF_FONT_PATH_KEY];
+
+#[derive(Debug, Deserialize)]
+pub struct ExportCompanyConfigRequest {
+    pub path: String,
+    pub format: ConfigFileFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportCompanyConfigRequest {
+    /// JSON 或 TOML 配置文档内容
+    pub content: String,
+    pub format: ConfigFileFormat,
+    /// 只预览差异不写入
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigImportCounts {
+    pub action_types: i32,
+    pub time_rules: i32,
+    pub holidays: i32,
+    pub settings: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigImportReport {
+    pub dry_run: bool,
+    pub changes: Vec<ConfigChange>,
+    /// 导入后会出现的配置不一致
+    pub issues: Vec<ConfigIssue>,
+    pub errors: Vec<String>,
+    /// 已写入时各部分的条数
+    pub applied: Option<ConfigImportCounts>,
+    /// 配置已写入但审计日志写入失败
+    pub audit_error: Option<String>,
+}
+
+struct StoredConfig {
+    action_types: Vec<ActionType>,
+    rules: Vec<TimeRule>,
+    holidays: Vec<Holiday>,
+    settings: HashMap<String, String>,
+}
+
+impl StoredConfig {
+    fn as_current(&self) -> CurrentConfig<'_> {
+        CurrentConfig {
+            action_types: &self.action_types,
+            rules: &self.rules,
+            holidays: &self.holidays,
+            settings: &self.settings,
+        }
+    }
+}
+
+async fn load_stored_config(db: &SupabaseClient) -> Result<StoredConfig, String> {
+    let action_types: Vec<ActionType> = db
+        .get("action_types", Some(vec![("order", "id.asc")]))
+        .await
+        .map_err(|e| format!("Failed to get action types: {}", e))?;
+    let rules: Vec<TimeRule> = db
+        .get("time_rules", Some(vec![("order", "id.asc")]))
+        .await
+        .map_err(|e| format!("Failed to get time rules: {}", e))?;
+    let holidays: Vec<Holiday> = db
+        .get("holidays", Some(vec![("order", "holiday_date.asc")]))
+        .await
+        .map_err(|e| format!("Failed to get holidays: {}", e))?;
+    let mut settings = load_system_config(db).await;
+    settings.retain(|key, _| !LOCAL_SETTING_KEYS.contains(&key.as_str()));
+
+    Ok(StoredConfig { action_types, rules, holidays, settings })
+}
+
+/// 导出打卡类型、时间规则、节假日和系统设置为带版本号的 JSON / TOML 文档
+#[tauri::command]
+pub async fn export_company_config(
+    request: ExportCompanyConfigRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let stored = load_stored_config(&db).await?;
+    let config = build_company_config(&stored.as_current(), chrono::Utc::now().to_rfc3339())?;
+    let content = serialize_company_config(&config, request.format)?;
+
+    std::fs::write(&request.path, content).map_err(|e| format!("Failed to write file: {}", e))
+}
+
+/// 导入配置文档：先试运行查看差异，确认后在一个事务中写入
+#[tauri::command]
+pub async fn import_company_config(
+    request: ImportCompanyConfigRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<ConfigImportReport, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    let mut config = parse_company_config(&request.content, request.format)?;
+    config
+        .settings
+        .retain(|key, _| !LOCAL_SETTING_KEYS.contains(&key.as_str()));
+
+    let stored = load_stored_config(&db).await?;
+    let plan = plan_company_config_import(&config, &stored.as_current());
+
+    let mut report = ConfigImportReport {
+        dry_run: request.dry_run,
+        changes: plan.changes.clone(),
+        issues: plan.issues.clone(),
+        errors: plan.errors.clone(),
+        applied: None,
+        audit_error: None,
+    };
+    if request.dry_run {
+        return Ok(report);
+    }
+    if !plan.can_apply() {
+        return Err("配置文件存在错误或会导致配置不一致，请先查看预览".to_string());
+    }
+
+    let counts: ConfigImportCounts = db
+        .rpc("import_company_config", &json!({ "config": config }))
+        .await
+        .map_err(|e| format!("Failed to import config: {}", e))?;
+    let summary = serde_json::to_value(&counts).ok();
+    if let Err(e) = record_audit(&db, &session, "import_company_config", "system_config", None, None, summary).await {
+        report.audit_error = Some(e);
+    }
+    report.applied = Some(counts);
+
+    Ok(report)
+}
@@ -0,0 +1,303 @@
+use crate::database::SupabaseClient;
+use crate::export::columns::{default_timesheet_template, AttendanceColumn, CheckInColumn, TimesheetColumn};
+use crate::export::{create_writer, Cell, ExportFormat, ExportLocale};
+use crate::models::{ActionType, CheckIn, User};
+use crate::session::Session;
+use crate::utils::permissions::Permission;
+use super::access::{current_user, require, view_scope};
+use super::admin::{department_user_ids, load_system_config};
+use super::statistics::{load_team_attendance, parse_date_range};
+use chrono::{Duration, NaiveDate};
+use serde_json::json;
+use std::collections::HashMap;
+use tauri::{State, Window};
+use serde::{Deserialize, Serialize};
+
+/// Rows fetched per request while streaming an export
+const EXPORT_PAGE_SIZE: usize = 1000;
+
+pub const EXPORT_PROGRESS_EVENT: &str = "export-progress";
+
+/// system_config key holding the payroll timesheet template as JSON
+pub const TIMESHEET_TEMPLATE_KEY: &str = "timesheet_template";
+
+#[derive(Debug, Deserialize)]
+pub struct ExportCheckInsRequest {
+    pub path: String,
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub locale: ExportLocale,
+    pub columns: Option<Vec<CheckInColumn>>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub user_id: Option<i32>,
+    /// 只导出该部门及下属团队的成员
+    pub department_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportAttendanceRequest {
+    pub path: String,
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub locale: ExportLocale,
+    pub columns: Option<Vec<AttendanceColumn>>,
+    pub start_date: String,
+    pub end_date: String,
+    pub department_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportTimesheetRequest {
+    pub path: String,
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub locale: ExportLocale,
+    pub year: i32,
+    pub month: u32,
+    /// 不传时使用已保存的模板
+    pub template: Option<Vec<TimesheetColumn>>,
+    pub department_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportResult {
+    pub path: String,
+    pub row_count: usize,
+}
+
+/// 导出打卡记录（CSV / XLSX），按页拉取并逐行写入，适用于大数据量
+#[tauri::command]
+pub async fn export_check_ins(
+    request: ExportCheckInsRequest,
+    window: Window,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<ExportResult, String> {
+    let scope = view_scope(&db, &session).await?;
+    let columns = match &request.columns {
+        Some(columns) if !columns.is_empty() => columns.clone(),
+        _ => CheckInColumn::DEFAULT.to_vec(),
+    };
+    let locale = request.locale;
+
+    let users: Vec<User> = db
+        .get("users", None)
+        .await
+        .map_err(|e| format!("Failed to get users: {}", e))?;
+    let users: HashMap<i32, User> = users.into_iter().map(|u| (u.id, u)).collect();
+
+    let action_types: Vec<ActionType> = db
+        .get("action_types", None)
+        .await
+        .map_err(|e| format!("Failed to get action types: {}", e))?;
+    let action_types: HashMap<i32, ActionType> =
+        action_types.into_iter().map(|at| (at.id, at)).collect();
+
+    let mut filters: Vec<(&str, String)> = Vec::new();
+    if let Some(filter) = scope.user_filter() {
+        filters.push(("user_id", filter));
+    }
+    if let Some(start) = &request.start_date {
+        filters.push(("check_time", format!("gte.{}T00:00:00", start)));
+    }
+    if let Some(end) = &request.end_date {
+        filters.push(("check_time", format!("lte.{}T23:59:59", end)));
+    }
+    if let Some(user_id) = request.user_id {
+        filters.push(("user_id", format!("eq.{}", user_id)));
+    }
+    if let Some(department_id) = request.department_id {
+        let member_ids = department_user_ids(&db, department_id).await?;
+        let member_ids: Vec<String> = member_ids.iter().map(|id| id.to_string()).collect();
+        filters.push(("user_id", format!("in.({})", member_ids.join(","))));
+    }
+
+    let mut writer = create_writer(request.format, &request.path, locale.pick("打卡记录", "Check-ins"))?;
+    let header: Vec<Cell> = columns.iter().map(|c| c.header(locale).into()).collect();
+    writer.write_row(&header)?;
+
+    // 按 (check_time, id) 做游标分页，避免大偏移量查询越来越慢
+    let mut row_count = 0;
+    let mut cursor: Option<(String, i32)> = None;
+    loop {
+        let cursor_filter = cursor.as_ref().map(|(check_time, id)| {
+            format!(
+                "(check_time.gt.\"{0}\",and(check_time.eq.\"{0}\",id.gt.{1}))",
+                check_time, id
+            )
+        });
+        let limit = EXPORT_PAGE_SIZE.to_string();
+
+        let mut params: Vec<(&str, &str)> = filters.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        if let Some(cursor_filter) = &cursor_filter {
+            params.push(("or", cursor_filter));
+        }
+        params.push(("order", "check_time.asc,id.asc"));
+        params.push(("limit", &limit));
+
+        let page: Vec<CheckIn> = db
+            .get("check_ins", Some(params))
+            .await
+            .map_err(|e| format!("Failed to get check-ins: {}", e))?;
+
+        for check_in in &page {
+            let user = users.get(&check_in.user_id);
+            let action_type = action_types.get(&check_in.action_type_id);
+            let row: Vec<Cell> = columns
+                .iter()
+                .map(|c| c.cell(check_in, user, action_type, locale))
+                .collect();
+            writer.write_row(&row)?;
+        }
+        row_count += page.len();
+        let _ = window.emit(EXPORT_PROGRESS_EVENT, row_count);
+
+        match page.last() {
+            Some(last) if page.len() == EXPORT_PAGE_SIZE => {
+                cursor = Some((last.check_time.clone(), last.id));
+            }
+            _ => break,
+        }
+    }
+
+    writer.finish()?;
+
+    Ok(ExportResult {
+        path: request.path,
+        row_count,
+    })
+}
+
+/// 导出考勤汇总报表：每位员工一行
+#[tauri::command]
+pub async fn export_attendance_report(
+    request: ExportAttendanceRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<ExportResult, String> {
+    let scope = view_scope(&db, &session).await?;
+    let (start, end) = parse_date_range(&request.start_date, &request.end_date)?;
+    let columns = match &request.columns {
+        Some(columns) if !columns.is_empty() => columns.clone(),
+        _ => AttendanceColumn::DEFAULT.to_vec(),
+    };
+    let locale = request.locale;
+
+    let team = load_team_attendance(&db, start, end, request.department_id, &scope).await?;
+    let period = format!("{} ~ {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
+
+    let mut writer = create_writer(request.format, &request.path, locale.pick("考勤汇总", "Attendance"))?;
+    let header: Vec<Cell> = columns.iter().map(|c| c.header(locale).into()).collect();
+    writer.write_row(&header)?;
+
+    for attendance in &team {
+        let row: Vec<Cell> = columns
+            .iter()
+            .map(|c| c.cell(&attendance.user, &attendance.totals, &period))
+            .collect();
+        writer.write_row(&row)?;
+    }
+    writer.finish()?;
+
+    Ok(ExportResult {
+        path: request.path,
+        row_count: team.len(),
+    })
+}
+
+/// First and last day of a calendar month
+pub(crate) fn month_range(year: i32, month: u32) -> Result<(NaiveDate, NaiveDate), String> {
+    let start = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| "Invalid month".to_string())?;
+    let next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .ok_or_else(|| "Invalid month".to_string())?;
+
+    Ok((start, next_month - Duration::days(1)))
+}
+
+/// The saved timesheet template, or the default one when none is saved or it cannot be parsed
+async fn load_timesheet_template(db: &SupabaseClient) -> Vec<TimesheetColumn> {
+    load_system_config(db)
+        .await
+        .get(TIMESHEET_TEMPLATE_KEY)
+        .and_then(|value| serde_json::from_str::<Vec<TimesheetColumn>>(value).ok())
+        .filter(|template| !template.is_empty())
+        .unwrap_or_else(default_timesheet_template)
+}
+
+#[tauri::command]
+pub async fn get_timesheet_template(
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<TimesheetColumn>, String> {
+    current_user(&session)?;
+    Ok(load_timesheet_template(&db).await)
+}
+
+#[tauri::command]
+pub async fn save_timesheet_template(
+    template: Vec<TimesheetColumn>,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<TimesheetColumn>, String> {
+    require(&session, Permission::ManageConfiguration)?;
+    if template.is_empty() {
+        return Err("工资表模板至少需要一列".to_string());
+    }
+
+    let value = serde_json::to_string(&template)
+        .map_err(|e| format!("Failed to serialize template: {}", e))?;
+    let data = json!({
+        "config_key": TIMESHEET_TEMPLATE_KEY,
+        "config_value": value,
+        "config_desc": "工资表导出列模板",
+        "updated_at": chrono::Utc::now().to_rfc3339(),
+    });
+
+    db.upsert::<_, serde_json::Value>("system_config", "config_key", &data)
+        .await
+        .map_err(|e| format!("Failed to save timesheet template: {}", e))?;
+
+    Ok(template)
+}
+
+/// 导出月度工资考勤表：每位员工一行，列由模板决定
+#[tauri::command]
+pub async fn export_monthly_timesheet(
+    request: ExportTimesheetRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<ExportResult, String> {
+    let scope = view_scope(&db, &session).await?;
+    let (start, end) = month_range(request.year, request.month)?;
+    let template = match request.template {
+        Some(template) if !template.is_empty() => template,
+        _ => load_timesheet_template(&db).await,
+    };
+    let locale = request.locale;
+    let period = start.format("%Y-%m").to_string();
+
+    let team = load_team_attendance(&db, start, end, request.department_id, &scope).await?;
+
+    let mut writer = create_writer(request.format, &request.path, &period)?;
+    let header: Vec<Cell> = template.iter().map(|c| c.header(locale).into()).collect();
+    writer.write_row(&header)?;
+
+    for attendance in &team {
+        let row: Vec<Cell> = template
+            .iter()
+            .map(|c| c.column.cell(&attendance.user, &attendance.totals, &period))
+            .collect();
+        writer.write_row(&row)?;
+    }
+    writer.finish()?;
+
+    Ok(ExportResult {
+        path: request.path,
+        row_count: team.len(),
+    })
+}
@@ -0,0 +1,330 @@
+use crate::database::SupabaseClient;
+use crate::export::{create_writer, Cell, ExportFormat, ExportLocale};
+use crate::models::{ActionType, CheckIn, OvertimeSettings, Role, TimeRule, User};
+use crate::session::Session;
+use crate::utils::crypto::{generate_password, hash_password};
+use crate::utils::import::{import_batches, parse_import_csv, plan_import, ImportReference, ImportRowError};
+use crate::utils::provisioning::{parse_provision_csv, parse_provision_json, validate_provision_rows};
+use crate::utils::permissions::Permission;
+use crate::utils::time::parse_date;
+use super::access::require;
+use super::audit::record_audit;
+use super::admin::{load_departments, load_holidays, load_system_config};
+use chrono::Duration;
+use tauri::State;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const DEFAULT_IMPORT_BATCH_SIZE: usize = 500;
+const DEFAULT_PASSWORD_LENGTH: usize = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct ImportCheckInsRequest {
+    /// CSV 文件内容，列为 username, action, timestamp, note
+    pub content: String,
+    /// 只校验不写入
+    pub dry_run: bool,
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub imported_rows: usize,
+    pub errors: Vec<ImportRowError>,
+    /// 写入中断时的错误。之前的批次已完整提交，失败的批次整批未写入；
+    /// 重新导入同一文件时已提交的记录会作为重复行跳过
+    pub batch_error: Option<String>,
+    /// 记录已写入但审计日志写入失败，不应重新导入
+    pub audit_error: Option<String>,
+}
+
+/// 导入历史打卡记录：按打卡规则重放配对，可先试运行查看校验报告，再分批写入
+#[tauri::command]
+pub async fn import_check_ins(
+    request: ImportCheckInsRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<ImportReport, String> {
+    require(&session, Permission::EditAllAttendance)?;
+    let timezone = "Asia/Phnom_Penh";
+    let (rows, mut errors) = parse_import_csv(&request.content)?;
+    let total_rows = rows.len() + errors.len();
+
+    let users: Vec<User> = db
+        .get("users", None)
+        .await
+        .map_err(|e| format!("Failed to get users: {}", e))?;
+
+    let action_types: Vec<ActionType> = db
+        .get("action_types", None)
+        .await
+        .map_err(|e| format!("Failed to get action types: {}", e))?;
+
+    let rules: Vec<TimeRule> = db
+        .get("time_rules", Some(vec![("is_active", "eq.true")]))
+        .await
+        .map_err(|e| format!("Failed to get time rules: {}", e))?;
+
+    let departments = load_departments(&db).await?;
+    let overtime_settings = OvertimeSettings::from_config(&load_system_config(&db).await);
+
+    // 导入范围内已有的记录，用于识别重复行
+    let dates: Vec<_> = rows.iter().filter_map(|r| parse_date(&r.timestamp)).collect();
+    let (holidays, existing) = match (dates.iter().min(), dates.iter().max()) {
+        (Some(first), Some(last)) => {
+            let first_str = first.format("%Y-%m-%d").to_string();
+            let last_str = last.format("%Y-%m-%d").to_string();
+            let holidays = load_holidays(&db, &first_str, &last_str).await;
+
+            let user_ids: Vec<String> = users
+                .iter()
+                .filter(|u| rows.iter().any(|r| r.username.eq_ignore_ascii_case(&u.username)))
+                .map(|u| u.id.to_string())
+                .collect();
+            let existing: Vec<CheckIn> = if user_ids.is_empty() {
+                Vec::new()
+            } else {
+                db.get_all(
+                    "check_ins",
+                    &[
+                        ("user_id", &format!("in.({})", user_ids.join(","))),
+                        ("check_time", &format!("gte.{}T00:00:00", first_str)),
+                        ("check_time", &format!("lte.{}T23:59:59", (*last + Duration::days(1)).format("%Y-%m-%d"))),
+                        ("order", "id.asc"),
+                    ],
+                )
+                .await
+                .map_err(|e| format!("Failed to get check-ins: {}", e))?
+            };
+            (holidays, existing)
+        }
+        _ => (Vec::new(), Vec::new()),
+    };
+
+    let plan = plan_import(
+        rows,
+        &ImportReference {
+            users: &users,
+            action_types: &action_types,
+            rules: &rules,
+            departments: &departments,
+            holidays: &holidays,
+            overtime_settings: &overtime_settings,
+            existing: &existing,
+            timezone,
+        },
+    );
+    errors.extend(plan.errors);
+    errors.sort_by_key(|e| e.line);
+
+    let mut report = ImportReport {
+        dry_run: request.dry_run,
+        total_rows,
+        valid_rows: plan.records.len(),
+        imported_rows: 0,
+        errors,
+        batch_error: None,
+        audit_error: None,
+    };
+    if request.dry_run {
+        return Ok(report);
+    }
+
+    // 分批写入：每批在数据库的一个事务中插入并回填配对 ID，一对记录总在同一批次内
+    let batch_size = request.batch_size.unwrap_or(DEFAULT_IMPORT_BATCH_SIZE).clamp(1, 1000);
+    for batch in import_batches(&plan.records, batch_size) {
+        let rows: Vec<_> = batch
+            .clone()
+            .map(|index| {
+                let pair_offset = plan.records[index].pair_index.map(|pair| pair - batch.start);
+                plan.records[index].to_json(pair_offset)
+            })
+            .collect();
+        match db.rpc::<_, usize>("import_check_in_batch", &json!({ "records": rows })).await {
+            Ok(inserted) => report.imported_rows += inserted,
+            Err(e) => {
+                report.batch_error = Some(format!(
+                    "第 {} 行起的批次写入失败: {}",
+                    plan.records[batch.start].line, e
+                ));
+                break;
+            }
+        }
+    }
+
+    let summary = json!({
+        "total_rows": report.total_rows,
+        "valid_rows": report.valid_rows,
+        "imported_rows": report.imported_rows,
+        "error_rows": report.errors.len(),
+        "batch_error": report.batch_error,
+    });
+    if let Err(e) = record_audit(&db, &session, "import_check_ins", "check_ins", None, None, Some(summary)).await {
+        report.audit_error = Some(e);
+    }
+
+    Ok(report)
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProvisionFormat {
+    Csv,
+    Json,
+}
+
+/// Where to write the sheet of initial passwords handed out to employees
+#[derive(Debug, Deserialize)]
+pub struct CredentialsSheet {
+    pub path: String,
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub locale: ExportLocale,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProvisionUsersRequest {
+    /// CSV（username, full_name, is_admin）或 JSON 数组
+    pub content: String,
+    pub format: ProvisionFormat,
+    /// 只校验不写入
+    pub dry_run: bool,
+    pub password_length: Option<usize>,
+    pub credentials: Option<CredentialsSheet>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvisionedCredential {
+    pub user_id: i32,
+    pub username: String,
+    pub full_name: String,
+    pub initial_password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvisionReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub created: Vec<ProvisionedCredential>,
+    pub errors: Vec<ImportRowError>,
+    /// 已写入的账号密码表路径
+    pub credentials_path: Option<String>,
+    pub credentials_error: Option<String>,
+    /// 账号已创建但审计日志写入失败，仍返回报告以免初始密码丢失
+    pub audit_error: Option<String>,
+}
+
+fn write_credentials_sheet(sheet: &CredentialsSheet, credentials: &[ProvisionedCredential]) -> Result<(), String> {
+    let locale = sheet.locale;
+    let mut writer = create_writer(sheet.format, &sheet.path, locale.pick("初始密码", "Credentials"))?;
+    writer.write_row(&[
+        locale.pick("用户名", "Username").into(),
+        locale.pick("姓名", "Full name").into(),
+        locale.pick("初始密码", "Initial password").into(),
+    ])?;
+    for credential in credentials {
+        let row: [Cell; 3] = [
+            credential.username.as_str().into(),
+            credential.full_name.as_str().into(),
+            credential.initial_password.as_str().into(),
+        ];
+        writer.write_row(&row)?;
+    }
+    writer.finish()
+}
+
+/// 批量开户：生成初始密码并要求首次登录修改，跳过已存在或文件内重复的用户名
+#[tauri::command]
+pub async fn provision_users(
+    request: ProvisionUsersRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<ProvisionReport, String> {
+    require(&session, Permission::ManageUsers)?;
+    let (rows, mut errors) = match request.format {
+        ProvisionFormat::Csv => parse_provision_csv(&request.content)?,
+        ProvisionFormat::Json => (parse_provision_json(&request.content)?, Vec::new()),
+    };
+    let total_rows = rows.len() + errors.len();
+
+    let existing: Vec<User> = db
+        .get("users", None)
+        .await
+        .map_err(|e| format!("Failed to get users: {}", e))?;
+
+    let (rows, row_errors) = validate_provision_rows(rows, &existing);
+    errors.extend(row_errors);
+    errors.sort_by_key(|e| e.line);
+
+    let mut report = ProvisionReport {
+        dry_run: request.dry_run,
+        total_rows,
+        valid_rows: rows.len(),
+        created: Vec::new(),
+        errors,
+        credentials_path: None,
+        credentials_error: None,
+        audit_error: None,
+    };
+    if request.dry_run || rows.is_empty() {
+        return Ok(report);
+    }
+
+    let password_length = request.password_length.unwrap_or(DEFAULT_PASSWORD_LENGTH).clamp(8, 64);
+    let passwords: Vec<String> = rows.iter().map(|_| generate_password(password_length)).collect();
+    let new_users: Vec<_> = rows
+        .iter()
+        .zip(&passwords)
+        .map(|(row, password)| {
+            json!({
+                "username": row.username,
+                "password_hash": hash_password(password),
+                "full_name": row.full_name,
+                "is_admin": row.is_admin,
+                "role": if row.is_admin { Role::SuperAdmin } else { Role::Employee },
+                "must_change_password": true,
+            })
+        })
+        .collect();
+
+    // 一次插入全部账号，避免中途失败只建了一部分而密码表不完整
+    let created: Vec<User> = db
+        .post_many("users", &new_users)
+        .await
+        .map_err(|e| format!("Failed to create users: {}", e))?;
+
+    report.created = created
+        .into_iter()
+        .zip(passwords)
+        .map(|(user, initial_password)| ProvisionedCredential {
+            user_id: user.id,
+            username: user.username,
+            full_name: user.full_name,
+            initial_password,
+        })
+        .collect();
+
+    let summary = json!({
+        "total_rows": report.total_rows,
+        "created": report.created.len(),
+        "error_rows": report.errors.len(),
+        "user_ids": report.created.iter().map(|c| c.user_id).collect::<Vec<_>>(),
+    });
+    if let Err(e) = record_audit(&db, &session, "provision_users", "users", None, None, Some(summary)).await {
+        report.audit_error = Some(e);
+    }
+
+    // 账号已创建，密码表写入失败时仍返回报告，初始密码不会丢失
+    if let Some(sheet) = &request.credentials {
+        match write_credentials_sheet(sheet, &report.created) {
+            Ok(()) => report.credentials_path = Some(sheet.path.clone()),
+            Err(e) => report.credentials_error = Some(e),
+        }
+    }
+
+    Ok(report)
+}
@@ -2,9 +2,32 @@
 pub mod auth;
 pub mod checkin;
 pub mod admin;
+pub mod profile;
 pub mod statistics;
+pub mod presence;
+pub mod export;
+pub mod report;
+pub mod import;
+pub mod company_config;
+pub mod backup;
+pub mod audit;
+pub mod throttle;
+pub mod two_factor;
+pub mod registration;
+pub mod access;
 
 pub use auth::*;
 pub use checkin::*;
 pub use admin::*;
+pub use profile::*;
 pub use statistics::*;
+pub use presence::*;
+pub use export::*;
+pub use report::*;
+pub use import::*;
+pub use company_config::*;
+pub use backup::*;
+pub use audit::*;
+pub use throttle::*;
+pub use two_factor::*;
+pub use registration::*;
@@ -0,0 +1,167 @@
+use crate::database::SupabaseClient;
+use crate::models::{ActionType, CheckIn, TimeRule, User};
+use crate::session::Session;
+use crate::utils::department::employee_rule;
+use crate::utils::time::{calculate_duration, format_time, get_company_time, parse_time};
+use super::access::view_scope;
+use super::admin::load_departments;
+use std::collections::HashMap;
+use tauri::State;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceState {
+    Working,
+    OnBreak,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserPresence {
+    pub user_id: i32,
+    pub username: String,
+    pub full_name: String,
+    pub state: PresenceState,
+    pub clocked_in_at: String,
+    pub clocked_in_minutes: i32,
+    pub break_action_type_id: Option<i32>,
+    pub break_name: Option<String>,
+    pub break_started_at: Option<String>,
+    pub break_minutes: Option<i32>,
+    pub is_break_overdue: bool,  // 本次临时事件已超过单次最长时长
+}
+
+#[derive(Debug, Serialize)]
+pub struct PresenceOverview {
+    pub generated_at: String,
+    pub working_count: usize,
+    pub on_break_count: usize,
+    pub users: Vec<UserPresence>,
+}
+
+/// 当前在岗情况：由进行中（ongoing）的打卡记录推导谁在上班、谁在临时事件中以及持续多久
+/// 只包含当前用户有权查看的员工
+#[tauri::command]
+pub async fn get_current_presence(
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<PresenceOverview, String> {
+    let timezone = "Asia/Phnom_Penh";
+    let now = get_company_time(timezone);
+
+    let scope_filter = view_scope(&db, &session).await?.user_filter();
+    let mut params = vec![("status", "eq.ongoing"), ("order", "check_time.asc,id.asc")];
+    if let Some(filter) = &scope_filter {
+        params.push(("user_id", filter));
+    }
+    let ongoing: Vec<CheckIn> = db
+        .get_all("check_ins", &params)
+        .await
+        .map_err(|e| format!("Failed to get check-ins: {}", e))?;
+
+    let action_types: Vec<ActionType> = db
+        .get("action_types", None)
+        .await
+        .map_err(|e| format!("Failed to get action types: {}", e))?;
+
+    let rules: Vec<TimeRule> = db
+        .get("time_rules", Some(vec![("is_active", "eq.true")]))
+        .await
+        .map_err(|e| format!("Failed to get time rules: {}", e))?;
+
+    let users: Vec<User> = db
+        .get("users", None)
+        .await
+        .map_err(|e| format!("Failed to get users: {}", e))?;
+
+    let departments = load_departments(&db).await?;
+
+    let role_of = |action_type_id: i32| {
+        action_types
+            .iter()
+            .find(|at| at.id == action_type_id)
+            .map(|at| at.action_role)
+    };
+
+    // 每位员工最近一条进行中的上班记录和临时事件记录
+    let mut main_starts: HashMap<i32, &CheckIn> = HashMap::new();
+    let mut break_starts: HashMap<i32, &CheckIn> = HashMap::new();
+    for check_in in &ongoing {
+        match role_of(check_in.action_type_id) {
+            Some(1) => {
+                main_starts.insert(check_in.user_id, check_in);
+            }
+            Some(3) => {
+                break_starts.insert(check_in.user_id, check_in);
+            }
+            _ => {}
+        }
+    }
+
+    let elapsed_minutes = |check_in: &CheckIn| {
+        parse_time(&check_in.check_time, timezone)
+            .map(|start| calculate_duration(&start, &now).max(0))
+            .unwrap_or(0)
+    };
+
+    let mut presence: Vec<UserPresence> = users
+        .iter()
+        .filter_map(|user| {
+            let main_start = main_starts.get(&user.id)?;
+            let break_start = break_starts.get(&user.id);
+
+            let break_minutes = break_start.map(|c| elapsed_minutes(c));
+            // 按员工所在部门和默认班次取规则，与打卡时的校验一致
+            let max_duration = break_start.and_then(|c| {
+                employee_rule(&rules, &action_types, &departments, user, c.action_type_id)
+                    .and_then(|r| r.max_duration_minutes)
+            });
+
+            Some(UserPresence {
+                user_id: user.id,
+                username: user.username.clone(),
+                full_name: user.full_name.clone(),
+                state: if break_start.is_some() {
+                    PresenceState::OnBreak
+                } else {
+                    PresenceState::Working
+                },
+                clocked_in_at: main_start.check_time.clone(),
+                clocked_in_minutes: elapsed_minutes(main_start),
+                break_action_type_id: break_start.map(|c| c.action_type_id),
+                break_name: break_start.and_then(|c| {
+                    action_types
+                        .iter()
+                        .find(|at| at.id == c.action_type_id)
+                        .map(|at| at.name.clone())
+                }),
+                break_started_at: break_start.map(|c| c.check_time.clone()),
+                break_minutes,
+                is_break_overdue: matches!(
+                    (break_minutes, max_duration),
+                    (Some(minutes), Some(max)) if minutes > max
+                ),
+            })
+        })
+        .collect();
+
+    // 临时事件中的员工排在前面，时间最长的优先
+    presence.sort_by(|a, b| {
+        (b.state == PresenceState::OnBreak)
+            .cmp(&(a.state == PresenceState::OnBreak))
+            .then(b.break_minutes.cmp(&a.break_minutes))
+            .then(a.clocked_in_at.cmp(&b.clocked_in_at))
+    });
+
+    let on_break_count = presence
+        .iter()
+        .filter(|p| p.state == PresenceState::OnBreak)
+        .count();
+
+    Ok(PresenceOverview {
+        generated_at: format_time(&now),
+        working_count: presence.len() - on_break_count,
+        on_break_count,
+        users: presence,
+    })
+}
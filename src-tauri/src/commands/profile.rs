@@ -0,0 +1,270 @@
+use crate::database::SupabaseClient;
+use crate::models::{ActionType, CreateUserRequest, Role, TimeRule, UpdateMyProfileRequest, UpdateUserProfileRequest, User};
+use crate::session::Session;
+use crate::utils::crypto::{generate_password, hash_password};
+use crate::utils::permissions::Permission;
+use crate::utils::profile::{normalize_text, validate_email, validate_employee_no, validate_employment_dates, validate_phone};
+use crate::utils::provisioning::{validate_provision_rows, ProvisionRow};
+use super::access::{current_user, ensure_can_view, require};
+use super::admin::load_departments;
+use super::audit::{record_audit, snapshot};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::State;
+
+const INITIAL_PASSWORD_LENGTH: usize = 10;
+
+#[derive(Debug, Serialize)]
+pub struct CreatedUser {
+    pub user: User,
+    /// 只显示一次，首次登录须修改
+    pub initial_password: String,
+}
+
+pub(crate) async fn load_user(db: &SupabaseClient, user_id: i32) -> Result<User, String> {
+    let users: Vec<User> = db
+        .get("users", Some(vec![("id", &format!("eq.{}", user_id))]))
+        .await
+        .map_err(|e| format!("Failed to get user: {}", e))?;
+    users.into_iter().next().ok_or_else(|| "用户不存在".to_string())
+}
+
+async fn ensure_employee_no_free(db: &SupabaseClient, employee_no: &str, user_id: Option<i32>) -> Result<(), String> {
+    let mut params = vec![("employee_no", format!("eq.{}", employee_no))];
+    if let Some(user_id) = user_id {
+        params.push(("id", format!("neq.{}", user_id)));
+    }
+    let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    let count = db
+        .count("users", &params)
+        .await
+        .map_err(|e| format!("Failed to count users: {}", e))?;
+    if count > 0 {
+        return Err(format!("工号「{}」已被使用", employee_no));
+    }
+    Ok(())
+}
+
+/// The default schedule must be an active 上班/下班 rule
+async fn ensure_schedule_rule(db: &SupabaseClient, rule_id: i32) -> Result<(), String> {
+    let rules: Vec<TimeRule> = db
+        .get("time_rules", Some(vec![("id", &format!("eq.{}", rule_id))]))
+        .await
+        .map_err(|e| format!("Failed to get time rule: {}", e))?;
+    let rule = rules.into_iter().next().ok_or_else(|| "时间规则不存在".to_string())?;
+    if !rule.is_active {
+        return Err(format!("时间规则「{}」未启用，不能作为默认排班", rule.rule_name));
+    }
+
+    let action_types: Vec<ActionType> = db
+        .get("action_types", Some(vec![("id", &format!("eq.{}", rule.action_type_id))]))
+        .await
+        .map_err(|e| format!("Failed to get action type: {}", e))?;
+    if !action_types.first().is_some_and(|at| matches!(at.action_role, 1 | 2)) {
+        return Err("默认排班必须是上班或下班的时间规则".to_string());
+    }
+    Ok(())
+}
+
+/// Validate the profile changes against the current profile and return the columns to write
+async fn profile_changes(
+    db: &SupabaseClient,
+    request: UpdateUserProfileRequest,
+    current: Option<&User>,
+) -> Result<Value, String> {
+    let mut data = json!({});
+
+    if let Some(full_name) = request.full_name {
+        let full_name = full_name.trim();
+        if full_name.is_empty() {
+            return Err("姓名不能为空".to_string());
+        }
+        data["full_name"] = json!(full_name);
+    }
+    if let Some(employee_no) = request.employee_no {
+        let employee_no = normalize_text(employee_no);
+        if let Some(employee_no) = &employee_no {
+            validate_employee_no(employee_no)?;
+            ensure_employee_no_free(db, employee_no, current.map(|u| u.id)).await?;
+        }
+        data["employee_no"] = json!(employee_no);
+    }
+    if let Some(email) = request.email {
+        let email = normalize_text(email);
+        if let Some(email) = &email {
+            validate_email(email)?;
+        }
+        data["email"] = json!(email);
+    }
+    if let Some(phone) = request.phone {
+        let phone = normalize_text(phone);
+        if let Some(phone) = &phone {
+            validate_phone(phone)?;
+        }
+        data["phone"] = json!(phone);
+    }
+
+    // 只修改其中一个日期时与另一个已保存的日期比较
+    let hire_date_changed = request.hire_date.is_some();
+    let termination_date_changed = request.termination_date.is_some();
+    let hire_date = match request.hire_date {
+        Some(date) => normalize_text(date),
+        None => current.and_then(|u| u.hire_date.clone()),
+    };
+    let termination_date = match request.termination_date {
+        Some(date) => normalize_text(date),
+        None => current.and_then(|u| u.termination_date.clone()),
+    };
+    validate_employment_dates(hire_date.as_deref(), termination_date.as_deref())?;
+    if hire_date_changed {
+        data["hire_date"] = json!(hire_date);
+    }
+    if termination_date_changed {
+        data["termination_date"] = json!(termination_date);
+    }
+
+    if let Some(rule_id) = request.default_schedule_rule_id {
+        if let Some(rule_id) = rule_id {
+            ensure_schedule_rule(db, rule_id).await?;
+        }
+        data["default_schedule_rule_id"] = json!(rule_id);
+    }
+
+    Ok(data)
+}
+
+/// 员工档案，员工只能查看自己的，经理可查看所负责团队成员的
+#[tauri::command]
+pub async fn get_user_profile(
+    user_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<User, String> {
+    ensure_can_view(&db, &session, user_id).await?;
+    load_user(&db, user_id).await
+}
+
+/// 新建员工账号和档案，生成初始密码并要求首次登录修改
+#[tauri::command]
+pub async fn create_user(
+    request: CreateUserRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<CreatedUser, String> {
+    require(&session, Permission::ManageUsers)?;
+
+    let existing: Vec<User> = db
+        .get("users", Some(vec![("username", &format!("ilike.{}", request.username.trim()))]))
+        .await
+        .map_err(|e| format!("Failed to get users: {}", e))?;
+    let row = ProvisionRow {
+        line: 1,
+        username: request.username.trim().to_string(),
+        full_name: request.full_name.trim().to_string(),
+        is_admin: request.role == Role::SuperAdmin,
+    };
+    let (_, errors) = validate_provision_rows(vec![row], &existing);
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error.message);
+    }
+    if let Some(department_id) = request.department_id {
+        if !load_departments(&db).await?.iter().any(|d| d.id == department_id) {
+            return Err("部门不存在".to_string());
+        }
+    }
+
+    let profile = UpdateUserProfileRequest {
+        user_id: 0,
+        full_name: None,
+        employee_no: request.employee_no.map(Some),
+        hire_date: request.hire_date.map(Some),
+        termination_date: request.termination_date.map(Some),
+        email: request.email.map(Some),
+        phone: request.phone.map(Some),
+        default_schedule_rule_id: request.default_schedule_rule_id.map(Some),
+    };
+    let mut new_user = profile_changes(&db, profile, None).await?;
+
+    let initial_password = generate_password(INITIAL_PASSWORD_LENGTH);
+    new_user["username"] = json!(request.username.trim());
+    new_user["full_name"] = json!(request.full_name.trim());
+    new_user["password_hash"] = json!(hash_password(&initial_password));
+    new_user["must_change_password"] = json!(true);
+    new_user["role"] = json!(request.role);
+    new_user["is_admin"] = json!(request.role == Role::SuperAdmin);
+    new_user["department_id"] = json!(request.department_id);
+
+    let user: User = db
+        .post("users", &new_user)
+        .await
+        .map_err(|e| format!("Failed to create user: {}", e))?;
+
+    let after = serde_json::to_value(&user).ok();
+    record_audit(&db, &session, "create_user", "users", Some(user.id), None, after).await?;
+
+    Ok(CreatedUser { user, initial_password })
+}
+
+/// 人事或管理员修改员工档案
+#[tauri::command]
+pub async fn update_user_profile(
+    request: UpdateUserProfileRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<User, String> {
+    require(&session, Permission::EditProfiles)?;
+    let user_id = request.user_id;
+    let current = load_user(&db, user_id).await?;
+
+    let update_data = profile_changes(&db, request, Some(&current)).await?;
+    let before = snapshot(&db, "users", user_id).await?;
+    db.patch(
+        "users",
+        vec![("id", &format!("eq.{}", user_id))],
+        &update_data,
+    )
+    .await
+    .map_err(|e| format!("Failed to update user: {}", e))?;
+
+    let after = snapshot(&db, "users", user_id).await?;
+    record_audit(&db, &session, "update_user_profile", "users", Some(user_id), before, after).await?;
+
+    load_user(&db, user_id).await
+}
+
+/// 员工自助修改联系方式，其余档案由人事维护
+#[tauri::command]
+pub async fn update_my_profile(
+    request: UpdateMyProfileRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<User, String> {
+    let current = current_user(&session)?;
+    let profile = UpdateUserProfileRequest {
+        user_id: current.id,
+        full_name: None,
+        employee_no: None,
+        hire_date: None,
+        termination_date: None,
+        email: request.email,
+        phone: request.phone,
+        default_schedule_rule_id: None,
+    };
+
+    let update_data = profile_changes(&db, profile, Some(&current)).await?;
+    let before = snapshot(&db, "users", current.id).await?;
+    db.patch(
+        "users",
+        vec![("id", &format!("eq.{}", current.id))],
+        &update_data,
+    )
+    .await
+    .map_err(|e| format!("Failed to update user: {}", e))?;
+
+    let after = snapshot(&db, "users", current.id).await?;
+    record_audit(&db, &session, "update_my_profile", "users", Some(current.id), before, after).await?;
+
+    let user = load_user(&db, current.id).await?;
+    session.set(user.clone());
+    Ok(user)
+}
@@ -0,0 +1,175 @@
+use crate::database::SupabaseClient;
+use crate::models::{ApprovalStatus, CreateInviteCodeRequest, InviteCode, RegistrationSettings, User};
+use crate::session::Session;
+use crate::utils::permissions::Permission;
+use crate::utils::registration::{generate_invite_code, invite_unusable_reason};
+use super::access::require;
+use super::admin::{load_departments, load_system_config};
+use super::audit::{record_audit, snapshot};
+use super::profile::load_user;
+use chrono::Utc;
+use serde_json::json;
+use tauri::State;
+
+pub(crate) async fn load_registration_settings(db: &SupabaseClient) -> RegistrationSettings {
+    RegistrationSettings::from_config(&load_system_config(db).await)
+}
+
+/// Take one use of the invite code, refusing codes that are revoked, used up or expired
+pub(crate) async fn consume_invite_code(db: &SupabaseClient, code: &str) -> Result<InviteCode, String> {
+    let invites: Vec<InviteCode> = db
+        .get("invite_codes", Some(vec![("code", &format!("eq.{}", code))]))
+        .await
+        .map_err(|e| format!("Failed to get invite code: {}", e))?;
+    let invite = invites.into_iter().next().ok_or_else(|| "邀请码无效".to_string())?;
+    if let Some(reason) = invite_unusable_reason(&invite, Utc::now()) {
+        return Err(format!("邀请码{}", reason));
+    }
+
+    // 检查与占用在数据库中一次完成，同时注册时不会超过使用次数
+    let consumed: Option<InviteCode> = db
+        .rpc("consume_invite_code", &json!({ "invite_code": code }))
+        .await
+        .map_err(|e| format!("Failed to use invite code: {}", e))?;
+    consumed.ok_or_else(|| "邀请码已失效".to_string())
+}
+
+/// 注册方式，登录页据此显示注册表单，无须登录
+#[tauri::command]
+pub async fn get_registration_settings(
+    db: State<'_, SupabaseClient>,
+) -> Result<RegistrationSettings, String> {
+    Ok(load_registration_settings(&db).await)
+}
+
+#[tauri::command]
+pub async fn get_invite_codes(
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<InviteCode>, String> {
+    require(&session, Permission::ManageUsers)?;
+    db.get("invite_codes", Some(vec![("order", "created_at.desc")]))
+        .await
+        .map_err(|e| format!("Failed to get invite codes: {}", e))
+}
+
+#[tauri::command]
+pub async fn create_invite_code(
+    request: CreateInviteCodeRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<InviteCode, String> {
+    let admin = require(&session, Permission::ManageUsers)?;
+    if request.max_uses.is_some_and(|n| n <= 0) {
+        return Err("使用次数必须大于 0".to_string());
+    }
+    if request.expires_in_days.is_some_and(|d| d <= 0) {
+        return Err("有效天数必须大于 0".to_string());
+    }
+    if request.max_uses.is_none() && request.expires_in_days.is_none() {
+        return Err("邀请码须设置使用次数或有效期".to_string());
+    }
+    if let Some(department_id) = request.department_id {
+        if !load_departments(&db).await?.iter().any(|d| d.id == department_id) {
+            return Err("部门不存在".to_string());
+        }
+    }
+
+    let expires_at = request
+        .expires_in_days
+        .map(|days| (Utc::now() + chrono::Duration::days(days)).to_rfc3339());
+    let invite: InviteCode = db
+        .post(
+            "invite_codes",
+            &json!({
+                "code": generate_invite_code(),
+                "department_id": request.department_id,
+                "max_uses": request.max_uses,
+                "expires_at": expires_at,
+                "created_by": admin.id,
+            }),
+        )
+        .await
+        .map_err(|e| format!("Failed to create invite code: {}", e))?;
+
+    let after = serde_json::to_value(&invite).ok();
+    record_audit(&db, &session, "create_invite_code", "invite_codes", Some(invite.id), None, after).await?;
+
+    Ok(invite)
+}
+
+#[tauri::command]
+pub async fn revoke_invite_code(
+    invite_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageUsers)?;
+
+    let before = snapshot(&db, "invite_codes", invite_id).await?;
+    if before.is_none() {
+        return Err("邀请码不存在".to_string());
+    }
+    db.patch(
+        "invite_codes",
+        vec![("id", &format!("eq.{}", invite_id))],
+        &json!({ "revoked": true }),
+    )
+    .await
+    .map_err(|e| format!("Failed to revoke invite code: {}", e))?;
+
+    let after = snapshot(&db, "invite_codes", invite_id).await?;
+    record_audit(&db, &session, "revoke_invite_code", "invite_codes", Some(invite_id), before, after).await?;
+
+    Ok(())
+}
+
+async fn set_approval_status(
+    db: &SupabaseClient,
+    session: &Session,
+    user_id: i32,
+    status: ApprovalStatus,
+    action: &str,
+) -> Result<User, String> {
+    require(session, Permission::ManageUsers)?;
+    let user = load_user(db, user_id).await?;
+    if user.approval_status == status {
+        return Ok(user);
+    }
+    if status == ApprovalStatus::Rejected && user.approval_status == ApprovalStatus::Approved {
+        return Err("只能拒绝待审批的注册申请，在职员工请使用停用".to_string());
+    }
+
+    let before = snapshot(db, "users", user_id).await?;
+    db.patch(
+        "users",
+        vec![("id", &format!("eq.{}", user_id))],
+        &json!({ "approval_status": status }),
+    )
+    .await
+    .map_err(|e| format!("Failed to update user: {}", e))?;
+
+    let after = snapshot(db, "users", user_id).await?;
+    record_audit(db, session, action, "users", Some(user_id), before, after).await?;
+
+    load_user(db, user_id).await
+}
+
+/// 通过注册申请，员工即可登录打卡
+#[tauri::command]
+pub async fn approve_registration(
+    user_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<User, String> {
+    set_approval_status(&db, &session, user_id, ApprovalStatus::Approved, "approve_registration").await
+}
+
+#[tauri::command]
+pub async fn reject_registration(
+    user_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<User, String> {
+    set_approval_status(&db, &session, user_id, ApprovalStatus::Rejected, "reject_registration").await
+}
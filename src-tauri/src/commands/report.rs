@@ -0,0 +1,353 @@
+use crate::database::SupabaseClient;
+use crate::export::pdf::{load_cjk_font, PdfColumn, PdfReport};
+use crate::export::ExportLocale;
+use crate::models::User;
+use crate::session::Session;
+use crate::utils::attendance::{AttendanceSummary, DayStatus, DaySummary};
+use crate::utils::time::{format_time, get_company_time, parse_date};
+use super::access::{ensure_can_view, view_scope};
+use super::admin::load_system_config;
+use super::export::{month_range, ExportResult};
+use super::statistics::{load_team_attendance, load_user_attendance, UserAttendance};
+use chrono::Datelike;
+use tauri::State;
+use serde::Deserialize;
+
+/// system_config key of a TTF/OTF font used for PDF reports
+pub const PDF_FONT_PATH_KEY: &str = "pdf_font_path";
+
+#[derive(Debug, Deserialize)]
+pub struct PdfReportRequest {
+    pub path: String,
+    pub year: i32,
+    pub month: u32,
+    /// 员工报表必填，团队报表忽略
+    pub user_id: Option<i32>,
+    /// 团队报表只包含该部门及下属团队的成员
+    pub department_id: Option<i32>,
+    #[serde(default)]
+    pub locale: ExportLocale,
+}
+
+fn status_label(status: DayStatus, locale: ExportLocale) -> &'static str {
+    match status {
+        DayStatus::Present => locale.pick("正常", "Present"),
+        DayStatus::Late => locale.pick("迟到", "Late"),
+        DayStatus::EarlyLeave => locale.pick("早退", "Early leave"),
+        DayStatus::Absent => locale.pick("缺勤", "Absent"),
+        DayStatus::Leave => locale.pick("请假", "Leave"),
+        DayStatus::Holiday => locale.pick("节假日", "Holiday"),
+        DayStatus::RestDay => locale.pick("休息", "Rest day"),
+        DayStatus::Upcoming => "-",
+        DayStatus::NotEmployed => locale.pick("未在职", "Not employed"),
+    }
+}
+
+fn weekday_label(date: &str, locale: ExportLocale) -> &'static str {
+    const ZH: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+    const EN: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    parse_date(date)
+        .map(|d| {
+            let index = d.weekday().num_days_from_monday() as usize;
+            locale.pick(ZH[index], EN[index])
+        })
+        .unwrap_or("")
+}
+
+fn hours(minutes: i32) -> String {
+    format!("{:.2}", minutes as f64 / 60.0)
+}
+
+fn columns(specs: &[(&str, &str, f32)], locale: ExportLocale) -> Vec<PdfColumn> {
+    specs
+        .iter()
+        .map(|(zh, en, width)| PdfColumn {
+            header: locale.pick(zh, en).to_string(),
+            width: *width,
+        })
+        .collect()
+}
+
+fn summary_lines(report: &mut PdfReport, summary: &AttendanceSummary, locale: ExportLocale) {
+    let lines = match locale {
+        ExportLocale::Zh => vec![
+            format!(
+                "应出勤 {} 天，出勤 {} 天，缺勤 {} 天，请假 {} 天",
+                summary.scheduled_days, summary.attended_days, summary.absent_days, summary.leave_days
+            ),
+            format!(
+                "迟到 {} 次共 {} 分钟，早退 {} 次共 {} 分钟",
+                summary.late_days, summary.late_minutes, summary.early_leave_days, summary.early_leave_minutes
+            ),
+            format!(
+                "净工作时长 {} 小时，休息 {} 分钟",
+                hours(summary.net_work_minutes), summary.break_minutes
+            ),
+            format!(
+                "加班 {} 小时（工作日 {}，休息日 {}，节假日 {}）",
+                hours(summary.overtime_minutes),
+                hours(summary.workday_overtime_minutes),
+                hours(summary.rest_day_overtime_minutes),
+                hours(summary.holiday_overtime_minutes)
+            ),
+        ],
+        ExportLocale::En => vec![
+            format!(
+                "Scheduled {} days, attended {}, absent {}, on leave {}",
+                summary.scheduled_days, summary.attended_days, summary.absent_days, summary.leave_days
+            ),
+            format!(
+                "Late {} times ({} min), left early {} times ({} min)",
+                summary.late_days, summary.late_minutes, summary.early_leave_days, summary.early_leave_minutes
+            ),
+            format!(
+                "Net work {} h, breaks {} min",
+                hours(summary.net_work_minutes), summary.break_minutes
+            ),
+            format!(
+                "Overtime {} h (workday {}, rest day {}, holiday {})",
+                hours(summary.overtime_minutes),
+                hours(summary.workday_overtime_minutes),
+                hours(summary.rest_day_overtime_minutes),
+                hours(summary.holiday_overtime_minutes)
+            ),
+        ],
+    };
+
+    for line in lines {
+        report.line(&line);
+    }
+}
+
+fn render_employee_report(
+    request: &PdfReportRequest,
+    font: &[u8],
+    user: &User,
+    days: &[DaySummary],
+    summary: &AttendanceSummary,
+    period: &str,
+    generated_at: &str,
+) -> Result<(), String> {
+    let locale = request.locale;
+    let title = locale.pick("员工月度考勤报表", "Monthly Attendance Report");
+    let mut report = PdfReport::new(title, font)?;
+
+    report.title(title);
+    report.line(&format!("{}: {} ({})", locale.pick("员工", "Employee"), user.full_name, user.username));
+    report.line(&format!("{}: {}", locale.pick("统计周期", "Period"), period));
+    report.line(&format!("{}: {}", locale.pick("生成时间", "Generated"), generated_at));
+    report.gap(4.0);
+
+    let table_columns = columns(
+        &[
+            ("日期", "Date", 22.0),
+            ("星期", "Day", 12.0),
+            ("状态", "Status", 20.0),
+            ("上班", "In", 16.0),
+            ("下班", "Out", 20.0),
+            ("迟到(分)", "Late", 16.0),
+            ("早退(分)", "Early", 16.0),
+            ("工时(时)", "Work (h)", 18.0),
+            ("休息(分)", "Break", 16.0),
+            ("加班(分)", "OT (min)", 18.0),
+        ],
+        locale,
+    );
+    let rows: Vec<Vec<String>> = days
+        .iter()
+        .map(|day| {
+            let overtime = day.workday_overtime_minutes + day.rest_day_overtime_minutes + day.holiday_overtime_minutes;
+            vec![
+                day.date.clone(),
+                weekday_label(&day.date, locale).to_string(),
+                status_label(day.status, locale).to_string(),
+                day.first_in.clone().unwrap_or_default(),
+                day.last_out.clone().unwrap_or_default(),
+                day.late_minutes.to_string(),
+                day.early_leave_minutes.to_string(),
+                hours(day.work_minutes),
+                day.break_minutes.to_string(),
+                overtime.to_string(),
+            ]
+        })
+        .collect();
+    report.table(&table_columns, &rows);
+
+    report.gap(6.0);
+    summary_lines(&mut report, summary, locale);
+
+    report.signature_lines(&[
+        locale.pick("员工签字", "Employee"),
+        locale.pick("主管签字", "Supervisor"),
+        locale.pick("日期", "Date"),
+    ]);
+    report.save(&request.path)
+}
+
+fn render_team_report(
+    request: &PdfReportRequest,
+    font: &[u8],
+    team: &[UserAttendance],
+    period: &str,
+    generated_at: &str,
+) -> Result<(), String> {
+    let locale = request.locale;
+    let title = locale.pick("团队月度考勤报表", "Team Monthly Attendance Report");
+    let mut report = PdfReport::new(title, font)?;
+
+    report.title(title);
+    report.line(&format!("{}: {}", locale.pick("统计周期", "Period"), period));
+    report.line(&format!("{}: {}", locale.pick("员工人数", "Employees"), team.len()));
+    report.line(&format!("{}: {}", locale.pick("生成时间", "Generated"), generated_at));
+    report.gap(4.0);
+
+    // 员工汇总表
+    let summary_columns = columns(
+        &[
+            ("姓名", "Name", 34.0),
+            ("应出勤", "Sched.", 16.0),
+            ("出勤", "Att.", 14.0),
+            ("缺勤", "Abs.", 14.0),
+            ("请假", "Leave", 14.0),
+            ("迟到次", "Late", 14.0),
+            ("迟到分", "Late min", 16.0),
+            ("早退分", "Early min", 18.0),
+            ("工时(时)", "Work (h)", 18.0),
+            ("加班(时)", "OT (h)", 18.0),
+        ],
+        locale,
+    );
+    let mut totals = AttendanceSummary::default();
+    let mut rows: Vec<Vec<String>> = team
+        .iter()
+        .map(|member| {
+            let s = &member.totals;
+            totals.scheduled_days += s.scheduled_days;
+            totals.attended_days += s.attended_days;
+            totals.absent_days += s.absent_days;
+            totals.leave_days += s.leave_days;
+            totals.late_days += s.late_days;
+            totals.late_minutes += s.late_minutes;
+            totals.early_leave_minutes += s.early_leave_minutes;
+            totals.net_work_minutes += s.net_work_minutes;
+            totals.overtime_minutes += s.overtime_minutes;
+            summary_row(&member.user.full_name, s)
+        })
+        .collect();
+    rows.push(summary_row(locale.pick("合计", "Total"), &totals));
+    report.table(&summary_columns, &rows);
+    report.gap(6.0);
+
+    // 每日出勤情况
+    let daily_columns = columns(
+        &[
+            ("日期", "Date", 24.0),
+            ("星期", "Day", 14.0),
+            ("出勤", "Present", 18.0),
+            ("迟到", "Late", 18.0),
+            ("早退", "Early", 18.0),
+            ("缺勤", "Absent", 18.0),
+            ("请假", "Leave", 18.0),
+        ],
+        locale,
+    );
+    let day_count = team.first().map(|m| m.days.len()).unwrap_or(0);
+    let daily_rows: Vec<Vec<String>> = (0..day_count)
+        .map(|i| {
+            let date = team[0].days[i].date.clone();
+            let count = |predicate: fn(&DaySummary) -> bool| {
+                team.iter().filter(|m| predicate(&m.days[i])).count().to_string()
+            };
+            vec![
+                date.clone(),
+                weekday_label(&date, locale).to_string(),
+                count(|d| d.shift_count > 0),
+                count(|d| d.is_late),
+                count(|d| d.is_early_leave),
+                count(|d| d.status == DayStatus::Absent),
+                count(|d| d.status == DayStatus::Leave),
+            ]
+        })
+        .collect();
+    report.table(&daily_columns, &daily_rows);
+
+    report.signature_lines(&[
+        locale.pick("制表人", "Prepared by"),
+        locale.pick("主管签字", "Supervisor"),
+        locale.pick("日期", "Date"),
+    ]);
+    report.save(&request.path)
+}
+
+fn summary_row(name: &str, s: &AttendanceSummary) -> Vec<String> {
+    vec![
+        name.to_string(),
+        s.scheduled_days.to_string(),
+        s.attended_days.to_string(),
+        s.absent_days.to_string(),
+        s.leave_days.to_string(),
+        s.late_days.to_string(),
+        s.late_minutes.to_string(),
+        s.early_leave_minutes.to_string(),
+        hours(s.net_work_minutes),
+        hours(s.overtime_minutes),
+    ]
+}
+
+async fn load_report_font(db: &SupabaseClient) -> Result<Vec<u8>, String> {
+    let config = load_system_config(db).await;
+    load_cjk_font(config.get(PDF_FONT_PATH_KEY).map(String::as_str))
+}
+
+/// 生成单个员工的月度考勤 PDF（路径由前端保存对话框选择）
+#[tauri::command]
+pub async fn export_employee_pdf_report(
+    request: PdfReportRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<ExportResult, String> {
+    let user_id = request.user_id.ok_or_else(|| "请选择员工".to_string())?;
+    ensure_can_view(&db, &session, user_id).await?;
+    let (start, end) = month_range(request.year, request.month)?;
+
+    let users: Vec<User> = db
+        .get("users", Some(vec![("id", &format!("eq.{}", user_id))]))
+        .await
+        .map_err(|e| format!("Failed to get user: {}", e))?;
+    let user = users.into_iter().next().ok_or_else(|| "User not found".to_string())?;
+
+    let (days, summary) = load_user_attendance(&db, user_id, start, end).await?;
+    let font = load_report_font(&db).await?;
+    let generated_at = format_time(&get_company_time("Asia/Phnom_Penh"));
+    let period = start.format("%Y-%m").to_string();
+
+    render_employee_report(&request, &font, &user, &days, &summary, &period, &generated_at)?;
+
+    Ok(ExportResult {
+        path: request.path,
+        row_count: days.len(),
+    })
+}
+
+/// 生成团队月度考勤 PDF：员工汇总、每日出勤与签字栏
+#[tauri::command]
+pub async fn export_team_pdf_report(
+    request: PdfReportRequest,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<ExportResult, String> {
+    let (start, end) = month_range(request.year, request.month)?;
+
+    let scope = view_scope(&db, &session).await?;
+    let team = load_team_attendance(&db, start, end, request.department_id, &scope).await?;
+    let font = load_report_font(&db).await?;
+    let generated_at = format_time(&get_company_time("Asia/Phnom_Penh"));
+    let period = start.format("%Y-%m").to_string();
+
+    render_team_report(&request, &font, &team, &period, &generated_at)?;
+
+    Ok(ExportResult {
+        path: request.path,
+        row_count: team.len(),
+    })
+}
@@ -1,5 +1,14 @@
 use crate::database::SupabaseClient;
-use crate::models::CheckIn;
+use crate::models::{ActionType, CheckIn, Department, Holiday, Leave, TimeRule, User};
+use crate::utils::attendance::{employee_schedule, summarize_attendance, summarize_days, AttendanceContext, AttendanceSummary, DayStatus, DaySummary};
+use crate::utils::profile::employment_end;
+use crate::utils::permissions::AttendanceScope;
+use crate::session::Session;
+use crate::utils::time::{get_company_time, parse_date};
+use super::admin::{department_user_ids, load_departments, load_holidays, load_leaves};
+use super::access::{ensure_can_view, view_scope};
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
 use tauri::State;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +19,8 @@ pub struct CheckInStatistics {
     pub on_time_count: i32,
     pub total_work_minutes: i32,
     pub average_work_minutes: i32,
+    #[serde(flatten)]
+    pub summary: AttendanceSummary,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,50 +32,305 @@ pub struct PaginatedCheckIns {
     pub total_pages: i32,
 }
 
-#[tauri::command]
-pub async fn get_user_statistics(
+#[derive(Debug, Serialize)]
+pub struct UserAttendanceRow {
+    pub user_id: i32,
+    pub username: String,
+    pub full_name: String,
+    pub is_active: bool,  // 已停用的员工仍显示在看板中
+    pub statuses: Vec<DayStatus>,  // 与 dates 一一对应
+    pub totals: AttendanceSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceDashboard {
+    pub start_date: String,
+    pub end_date: String,
+    pub dates: Vec<String>,
+    pub rows: Vec<UserAttendanceRow>,
+}
+
+/// Reference data shared by every attendance calculation over a date range
+pub(crate) struct AttendanceReference {
+    pub action_types: Vec<ActionType>,
+    pub rules: Vec<TimeRule>,
+    pub departments: Vec<Department>,
+    pub holidays: Vec<Holiday>,
+    pub today: NaiveDate,
+}
+
+impl AttendanceReference {
+    pub(crate) const TIMEZONE: &'static str = "Asia/Phnom_Penh";
+
+    /// Context for an employee: the default schedule of the employee when set,
+    /// otherwise the schedule of its department or the company. A deactivated
+    /// employee is not employed after the day of deactivation.
+    pub(crate) fn context<'a>(&'a self, user: &User, leaves: &'a [Leave]) -> AttendanceContext<'a> {
+        let schedule = employee_schedule(&self.rules, &self.action_types, &self.departments, user);
+        AttendanceContext {
+            action_types: &self.action_types,
+            schedule,
+            holidays: &self.holidays,
+            leaves,
+            timezone: Self::TIMEZONE,
+            today: self.today,
+            hired_on: user.hire_date.as_deref().and_then(parse_date),
+            terminated_on: employment_end(
+                user.termination_date.as_deref(),
+                user.deactivated_at.as_deref(),
+                Self::TIMEZONE,
+            ),
+        }
+    }
+}
+
+pub(crate) async fn load_attendance_reference(
+    db: &SupabaseClient,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<AttendanceReference, String> {
+    let action_types: Vec<ActionType> = db
+        .get("action_types", None)
+        .await
+        .map_err(|e| format!("Failed to get action types: {}", e))?;
+
+    let rules: Vec<TimeRule> = db
+        .get("time_rules", Some(vec![("is_active", "eq.true")]))
+        .await
+        .map_err(|e| format!("Failed to get time rules: {}", e))?;
+
+    let departments = load_departments(db).await?;
+
+    let holidays = load_holidays(
+        db,
+        &start.format("%Y-%m-%d").to_string(),
+        &end.format("%Y-%m-%d").to_string(),
+    )
+    .await;
+
+    Ok(AttendanceReference {
+        action_types,
+        rules,
+        departments,
+        holidays,
+        today: get_company_time(AttendanceReference::TIMEZONE).date_naive(),
+    })
+}
+
+/// Parse and validate a statistics date range
+pub(crate) fn parse_date_range(start_date: &str, end_date: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let start = parse_date(start_date).ok_or_else(|| "Invalid start date".to_string())?;
+    let end = parse_date(end_date).ok_or_else(|| "Invalid end date".to_string())?;
+    if end < start {
+        return Err("结束日期不能早于开始日期".to_string());
+    }
+    Ok((start, end))
+}
+
+/// check_time filters covering a date range, plus one extra day so that
+/// cross-day shifts keep their end record
+pub(crate) fn check_time_filters(start: NaiveDate, end: NaiveDate) -> (String, String) {
+    let query_end = end + Duration::days(1);
+    (
+        format!("gte.{}T00:00:00", start.format("%Y-%m-%d")),
+        format!("lte.{}T23:59:59", query_end.format("%Y-%m-%d")),
+    )
+}
+
+/// Per-day attendance and totals of one employee over a date range
+pub(crate) async fn load_user_attendance(
+    db: &SupabaseClient,
     user_id: i32,
-    start_date: String,
-    end_date: String,
-    db: State<'_, SupabaseClient>,
-) -> Result<CheckInStatistics, String> {
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<(Vec<DaySummary>, AttendanceSummary), String> {
+    let (start_filter, end_filter) = check_time_filters(start, end);
+
     let check_ins: Vec<CheckIn> = db
-        .get(
+        .get_all(
             "check_ins",
-            Some(vec![
+            &[
                 ("user_id", &format!("eq.{}", user_id)),
-                ("check_time", &format!("gte.{}", start_date)),
-                ("check_time", &format!("lte.{}", end_date)),
-            ]),
+                ("check_time", &start_filter),
+                ("check_time", &end_filter),
+                ("order", "check_time.asc,id.asc"),
+            ],
         )
         .await
         .map_err(|e| format!("Failed to get check-ins: {}", e))?;
 
-    let late_count = check_ins.iter().filter(|c| c.is_late).count() as i32;
-    let on_time_count = check_ins.len() as i32 - late_count;
-    
-    let total_work_minutes: i32 = check_ins
-        .iter()
-        .filter_map(|c| c.duration_minutes)
-        .sum();
-    
-    let completed_days = check_ins
-        .iter()
-        .filter(|c| c.status == "completed" && c.duration_minutes.is_some())
-        .count() as i32;
-    
-    let average_work_minutes = if completed_days > 0 {
-        total_work_minutes / completed_days
+    let users: Vec<User> = db
+        .get("users", Some(vec![("id", &format!("eq.{}", user_id))]))
+        .await
+        .map_err(|e| format!("Failed to get user: {}", e))?;
+    let user = users.into_iter().next().ok_or_else(|| "User not found".to_string())?;
+
+    let reference = load_attendance_reference(db, start, end).await?;
+    let leaves = load_leaves(
+        db,
+        Some(user_id),
+        &start.format("%Y-%m-%d").to_string(),
+        &end.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+
+    let days = summarize_days(&check_ins, start, end, &reference.context(&user, &leaves));
+    let summary = summarize_attendance(&days, reference.today);
+
+    Ok((days, summary))
+}
+
+#[tauri::command]
+pub async fn get_user_statistics(
+    user_id: i32,
+    start_date: String,
+    end_date: String,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<CheckInStatistics, String> {
+    ensure_can_view(&db, &session, user_id).await?;
+    let (start, end) = parse_date_range(&start_date, &end_date)?;
+    let (_, summary) = load_user_attendance(&db, user_id, start, end).await?;
+
+    let average_work_minutes = if summary.attended_days > 0 {
+        summary.net_work_minutes / summary.attended_days
     } else {
         0
     };
 
     Ok(CheckInStatistics {
-        total_days: completed_days,
-        late_count,
-        on_time_count,
-        total_work_minutes,
+        total_days: summary.attended_days,
+        late_count: summary.late_days,
+        on_time_count: summary.attended_days - summary.late_days,
+        total_work_minutes: summary.net_work_minutes,
         average_work_minutes,
+        summary,
+    })
+}
+
+/// Attendance of one employee over a date range
+pub(crate) struct UserAttendance {
+    pub user: User,
+    pub days: Vec<DaySummary>,
+    pub totals: AttendanceSummary,
+}
+
+/// Compute the attendance of every employee in scope over a date range, optionally only
+/// for the members of a department and its teams.
+/// All check-ins are fetched in one paged query and grouped per employee in memory.
+pub(crate) async fn load_team_attendance(
+    db: &SupabaseClient,
+    start: NaiveDate,
+    end: NaiveDate,
+    department_id: Option<i32>,
+    scope: &AttendanceScope,
+) -> Result<Vec<UserAttendance>, String> {
+    let (start_filter, end_filter) = check_time_filters(start, end);
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.format("%Y-%m-%d").to_string();
+
+    let mut params = vec![
+        ("check_time", start_filter.as_str()),
+        ("check_time", end_filter.as_str()),
+        ("order", "check_time.asc,id.asc"),
+    ];
+    let user_filter;
+    let mut users: Vec<User> = db
+        .get("users", Some(vec![("order", "id.asc")]))
+        .await
+        .map_err(|e| format!("Failed to get users: {}", e))?;
+    users.retain(|u| scope.allows(u.id));
+    let scope_filter = scope.user_filter();
+    if let Some(filter) = &scope_filter {
+        params.push(("user_id", filter));
+    }
+    if let Some(department_id) = department_id {
+        let member_ids = department_user_ids(db, department_id).await?;
+        if member_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        users.retain(|u| member_ids.contains(&u.id));
+        user_filter = format!(
+            "in.({})",
+            member_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+        );
+        params.push(("user_id", &user_filter));
+    }
+
+    let check_ins: Vec<CheckIn> = db
+        .get_all("check_ins", &params)
+        .await
+        .map_err(|e| format!("Failed to get check-ins: {}", e))?;
+
+    let reference = load_attendance_reference(db, start, end).await?;
+    let leaves = load_leaves(db, None, &start_str, &end_str).await?;
+
+    let mut check_ins_by_user: HashMap<i32, Vec<CheckIn>> = HashMap::new();
+    for check_in in check_ins {
+        check_ins_by_user.entry(check_in.user_id).or_default().push(check_in);
+    }
+    let mut leaves_by_user: HashMap<i32, Vec<Leave>> = HashMap::new();
+    for leave in leaves {
+        leaves_by_user.entry(leave.user_id).or_default().push(leave);
+    }
+
+    Ok(users
+        .into_iter()
+        .map(|user| {
+            let user_check_ins = check_ins_by_user.remove(&user.id).unwrap_or_default();
+            let user_leaves = leaves_by_user.remove(&user.id).unwrap_or_default();
+            let days = summarize_days(
+                &user_check_ins,
+                start,
+                end,
+                &reference.context(&user, &user_leaves),
+            );
+            let totals = summarize_attendance(&days, reference.today);
+
+            UserAttendance { user, days, totals }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_attendance_dashboard(
+    start_date: String,
+    end_date: String,
+    department_id: Option<i32>,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<AttendanceDashboard, String> {
+    let scope = view_scope(&db, &session).await?;
+    let (start, end) = parse_date_range(&start_date, &end_date)?;
+    if (end - start).num_days() >= 366 {
+        return Err("日期范围不能超过一年".to_string());
+    }
+
+    let mut dates = Vec::new();
+    let mut date = start;
+    while date <= end {
+        dates.push(date.format("%Y-%m-%d").to_string());
+        date += Duration::days(1);
+    }
+
+    let rows = load_team_attendance(&db, start, end, department_id, &scope)
+        .await?
+        .into_iter()
+        .map(|attendance| UserAttendanceRow {
+            user_id: attendance.user.id,
+            username: attendance.user.username,
+            full_name: attendance.user.full_name,
+            is_active: attendance.user.is_active,
+            statuses: attendance.days.iter().map(|d| d.status).collect(),
+            totals: attendance.totals,
+        })
+        .collect();
+
+    Ok(AttendanceDashboard {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        dates,
+        rows,
     })
 }
 
@@ -73,11 +339,16 @@ pub async fn get_all_check_ins(
     start_date: Option<String>,
     end_date: Option<String>,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<Vec<CheckIn>, String> {
     let mut params = vec![("order", "check_time.desc")];
     
     let start_filter;
     let end_filter;
+    let scope_filter = view_scope(&db, &session).await?.user_filter();
+    if let Some(filter) = &scope_filter {
+        params.push(("user_id", filter));
+    }
     
     if let Some(start) = &start_date {
         // Add T00:00:00 to start date to match beginning of day
@@ -99,6 +370,8 @@ pub async fn get_all_check_ins(
     Ok(check_ins)
 }
 
+// 命令参数由前端按名称传入
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn get_paginated_check_ins(
     start_date: Option<String>,
@@ -106,13 +379,21 @@ pub async fn get_paginated_check_ins(
     page: i32,
     page_size: i32,
     user_id: Option<i32>,
+    department_id: Option<i32>,
     db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
 ) -> Result<PaginatedCheckIns, String> {
     let mut params = vec![("order", "check_time.desc")];
     
     let start_filter;
     let end_filter;
     let user_filter;
+    let department_filter;
+    // 只返回当前用户有权查看的员工的记录
+    let scope_filter = view_scope(&db, &session).await?.user_filter();
+    if let Some(filter) = &scope_filter {
+        params.push(("user_id", filter));
+    }
     
     if let Some(start) = &start_date {
         start_filter = format!("gte.{}T00:00:00", start);
@@ -129,6 +410,16 @@ pub async fn get_paginated_check_ins(
         params.push(("user_id", &user_filter));
     }
 
+    // 部门筛选包含下属团队的成员
+    if let Some(department_id) = department_id {
+        let member_ids = department_user_ids(&db, department_id).await?;
+        department_filter = format!(
+            "in.({})",
+            member_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+        );
+        params.push(("user_id", &department_filter));
+    }
+
     // 先获取总数
     let all_check_ins: Vec<CheckIn> = db
         .get("check_ins", Some(params.clone()))
@@ -0,0 +1,167 @@
+use crate::database::SupabaseClient;
+use crate::models::{LoginRequest, LoginThrottle, LoginThrottlePolicy, ThrottleScope};
+use crate::session::Session;
+use crate::utils::permissions::Permission;
+use crate::utils::throttle::{login_block, parse_timestamp, record_failure, LoginBlock, ThrottleState};
+use super::access::require;
+use super::admin::load_system_config;
+use super::audit::{record_audit, snapshot};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use tauri::State;
+
+const MAX_DEVICE_ID_LENGTH: usize = 255;
+
+/// Failure counts that apply to one login attempt
+pub(crate) struct LoginThrottles {
+    policy: LoginThrottlePolicy,
+    now: DateTime<Utc>,
+    entries: Vec<(ThrottleScope, String, Option<LoginThrottle>)>,
+}
+
+fn scope_name(scope: ThrottleScope) -> &'static str {
+    match scope {
+        ThrottleScope::User => "user",
+        ThrottleScope::Device => "device",
+    }
+}
+
+fn state_of(row: Option<&LoginThrottle>) -> ThrottleState {
+    row.map(|row| ThrottleState {
+        failed_count: row.failed_count,
+        last_failed_at: parse_timestamp(row.last_failed_at.as_deref()),
+        locked_until: parse_timestamp(row.locked_until.as_deref()),
+    })
+    .unwrap_or_default()
+}
+
+/// 用户名不存在时同样计数，避免通过提示区分账号是否存在
+fn throttle_keys(credentials: &LoginRequest) -> Vec<(ThrottleScope, String)> {
+    let mut keys = vec![(ThrottleScope::User, credentials.username.trim().to_lowercase())];
+    if let Some(device_id) = credentials
+        .device_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty() && d.len() <= MAX_DEVICE_ID_LENGTH)
+    {
+        keys.push((ThrottleScope::Device, device_id.to_string()));
+    }
+    keys
+}
+
+/// Refuse the attempt while the username or the device is locked or backing off
+pub(crate) async fn check_login_throttle(
+    db: &SupabaseClient,
+    credentials: &LoginRequest,
+) -> Result<LoginThrottles, String> {
+    let policy = LoginThrottlePolicy::from_config(&load_system_config(db).await);
+    let now = Utc::now();
+
+    let mut entries = Vec::new();
+    for (scope, key) in throttle_keys(credentials) {
+        let scope_filter = format!("eq.{}", scope_name(scope));
+        let key_filter = format!("eq.{}", key);
+        let rows: Vec<LoginThrottle> = db
+            .get("login_throttles", Some(vec![("scope", &scope_filter), ("key", &key_filter)]))
+            .await
+            .map_err(|e| format!("Failed to get login throttles: {}", e))?;
+        let row = rows.into_iter().next();
+
+        match login_block(&policy, &state_of(row.as_ref()), now) {
+            Some(LoginBlock::Locked(until)) => {
+                let minutes = ((until - now).num_seconds() + 59) / 60;
+                return Err(format!("登录失败次数过多，已临时锁定，请 {} 分钟后重试或联系管理员解锁", minutes));
+            }
+            Some(LoginBlock::Backoff(until)) => {
+                let seconds = ((until - now).num_milliseconds() + 999) / 1000;
+                return Err(format!("登录尝试过于频繁，请 {} 秒后重试", seconds));
+            }
+            None => {}
+        }
+        entries.push((scope, key, row));
+    }
+
+    Ok(LoginThrottles { policy, now, entries })
+}
+
+/// Count a failed attempt; reaching the limit locks the key and is written to the audit log
+pub(crate) async fn record_login_failure(
+    db: &SupabaseClient,
+    session: &Session,
+    throttles: &LoginThrottles,
+) -> Result<(), String> {
+    for (scope, key, row) in &throttles.entries {
+        let state = record_failure(&throttles.policy, &state_of(row.as_ref()), throttles.now);
+        let updated: LoginThrottle = db
+            .upsert(
+                "login_throttles",
+                "scope,key",
+                &json!({
+                    "scope": scope,
+                    "key": key,
+                    "failed_count": state.failed_count,
+                    "last_failed_at": state.last_failed_at.map(|t| t.to_rfc3339()),
+                    "locked_until": state.locked_until.map(|t| t.to_rfc3339()),
+                }),
+            )
+            .await
+            .map_err(|e| format!("Failed to record login failure: {}", e))?;
+
+        if state.locked_until.is_some() {
+            let before = row.as_ref().and_then(|r| serde_json::to_value(r).ok());
+            let after = serde_json::to_value(&updated).ok();
+            record_audit(db, session, "login_locked", "login_throttles", Some(updated.id), before, after).await?;
+        }
+    }
+    Ok(())
+}
+
+/// A successful login clears the failure counts of the username and the device
+pub(crate) async fn clear_login_failures(db: &SupabaseClient, throttles: &LoginThrottles) -> Result<(), String> {
+    for (_, _, row) in &throttles.entries {
+        if let Some(row) = row {
+            db.delete("login_throttles", vec![("id", &format!("eq.{}", row.id))])
+                .await
+                .map_err(|e| format!("Failed to clear login failures: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// 当前被锁定的用户名和设备
+#[tauri::command]
+pub async fn get_login_lockouts(
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<LoginThrottle>, String> {
+    require(&session, Permission::ManageUsers)?;
+    let now = format!("gt.{}", Utc::now().to_rfc3339());
+    db.get(
+        "login_throttles",
+        Some(vec![("locked_until", &now), ("order", "locked_until.desc")]),
+    )
+    .await
+    .map_err(|e| format!("Failed to get login lockouts: {}", e))
+}
+
+/// 管理员解除锁定，同时清除失败计数
+#[tauri::command]
+pub async fn unlock_login(
+    throttle_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    require(&session, Permission::ManageUsers)?;
+
+    let before = snapshot(&db, "login_throttles", throttle_id).await?;
+    if before.is_none() {
+        return Err("锁定记录不存在".to_string());
+    }
+    db.delete("login_throttles", vec![("id", &format!("eq.{}", throttle_id))])
+        .await
+        .map_err(|e| format!("Failed to unlock login: {}", e))?;
+
+    record_audit(&db, &session, "unlock_login", "login_throttles", Some(throttle_id), before, None).await?;
+
+    Ok(())
+}
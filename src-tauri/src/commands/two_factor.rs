@@ -0,0 +1,257 @@
+use crate::database::SupabaseClient;
+use crate::models::{LoginRequest, LoginResponse, TwoFactorEnabled, TwoFactorSetup, User};
+use crate::session::Session;
+use crate::utils::crypto::{hash_password, verify_password};
+use crate::utils::permissions::Permission;
+use crate::utils::totp::{generate_recovery_code, generate_secret, normalize_recovery_code, provisioning_uri, verify_totp};
+use super::access::require;
+use super::admin::load_system_config;
+use super::audit::{record_audit, snapshot};
+use super::auth::finish_login;
+use super::profile::load_user;
+use super::throttle::{check_login_throttle, clear_login_failures, record_login_failure};
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+const REQUIRE_ADMIN_TWO_FACTOR_KEY: &str = "require_admin_two_factor";
+const TOTP_ISSUER: &str = "员工打卡系统";
+const RECOVERY_CODE_COUNT: usize = 10;
+
+async fn admin_two_factor_required(db: &SupabaseClient) -> bool {
+    load_system_config(db)
+        .await
+        .get(REQUIRE_ADMIN_TWO_FACTOR_KEY)
+        .is_some_and(|v| v.trim() == "true")
+}
+
+/// Flag admins who must enroll before doing anything else, when the company requires it
+pub(crate) async fn apply_two_factor_requirement(db: &SupabaseClient, user: &mut User) {
+    user.two_factor_setup_required =
+        user.is_admin && !user.totp_enabled && admin_two_factor_required(db).await;
+}
+
+/// 设置两步验证不经过 current_user 的检查，须启用的管理员也能调用
+fn enrolling_user(session: &Session) -> Result<User, String> {
+    let user = session.current().ok_or_else(|| "请先登录".to_string())?;
+    if user.must_change_password {
+        return Err("请先修改密码".to_string());
+    }
+    Ok(user)
+}
+
+/// New recovery codes and the hashes to store
+fn new_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT).map(|_| generate_recovery_code()).collect();
+    let hashes = codes.iter().map(|c| hash_password(&normalize_recovery_code(c))).collect();
+    (codes, hashes)
+}
+
+fn cleared_two_factor() -> Value {
+    json!({
+        "totp_secret": null,
+        "totp_enabled": false,
+        "totp_recovery_codes": [],
+        "totp_last_step": null,
+    })
+}
+
+async fn update_user(db: &SupabaseClient, user_id: i32, data: &Value) -> Result<(), String> {
+    db.patch("users", vec![("id", &format!("eq.{}", user_id))], data)
+        .await
+        .map_err(|e| format!("Failed to update user: {}", e))
+}
+
+/// Reload the signed-in user after a change to their own account
+async fn refresh_session(db: &SupabaseClient, session: &Session, user_id: i32) -> Result<User, String> {
+    let mut user = load_user(db, user_id).await?;
+    apply_two_factor_requirement(db, &mut user).await;
+    session.set(user.clone());
+    Ok(user)
+}
+
+/// 生成新的密钥和二维码，用验证码确认后才启用
+#[tauri::command]
+pub async fn begin_two_factor_setup(
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<TwoFactorSetup, String> {
+    let user = load_user(&db, enrolling_user(&session)?.id).await?;
+    if user.totp_enabled {
+        return Err("两步验证已启用".to_string());
+    }
+
+    let secret = generate_secret();
+    update_user(&db, user.id, &json!({ "totp_secret": secret })).await?;
+
+    let provisioning_uri = provisioning_uri(TOTP_ISSUER, &user.username, &secret);
+    let qr_svg = QrCode::new(provisioning_uri.as_bytes())
+        .map_err(|e| format!("Failed to create QR code: {}", e))?
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .build();
+
+    Ok(TwoFactorSetup { secret, provisioning_uri, qr_svg })
+}
+
+/// 用验证器上的验证码确认后启用，返回只显示一次的恢复码
+#[tauri::command]
+pub async fn enable_two_factor(
+    code: String,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<TwoFactorEnabled, String> {
+    let user = load_user(&db, enrolling_user(&session)?.id).await?;
+    if user.totp_enabled {
+        return Err("两步验证已启用".to_string());
+    }
+    let secret = user.totp_secret.as_deref().ok_or_else(|| "请先生成两步验证密钥".to_string())?;
+    let step = verify_totp(secret, &code, chrono::Utc::now().timestamp(), None)
+        .ok_or_else(|| "验证码错误".to_string())?;
+
+    let (recovery_codes, hashes) = new_recovery_codes();
+    let before = snapshot(&db, "users", user.id).await?;
+    update_user(
+        &db,
+        user.id,
+        &json!({
+            "totp_enabled": true,
+            "totp_last_step": step,
+            "totp_recovery_codes": hashes,
+        }),
+    )
+    .await?;
+
+    let after = snapshot(&db, "users", user.id).await?;
+    record_audit(&db, &session, "enable_two_factor", "users", Some(user.id), before, after).await?;
+
+    let user = refresh_session(&db, &session, user.id).await?;
+    Ok(TwoFactorEnabled { user, recovery_codes })
+}
+
+/// 关闭自己的两步验证，须输入登录密码
+#[tauri::command]
+pub async fn disable_two_factor(
+    password: String,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<User, String> {
+    let user = load_user(&db, enrolling_user(&session)?.id).await?;
+    if !user.totp_enabled {
+        return Err("两步验证未启用".to_string());
+    }
+    if !verify_password(&password, &user.password_hash) {
+        return Err("密码错误".to_string());
+    }
+    if user.is_admin && admin_two_factor_required(&db).await {
+        return Err("管理员账号必须启用两步验证".to_string());
+    }
+
+    let before = snapshot(&db, "users", user.id).await?;
+    update_user(&db, user.id, &cleared_two_factor()).await?;
+    let after = snapshot(&db, "users", user.id).await?;
+    record_audit(&db, &session, "disable_two_factor", "users", Some(user.id), before, after).await?;
+
+    refresh_session(&db, &session, user.id).await
+}
+
+/// 重新生成恢复码，旧的恢复码全部作废
+#[tauri::command]
+pub async fn regenerate_recovery_codes(
+    code: String,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    let user = load_user(&db, enrolling_user(&session)?.id).await?;
+    let step = user
+        .totp_secret
+        .as_deref()
+        .filter(|_| user.totp_enabled)
+        .ok_or_else(|| "两步验证未启用".to_string())
+        .map(|secret| verify_totp(secret, &code, chrono::Utc::now().timestamp(), user.totp_last_step))?
+        .ok_or_else(|| "验证码错误".to_string())?;
+
+    let (recovery_codes, hashes) = new_recovery_codes();
+    let before = snapshot(&db, "users", user.id).await?;
+    update_user(&db, user.id, &json!({ "totp_last_step": step, "totp_recovery_codes": hashes })).await?;
+    let after = snapshot(&db, "users", user.id).await?;
+    record_audit(&db, &session, "regenerate_recovery_codes", "users", Some(user.id), before, after).await?;
+
+    Ok(recovery_codes)
+}
+
+/// 员工丢失验证器时由管理员清除其两步验证，员工可重新设置
+#[tauri::command]
+pub async fn reset_user_two_factor(
+    user_id: i32,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<(), String> {
+    let admin = require(&session, Permission::ManageUsers)?;
+    if admin.id == user_id {
+        return Err("请在个人设置中管理自己的两步验证".to_string());
+    }
+    load_user(&db, user_id).await?;
+
+    let before = snapshot(&db, "users", user_id).await?;
+    update_user(&db, user_id, &cleared_two_factor()).await?;
+    let after = snapshot(&db, "users", user_id).await?;
+    record_audit(&db, &session, "reset_user_two_factor", "users", Some(user_id), before, after).await?;
+
+    Ok(())
+}
+
+/// 登录第二步：验证器上的验证码或一个未使用的恢复码
+#[tauri::command]
+pub async fn verify_login_code(
+    code: String,
+    app: AppHandle,
+    db: State<'_, SupabaseClient>,
+    session: State<'_, Session>,
+) -> Result<LoginResponse, String> {
+    let pending = session.pending_login().ok_or_else(|| "验证已超时，请重新登录".to_string())?;
+    // 验证码错误与密码错误计入同一失败次数
+    let credentials = LoginRequest {
+        username: pending.username.clone(),
+        password: String::new(),
+        device_id: pending.device_id.clone(),
+    };
+    let throttles = check_login_throttle(&db, &credentials).await?;
+
+    let user = load_user(&db, pending.user_id).await?;
+    if !user.is_active || !user.totp_enabled {
+        session.clear();
+        return Err("请重新登录".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let step = user
+        .totp_secret
+        .as_deref()
+        .and_then(|secret| verify_totp(secret, &code, now, user.totp_last_step));
+    let code_hash = hash_password(&normalize_recovery_code(&code));
+    let recovery_used = step.is_none() && user.totp_recovery_codes.contains(&code_hash);
+    if step.is_none() && !recovery_used {
+        record_login_failure(&db, &session, &throttles).await?;
+        return Err("验证码错误".to_string());
+    }
+    clear_login_failures(&db, &throttles).await?;
+
+    let before = if recovery_used { snapshot(&db, "users", user.id).await? } else { None };
+    if let Some(step) = step {
+        update_user(&db, user.id, &json!({ "totp_last_step": step })).await?;
+    } else {
+        let remaining: Vec<&String> = user.totp_recovery_codes.iter().filter(|h| **h != code_hash).collect();
+        update_user(&db, user.id, &json!({ "totp_recovery_codes": remaining })).await?;
+    }
+
+    let user_id = user.id;
+    let response = finish_login(&app, &db, &session, load_user(&db, user_id).await?).await;
+    if recovery_used {
+        let after = snapshot(&db, "users", user_id).await?;
+        record_audit(&db, &session, "use_recovery_code", "users", Some(user_id), before, after).await?;
+    }
+
+    Ok(response)
+}
@@ -1,4 +1,5 @@
 // Database module
 pub mod supabase;
+pub mod realtime;
 
 pub use supabase::*;
@@ -0,0 +1,184 @@
+use crate::database::SupabaseClient;
+use crate::models::Role;
+use crate::session::Session;
+use anyhow::{anyhow, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// Emitted for every insert or update on `check_ins`, payload is a [`CheckInChange`].
+/// Carries no row data: every window receives it, so listeners reload through
+/// the regular commands, which apply the session's view scope
+pub const CHECK_IN_CHANGED_EVENT: &str = "check-in-changed";
+/// Emitted when the realtime connection goes up or down, payload is a [`RealtimeStatus`]
+pub const REALTIME_STATUS_EVENT: &str = "realtime-status";
+
+const CHECK_INS_TOPIC: &str = "realtime:public:check_ins";
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(25);
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RealtimeStatus {
+    pub connected: bool,
+    /// 连接失败、断开或推送数据无法解析的原因
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckInChange {
+    pub event_type: String,  // INSERT / UPDATE
+}
+
+/// Phoenix channel message as sent by Supabase Realtime
+#[derive(Debug, Deserialize)]
+struct ChannelMessage {
+    event: String,
+    #[serde(default)]
+    payload: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostgresChange {
+    #[serde(rename = "type")]
+    event_type: String,
+}
+
+/// Starts the check-in subscription at most once per app instance. The
+/// subscription outlives logins, so changes are only forwarded while the
+/// signed-in user may see other employees (see [`may_receive_changes`]).
+#[derive(Default)]
+pub struct RealtimeListener {
+    started: AtomicBool,
+}
+
+impl RealtimeListener {
+    pub fn ensure_started(&self, app: &AppHandle, db: &SupabaseClient) {
+        if !self.started.swap(true, Ordering::SeqCst) {
+            spawn_check_in_listener(app.clone(), db.clone());
+        }
+    }
+}
+
+/// Whether the current session may be told about other employees' check-ins
+fn may_receive_changes(app: &AppHandle) -> bool {
+    app.state::<Session>()
+        .current()
+        .is_some_and(|user| user.role != Role::Employee)
+}
+
+fn emit_status(app: &AppHandle, connected: bool, error: Option<String>) {
+    let _ = app.emit_all(REALTIME_STATUS_EVENT, RealtimeStatus { connected, error });
+}
+
+/// Keep a Supabase Realtime subscription on `check_ins` open for the lifetime of the app,
+/// reconnecting with exponential backoff when the connection drops
+fn spawn_check_in_listener(app: AppHandle, db: SupabaseClient) {
+    tauri::async_runtime::spawn(async move {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        loop {
+            match connect_async(db.realtime_url()).await {
+                Ok((socket, _)) => {
+                    delay = INITIAL_RECONNECT_DELAY;
+                    let error = run_session(&app, socket)
+                        .await
+                        .err()
+                        .map(|e| format!("Realtime connection lost: {}", e));
+                    emit_status(&app, false, error);
+                }
+                Err(e) => emit_status(&app, false, Some(format!("Failed to connect to realtime: {}", e))),
+            }
+
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    });
+}
+
+async fn run_session<S>(app: &AppHandle, socket: tokio_tungstenite::WebSocketStream<S>) -> Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut write, mut read) = socket.split();
+
+    let join = json!({
+        "topic": CHECK_INS_TOPIC,
+        "event": "phx_join",
+        "payload": {
+            "config": {
+                "postgres_changes": [
+                    { "event": "INSERT", "schema": "public", "table": "check_ins" },
+                    { "event": "UPDATE", "schema": "public", "table": "check_ins" },
+                ]
+            }
+        },
+        "ref": "1",
+    });
+    write.send(Message::Text(join.to_string())).await?;
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut message_ref: u64 = 1;
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                message_ref += 1;
+                let beat = json!({
+                    "topic": "phoenix",
+                    "event": "heartbeat",
+                    "payload": {},
+                    "ref": message_ref.to_string(),
+                });
+                write.send(Message::Text(beat.to_string())).await?;
+            }
+            message = read.next() => match message {
+                Some(Ok(Message::Text(text))) => handle_message(app, &text)?,
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+}
+
+fn handle_message(app: &AppHandle, text: &str) -> Result<()> {
+    let message: ChannelMessage = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(_) => return Ok(()),
+    };
+
+    match message.event.as_str() {
+        "phx_reply" => {
+            let status = message.payload.get("status").and_then(Value::as_str);
+            if status == Some("error") {
+                return Err(anyhow!("Realtime subscription rejected: {}", message.payload));
+            }
+            if status == Some("ok") && message.payload.pointer("/response/postgres_changes").is_some() {
+                emit_status(app, true, None);
+            }
+        }
+        // 退出登录或换成普通员工登录后不再转发
+        "postgres_changes" if !may_receive_changes(app) => {}
+        "postgres_changes" => {
+            let data = message.payload.get("data").cloned().unwrap_or(Value::Null);
+            match serde_json::from_value::<PostgresChange>(data) {
+                Ok(change) => {
+                    let _ = app.emit_all(
+                        CHECK_IN_CHANGED_EVENT,
+                        CheckInChange {
+                            event_type: change.event_type,
+                        },
+                    );
+                }
+                Err(e) => emit_status(app, true, Some(format!("Failed to parse realtime change: {}", e))),
+            }
+        }
+        "phx_error" => return Err(anyhow!("Realtime channel error")),
+        _ => {}
+    }
+
+    Ok(())
+}
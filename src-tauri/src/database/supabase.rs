@@ -17,6 +17,22 @@ impl SupabaseClient {
         }
     }
 
+    /// Websocket endpoint of Supabase Realtime for this project
+    pub fn realtime_url(&self) -> String {
+        let ws_base = if let Some(rest) = self.base_url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = self.base_url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            self.base_url.clone()
+        };
+        format!(
+            "{}/realtime/v1/websocket?apikey={}&vsn=1.0.0",
+            ws_base.trim_end_matches('/'),
+            self.api_key
+        )
+    }
+
     /// GET request to Supabase
     pub async fn get<T: serde::de::DeserializeOwned>(
         &self,
@@ -45,6 +61,74 @@ impl SupabaseClient {
         Ok(data)
     }
 
+    /// GET one page of rows; callers should pass an `order` param so pages are stable
+    pub async fn get_page<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        params: &[(&str, &str)],
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<T>> {
+        let offset = offset.to_string();
+        let limit = limit.to_string();
+        let mut page_params = params.to_vec();
+        page_params.push(("offset", &offset));
+        page_params.push(("limit", &limit));
+
+        self.get(table, Some(page_params)).await
+    }
+
+    /// GET all matching rows, page by page, to get past the server-side row limit
+    pub async fn get_all<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Vec<T>> {
+        const PAGE_SIZE: usize = 1000;
+
+        let mut rows = Vec::new();
+        loop {
+            let page: Vec<T> = self.get_page(table, params, rows.len(), PAGE_SIZE).await?;
+            let page_len = page.len();
+            rows.extend(page);
+            if page_len < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Count matching rows without fetching them
+    pub async fn count(
+        &self,
+        table: &str,
+        params: &[(&str, &str)],
+    ) -> Result<usize> {
+        let url = format!("{}/rest/v1/{}", self.base_url, table);
+        
+        let response = self.client
+            .head(&url)
+            .header("apikey", &self.api_key)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Prefer", "count=exact")
+            .query(params)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            return Err(anyhow!("Supabase error: {}", response.status()));
+        }
+        
+        // Content-Range: 0-24/3573 或 */0
+        response
+            .headers()
+            .get("content-range")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|range| range.rsplit('/').next())
+            .and_then(|total| total.parse().ok())
+            .context("Missing row count in response")
+    }
+
     /// POST request to Supabase
     pub async fn post<T: serde::Serialize, R: serde::de::DeserializeOwned>(
         &self,
@@ -72,6 +156,90 @@ impl SupabaseClient {
         results.pop().context("No data returned from Supabase")
     }
 
+    /// POST request with upsert semantics, merging on the given unique column
+    pub async fn upsert<T: serde::Serialize, R: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        on_conflict: &str,
+        data: &T,
+    ) -> Result<R> {
+        let url = format!("{}/rest/v1/{}", self.base_url, table);
+        
+        let response = self.client
+            .post(&url)
+            .header("apikey", &self.api_key)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation,resolution=merge-duplicates")
+            .query(&[("on_conflict", on_conflict)])
+            .json(data)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("Supabase error: {}", error_text));
+        }
+        
+        let mut results = response.json::<Vec<R>>().await?;
+        results.pop().context("No data returned from Supabase")
+    }
+
+    /// POST several rows in one request; rows come back in the order they were sent
+    pub async fn post_many<T: serde::Serialize, R: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        rows: &[T],
+    ) -> Result<Vec<R>> {
+        let url = format!("{}/rest/v1/{}", self.base_url, table);
+        
+        let response = self.client
+            .post(&url)
+            .header("apikey", &self.api_key)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(rows)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("Supabase error: {}", error_text));
+        }
+        
+        let results = response.json::<Vec<R>>().await?;
+        Ok(results)
+    }
+
+    /// Upsert several rows in one request, merging on the given unique column
+    pub async fn upsert_many<T: serde::Serialize>(
+        &self,
+        table: &str,
+        on_conflict: &str,
+        rows: &[T],
+    ) -> Result<()> {
+        let url = format!("{}/rest/v1/{}", self.base_url, table);
+        
+        let response = self.client
+            .post(&url)
+            .header("apikey", &self.api_key)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=minimal,resolution=merge-duplicates")
+            .query(&[("on_conflict", on_conflict)])
+            .json(rows)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("Supabase error: {}", error_text));
+        }
+        
+        Ok(())
+    }
+
     /// PATCH request to Supabase
     pub async fn patch<T: serde::Serialize>(
         &self,
@@ -122,4 +290,30 @@ impl SupabaseClient {
         
         Ok(())
     }
+
+    /// Call a Postgres function; it runs in a single transaction
+    pub async fn rpc<T: serde::Serialize, R: serde::de::DeserializeOwned>(
+        &self,
+        function: &str,
+        args: &T,
+    ) -> Result<R> {
+        let url = format!("{}/rest/v1/rpc/{}", self.base_url, function);
+        
+        let response = self.client
+            .post(&url)
+            .header("apikey", &self.api_key)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(args)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("Supabase error: {}", error_text));
+        }
+        
+        let result = response.json::<R>().await?;
+        Ok(result)
+    }
 }
@@ -0,0 +1,257 @@
+use super::{format_timestamp, Cell, ExportLocale};
+use crate::models::{ActionType, CheckIn, OvertimeCategory, User};
+use crate::utils::attendance::AttendanceSummary;
+use serde::{Deserialize, Serialize};
+
+/// Columns available when exporting check-in records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckInColumn {
+    Id,
+    Username,
+    FullName,
+    ActionName,
+    CheckTime,
+    Status,
+    DurationMinutes,
+    NetMinutes,
+    IsLate,
+    IsEarlyLeave,
+    IsOvertimeBreak,
+    OvertimeMinutes,
+    OvertimeCategory,
+    IsManual,
+    Note,
+}
+
+impl CheckInColumn {
+    pub const DEFAULT: &'static [CheckInColumn] = &[
+        CheckInColumn::Username,
+        CheckInColumn::FullName,
+        CheckInColumn::ActionName,
+        CheckInColumn::CheckTime,
+        CheckInColumn::Status,
+        CheckInColumn::DurationMinutes,
+        CheckInColumn::IsLate,
+        CheckInColumn::IsEarlyLeave,
+        CheckInColumn::IsManual,
+        CheckInColumn::Note,
+    ];
+
+    pub fn header(&self, locale: ExportLocale) -> &'static str {
+        match self {
+            CheckInColumn::Id => locale.pick("记录ID", "Record ID"),
+            CheckInColumn::Username => locale.pick("用户名", "Username"),
+            CheckInColumn::FullName => locale.pick("姓名", "Full Name"),
+            CheckInColumn::ActionName => locale.pick("打卡类型", "Action"),
+            CheckInColumn::CheckTime => locale.pick("打卡时间", "Check Time"),
+            CheckInColumn::Status => locale.pick("状态", "Status"),
+            CheckInColumn::DurationMinutes => locale.pick("时长(分钟)", "Duration (min)"),
+            CheckInColumn::NetMinutes => locale.pick("净工作时长(分钟)", "Net Work (min)"),
+            CheckInColumn::IsLate => locale.pick("迟到", "Late"),
+            CheckInColumn::IsEarlyLeave => locale.pick("早退", "Early Leave"),
+            CheckInColumn::IsOvertimeBreak => locale.pick("超额休息", "Overtime Break"),
+            CheckInColumn::OvertimeMinutes => locale.pick("加班(分钟)", "Overtime (min)"),
+            CheckInColumn::OvertimeCategory => locale.pick("加班类别", "Overtime Category"),
+            CheckInColumn::IsManual => locale.pick("手动补卡", "Manual"),
+            CheckInColumn::Note => locale.pick("备注", "Note"),
+        }
+    }
+
+    pub fn cell(
+        &self,
+        check_in: &CheckIn,
+        user: Option<&User>,
+        action_type: Option<&ActionType>,
+        locale: ExportLocale,
+    ) -> Cell {
+        match self {
+            CheckInColumn::Id => check_in.id.into(),
+            CheckInColumn::Username => user.map(|u| u.username.as_str()).into(),
+            CheckInColumn::FullName => user.map(|u| u.full_name.as_str()).into(),
+            CheckInColumn::ActionName => action_type.map(|at| at.name.as_str()).into(),
+            CheckInColumn::CheckTime => format_timestamp(&check_in.check_time).into(),
+            CheckInColumn::Status => match check_in.status.as_str() {
+                "ongoing" => locale.pick("进行中", "Ongoing").into(),
+                "completed" => locale.pick("已完成", "Completed").into(),
+                other => other.into(),
+            },
+            CheckInColumn::DurationMinutes => check_in.duration_minutes.into(),
+            CheckInColumn::NetMinutes => check_in.net_minutes.into(),
+            CheckInColumn::IsLate => yes_no(check_in.is_late, locale),
+            CheckInColumn::IsEarlyLeave => yes_no(check_in.is_early_leave, locale),
+            CheckInColumn::IsOvertimeBreak => yes_no(check_in.is_overtime_break, locale),
+            CheckInColumn::OvertimeMinutes => check_in.overtime_minutes.into(),
+            CheckInColumn::OvertimeCategory => check_in
+                .overtime_category
+                .map(|category| overtime_category_label(category, locale))
+                .into(),
+            CheckInColumn::IsManual => yes_no(check_in.is_manual, locale),
+            CheckInColumn::Note => check_in.note.as_deref().into(),
+        }
+    }
+}
+
+/// Columns of the per-employee attendance report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttendanceColumn {
+    UserId,
+    Username,
+    FullName,
+    ScheduledDays,
+    AttendedDays,
+    AbsentDays,
+    LeaveDays,
+    LateDays,
+    LateMinutes,
+    EarlyLeaveDays,
+    EarlyLeaveMinutes,
+    NetWorkMinutes,
+    BreakMinutes,
+    OvertimeMinutes,
+    WorkdayOvertimeMinutes,
+    RestDayOvertimeMinutes,
+    HolidayOvertimeMinutes,
+    Period,
+    NetWorkHours,
+    OvertimeHours,
+    WorkdayOvertimeHours,
+    RestDayOvertimeHours,
+    HolidayOvertimeHours,
+}
+
+impl AttendanceColumn {
+    pub const DEFAULT: &'static [AttendanceColumn] = &[
+        AttendanceColumn::Username,
+        AttendanceColumn::FullName,
+        AttendanceColumn::ScheduledDays,
+        AttendanceColumn::AttendedDays,
+        AttendanceColumn::AbsentDays,
+        AttendanceColumn::LeaveDays,
+        AttendanceColumn::LateDays,
+        AttendanceColumn::LateMinutes,
+        AttendanceColumn::EarlyLeaveDays,
+        AttendanceColumn::EarlyLeaveMinutes,
+        AttendanceColumn::NetWorkMinutes,
+        AttendanceColumn::BreakMinutes,
+        AttendanceColumn::OvertimeMinutes,
+    ];
+
+    pub fn header(&self, locale: ExportLocale) -> &'static str {
+        match self {
+            AttendanceColumn::UserId => locale.pick("员工ID", "Employee ID"),
+            AttendanceColumn::Username => locale.pick("用户名", "Username"),
+            AttendanceColumn::FullName => locale.pick("姓名", "Full Name"),
+            AttendanceColumn::ScheduledDays => locale.pick("应出勤天数", "Scheduled Days"),
+            AttendanceColumn::AttendedDays => locale.pick("出勤天数", "Attended Days"),
+            AttendanceColumn::AbsentDays => locale.pick("缺勤天数", "Absent Days"),
+            AttendanceColumn::LeaveDays => locale.pick("请假天数", "Leave Days"),
+            AttendanceColumn::LateDays => locale.pick("迟到次数", "Late Days"),
+            AttendanceColumn::LateMinutes => locale.pick("迟到(分钟)", "Late (min)"),
+            AttendanceColumn::EarlyLeaveDays => locale.pick("早退次数", "Early Leave Days"),
+            AttendanceColumn::EarlyLeaveMinutes => locale.pick("早退(分钟)", "Early Leave (min)"),
+            AttendanceColumn::NetWorkMinutes => locale.pick("净工作时长(分钟)", "Net Work (min)"),
+            AttendanceColumn::BreakMinutes => locale.pick("休息(分钟)", "Breaks (min)"),
+            AttendanceColumn::OvertimeMinutes => locale.pick("加班(分钟)", "Overtime (min)"),
+            AttendanceColumn::WorkdayOvertimeMinutes => locale.pick("工作日加班(分钟)", "Workday Overtime (min)"),
+            AttendanceColumn::RestDayOvertimeMinutes => locale.pick("休息日加班(分钟)", "Rest Day Overtime (min)"),
+            AttendanceColumn::HolidayOvertimeMinutes => locale.pick("节假日加班(分钟)", "Holiday Overtime (min)"),
+            AttendanceColumn::Period => locale.pick("统计周期", "Period"),
+            AttendanceColumn::NetWorkHours => locale.pick("净工作时长(小时)", "Net Work (h)"),
+            AttendanceColumn::OvertimeHours => locale.pick("加班(小时)", "Overtime (h)"),
+            AttendanceColumn::WorkdayOvertimeHours => locale.pick("工作日加班(小时)", "Workday Overtime (h)"),
+            AttendanceColumn::RestDayOvertimeHours => locale.pick("休息日加班(小时)", "Rest Day Overtime (h)"),
+            AttendanceColumn::HolidayOvertimeHours => locale.pick("节假日加班(小时)", "Holiday Overtime (h)"),
+        }
+    }
+
+    /// `period` is the label of the reported range, e.g. "2024-05"
+    pub fn cell(&self, user: &User, summary: &AttendanceSummary, period: &str) -> Cell {
+        match self {
+            AttendanceColumn::UserId => user.id.into(),
+            AttendanceColumn::Username => user.username.as_str().into(),
+            AttendanceColumn::FullName => user.full_name.as_str().into(),
+            AttendanceColumn::ScheduledDays => summary.scheduled_days.into(),
+            AttendanceColumn::AttendedDays => summary.attended_days.into(),
+            AttendanceColumn::AbsentDays => summary.absent_days.into(),
+            AttendanceColumn::LeaveDays => summary.leave_days.into(),
+            AttendanceColumn::LateDays => summary.late_days.into(),
+            AttendanceColumn::LateMinutes => summary.late_minutes.into(),
+            AttendanceColumn::EarlyLeaveDays => summary.early_leave_days.into(),
+            AttendanceColumn::EarlyLeaveMinutes => summary.early_leave_minutes.into(),
+            AttendanceColumn::NetWorkMinutes => summary.net_work_minutes.into(),
+            AttendanceColumn::BreakMinutes => summary.break_minutes.into(),
+            AttendanceColumn::OvertimeMinutes => summary.overtime_minutes.into(),
+            AttendanceColumn::WorkdayOvertimeMinutes => summary.workday_overtime_minutes.into(),
+            AttendanceColumn::RestDayOvertimeMinutes => summary.rest_day_overtime_minutes.into(),
+            AttendanceColumn::HolidayOvertimeMinutes => summary.holiday_overtime_minutes.into(),
+            AttendanceColumn::Period => period.into(),
+            AttendanceColumn::NetWorkHours => hours(summary.net_work_minutes),
+            AttendanceColumn::OvertimeHours => hours(summary.overtime_minutes),
+            AttendanceColumn::WorkdayOvertimeHours => hours(summary.workday_overtime_minutes),
+            AttendanceColumn::RestDayOvertimeHours => hours(summary.rest_day_overtime_minutes),
+            AttendanceColumn::HolidayOvertimeHours => hours(summary.holiday_overtime_minutes),
+        }
+    }
+}
+
+/// Minutes as hours rounded to two decimals
+fn hours(minutes: i32) -> Cell {
+    Cell::Number((minutes as f64 / 60.0 * 100.0).round() / 100.0)
+}
+
+fn yes_no(value: bool, locale: ExportLocale) -> Cell {
+    if value {
+        locale.pick("是", "Yes").into()
+    } else {
+        locale.pick("否", "No").into()
+    }
+}
+
+pub fn overtime_category_label(category: OvertimeCategory, locale: ExportLocale) -> &'static str {
+    match category {
+        OvertimeCategory::Workday => locale.pick("工作日延时", "Workday"),
+        OvertimeCategory::RestDay => locale.pick("休息日", "Rest Day"),
+        OvertimeCategory::Holiday => locale.pick("节假日", "Holiday"),
+    }
+}
+
+/// A column of the payroll timesheet template, optionally with a custom header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimesheetColumn {
+    pub column: AttendanceColumn,
+    pub header: Option<String>,
+}
+
+impl TimesheetColumn {
+    pub fn header(&self, locale: ExportLocale) -> String {
+        match &self.header {
+            Some(header) if !header.trim().is_empty() => header.clone(),
+            _ => self.column.header(locale).to_string(),
+        }
+    }
+}
+
+/// Default payroll timesheet template
+pub fn default_timesheet_template() -> Vec<TimesheetColumn> {
+    [
+        AttendanceColumn::UserId,
+        AttendanceColumn::Username,
+        AttendanceColumn::FullName,
+        AttendanceColumn::Period,
+        AttendanceColumn::ScheduledDays,
+        AttendanceColumn::AttendedDays,
+        AttendanceColumn::NetWorkHours,
+        AttendanceColumn::LateMinutes,
+        AttendanceColumn::EarlyLeaveMinutes,
+        AttendanceColumn::WorkdayOvertimeHours,
+        AttendanceColumn::RestDayOvertimeHours,
+        AttendanceColumn::HolidayOvertimeHours,
+        AttendanceColumn::LeaveDays,
+        AttendanceColumn::AbsentDays,
+    ]
+    .into_iter()
+    .map(|column| TimesheetColumn { column, header: None })
+    .collect()
+}
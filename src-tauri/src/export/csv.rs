@@ -0,0 +1,41 @@
+use super::{Cell, TableWriter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+pub struct CsvTableWriter {
+    writer: ::csv::Writer<BufWriter<File>>,
+}
+
+impl CsvTableWriter {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
+        let mut buffer = BufWriter::new(file);
+        // UTF-8 BOM，确保 Excel 直接打开时中文不乱码
+        buffer
+            .write_all(b"\xEF\xBB\xBF")
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+
+        Ok(Self {
+            writer: ::csv::Writer::from_writer(buffer),
+        })
+    }
+}
+
+impl TableWriter for CsvTableWriter {
+    fn write_row(&mut self, row: &[Cell]) -> Result<(), String> {
+        let fields = row.iter().map(|cell| match cell {
+            Cell::Text(text) => text.clone(),
+            Cell::Number(number) => number.to_string(),
+            Cell::Empty => String::new(),
+        });
+        self.writer
+            .write_record(fields)
+            .map_err(|e| format!("Failed to write CSV row: {}", e))
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+}
@@ -0,0 +1,84 @@
+// Export module
+pub mod csv;
+pub mod xlsx;
+pub mod columns;
+pub mod pdf;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+/// Language of headers and enumerated values in exported files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportLocale {
+    #[default]
+    Zh,
+    En,
+}
+
+impl ExportLocale {
+    /// Pick the Chinese or English variant of a label
+    pub fn pick<'a>(&self, zh: &'a str, en: &'a str) -> &'a str {
+        match self {
+            ExportLocale::Zh => zh,
+            ExportLocale::En => en,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(Cell::Empty)
+    }
+}
+
+/// A tabular file written one row at a time, so exports never hold every row in memory
+pub trait TableWriter: Send {
+    fn write_row(&mut self, row: &[Cell]) -> Result<(), String>;
+    fn finish(self: Box<Self>) -> Result<(), String>;
+}
+
+/// Create a writer for the given format; the file is created immediately
+pub fn create_writer(format: ExportFormat, path: &str, sheet_name: &str) -> Result<Box<dyn TableWriter>, String> {
+    match format {
+        ExportFormat::Csv => Ok(Box::new(csv::CsvTableWriter::create(path)?)),
+        ExportFormat::Xlsx => Ok(Box::new(xlsx::XlsxTableWriter::create(path, sheet_name)?)),
+    }
+}
+
+/// Normalize a stored timestamp ("2024-01-01T08:00:00+00:00") to "2024-01-01 08:00:00"
+pub fn format_timestamp(value: &str) -> String {
+    let trimmed = value.get(..19).unwrap_or(value);
+    trimmed.replacen('T', " ", 1)
+}
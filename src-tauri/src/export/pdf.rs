@@ -0,0 +1,172 @@
+use printpdf::{IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const ROW_HEIGHT: f32 = 6.5;
+const TABLE_FONT_SIZE: f32 = 8.5;
+
+/// CJK fonts shipped with Windows, macOS and common Linux distributions.
+/// Only single-face TTF/OTF files are listed: font collections (.ttc) cannot be embedded as-is.
+const CJK_FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simkai.ttf",
+    "C:\\Windows\\Fonts\\simfang.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/opentype/noto/NotoSansSC-Regular.otf",
+    "/usr/share/fonts/noto-cjk/NotoSansSC-Regular.otf",
+    "/usr/share/fonts/truetype/arphic/uming.ttf",
+];
+
+/// Load a font able to render Chinese text: the configured path first, then well-known system fonts
+pub fn load_cjk_font(configured_path: Option<&str>) -> Result<Vec<u8>, String> {
+    if let Some(path) = configured_path.filter(|p| !p.trim().is_empty()) {
+        return std::fs::read(path).map_err(|e| format!("无法读取 PDF 字体 {}: {}", path, e));
+    }
+
+    CJK_FONT_CANDIDATES
+        .iter()
+        .filter(|path| Path::new(path).exists())
+        .find_map(|path| std::fs::read(path).ok())
+        .ok_or_else(|| "未找到可用的中文字体，请在系统配置 pdf_font_path 中指定 TTF 字体路径".to_string())
+}
+
+/// A column of a PDF table; widths are in millimetres
+pub struct PdfColumn {
+    pub header: String,
+    pub width: f32,
+}
+
+/// Minimal flowing A4 layout: text lines and tables that continue on new pages
+pub struct PdfReport {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    y: f32,
+    page_count: usize,
+}
+
+impl PdfReport {
+    pub fn new(title: &str, font_bytes: &[u8]) -> Result<Self, String> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let font = doc
+            .add_external_font(font_bytes)
+            .map_err(|e| format!("Failed to load PDF font: {}", e))?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        Ok(Self {
+            doc,
+            layer,
+            font,
+            y: PAGE_HEIGHT - MARGIN,
+            page_count: 1,
+        })
+    }
+
+    fn new_page(&mut self) {
+        self.page_count += 1;
+        let (page, layer) = self.doc.add_page(
+            Mm(PAGE_WIDTH),
+            Mm(PAGE_HEIGHT),
+            format!("Layer {}", self.page_count),
+        );
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Start a new page when less than `height` millimetres are left
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn text_at(&self, text: &str, size: f32, x: f32, y: f32) {
+        self.layer.use_text(text, size, Mm(x), Mm(y), &self.font);
+    }
+
+    fn horizontal_line(&self, x1: f32, x2: f32, y: f32) {
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(x1), Mm(y)), false),
+                (Point::new(Mm(x2), Mm(y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    pub fn title(&mut self, text: &str) {
+        self.ensure_space(12.0);
+        self.y -= 8.0;
+        self.text_at(text, 16.0, MARGIN, self.y);
+        self.y -= 4.0;
+    }
+
+    pub fn line(&mut self, text: &str) {
+        self.ensure_space(ROW_HEIGHT);
+        self.y -= 5.0;
+        self.text_at(text, 10.0, MARGIN, self.y);
+        self.y -= 1.5;
+    }
+
+    pub fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// Table with a header row, repeated on every page the table spans
+    pub fn table(&mut self, columns: &[PdfColumn], rows: &[Vec<String>]) {
+        let right = MARGIN + columns.iter().map(|c| c.width).sum::<f32>();
+
+        self.ensure_space(ROW_HEIGHT * 2.0);
+        self.table_header(columns, right);
+
+        for row in rows {
+            if self.y - ROW_HEIGHT < MARGIN {
+                self.new_page();
+                self.table_header(columns, right);
+            }
+            self.y -= ROW_HEIGHT;
+            let mut x = MARGIN;
+            for (column, value) in columns.iter().zip(row) {
+                self.text_at(value, TABLE_FONT_SIZE, x + 1.0, self.y + 2.0);
+                x += column.width;
+            }
+            self.horizontal_line(MARGIN, right, self.y);
+        }
+    }
+
+    fn table_header(&mut self, columns: &[PdfColumn], right: f32) {
+        self.horizontal_line(MARGIN, right, self.y);
+        self.y -= ROW_HEIGHT;
+        let mut x = MARGIN;
+        for column in columns {
+            self.text_at(&column.header, TABLE_FONT_SIZE, x + 1.0, self.y + 2.0);
+            x += column.width;
+        }
+        self.horizontal_line(MARGIN, right, self.y);
+    }
+
+    /// Signature lines at the bottom of the report
+    pub fn signature_lines(&mut self, labels: &[&str]) {
+        self.ensure_space(25.0);
+        self.y -= 20.0;
+        let slot = (PAGE_WIDTH - MARGIN * 2.0) / labels.len().max(1) as f32;
+        for (i, label) in labels.iter().enumerate() {
+            let x = MARGIN + slot * i as f32;
+            self.text_at(label, 10.0, x, self.y);
+            self.horizontal_line(x + 22.0, x + slot - 8.0, self.y - 1.0);
+        }
+    }
+
+    pub fn save(self, path: &str) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
+        self.doc
+            .save(&mut BufWriter::new(file))
+            .map_err(|e| format!("Failed to save PDF: {}", e))
+    }
+}
@@ -0,0 +1,66 @@
+use super::{Cell, TableWriter};
+use rust_xlsxwriter::{Format, Workbook};
+
+/// Rows per worksheet supported by Excel
+const MAX_ROWS: u32 = 1_048_576;
+
+/// XLSX writer in constant memory mode: rows are flushed to a temp file as they are written
+pub struct XlsxTableWriter {
+    workbook: Workbook,
+    path: String,
+    header_format: Format,
+    row: u32,
+}
+
+impl XlsxTableWriter {
+    pub fn create(path: &str, sheet_name: &str) -> Result<Self, String> {
+        let mut workbook = Workbook::new();
+        workbook
+            .add_worksheet_with_constant_memory()
+            .set_name(sheet_name)
+            .map_err(|e| format!("Failed to create worksheet: {}", e))?;
+
+        Ok(Self {
+            workbook,
+            path: path.to_string(),
+            header_format: Format::new().set_bold(),
+            row: 0,
+        })
+    }
+}
+
+impl TableWriter for XlsxTableWriter {
+    fn write_row(&mut self, row: &[Cell]) -> Result<(), String> {
+        if self.row >= MAX_ROWS {
+            return Err(format!("超过 Excel 单表 {} 行上限，请缩小日期范围或导出为 CSV", MAX_ROWS));
+        }
+
+        let worksheet = self
+            .workbook
+            .worksheet_from_index(0)
+            .map_err(|e| format!("Failed to get worksheet: {}", e))?;
+
+        for (col, cell) in row.iter().enumerate() {
+            let col = col as u16;
+            let result = match cell {
+                // 首行为表头
+                Cell::Text(text) if self.row == 0 => {
+                    worksheet.write_string_with_format(self.row, col, text, &self.header_format)
+                }
+                Cell::Text(text) => worksheet.write_string(self.row, col, text),
+                Cell::Number(number) => worksheet.write_number(self.row, col, *number),
+                Cell::Empty => continue,
+            };
+            result.map_err(|e| format!("Failed to write XLSX row: {}", e))?;
+        }
+
+        self.row += 1;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), String> {
+        self.workbook
+            .save(&self.path)
+            .map_err(|e| format!("Failed to save file: {}", e))
+    }
+}
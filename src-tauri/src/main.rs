@@ -1,12 +1,17 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod backup;
 mod commands;
 mod database;
+mod export;
 mod models;
+mod session;
 mod utils;
 
+use database::realtime::RealtimeListener;
 use database::SupabaseClient;
+use session::Session;
 use tauri::{CustomMenuItem, Menu, MenuItem, Submenu};
 
 fn create_menu() -> Menu {
@@ -117,11 +122,22 @@ fn main() {
             }
         })
         .manage(db)
+        .manage(Session::default())
+        .manage(RealtimeListener::default())
         .invoke_handler(tauri::generate_handler![
             // Auth commands
             commands::login,
+            commands::logout,
             commands::register,
             commands::get_current_user,
+            commands::change_password,
+            commands::get_password_policy,
+            commands::get_registration_settings,
+            commands::verify_login_code,
+            commands::begin_two_factor_setup,
+            commands::enable_two_factor,
+            commands::disable_two_factor,
+            commands::regenerate_recovery_codes,
             // Check-in commands
             commands::create_check_in,
             commands::create_manual_check_in,
@@ -131,20 +147,73 @@ fn main() {
             commands::update_check_in,
             // Admin commands
             commands::get_all_users,
-            commands::update_user_admin_status,
-            commands::delete_user,
+            commands::set_user_role,
+            commands::deactivate_user,
+            commands::restore_user,
+            commands::reset_user_password,
+            commands::get_login_lockouts,
+            commands::unlock_login,
+            commands::reset_user_two_factor,
+            commands::get_invite_codes,
+            commands::create_invite_code,
+            commands::revoke_invite_code,
+            commands::approve_registration,
+            commands::reject_registration,
+            commands::get_user_profile,
+            commands::create_user,
+            commands::update_user_profile,
+            commands::update_my_profile,
+            commands::get_departments,
+            commands::create_department,
+            commands::update_department,
+            commands::delete_department,
+            commands::set_user_department,
             commands::get_all_action_types,
             commands::create_action_type,
             commands::update_action_type,
             commands::delete_action_type,
+            commands::archive_action_type,
+            commands::restore_action_type,
+            commands::merge_action_types,
             commands::get_all_time_rules,
             commands::create_time_rule,
             commands::update_time_rule,
             commands::delete_time_rule,
+            commands::archive_time_rule,
+            commands::restore_time_rule,
+            commands::validate_configuration,
+            commands::export_company_config,
+            commands::import_company_config,
+            commands::get_holidays,
+            commands::create_holiday,
+            commands::delete_holiday,
+            commands::get_leaves,
+            commands::create_leave,
+            commands::delete_leave,
+            commands::get_system_configs,
+            commands::update_system_config,
+            commands::import_check_ins,
+            commands::provision_users,
             // Statistics commands
             commands::get_user_statistics,
             commands::get_all_check_ins,
             commands::get_paginated_check_ins,
+            commands::get_attendance_dashboard,
+            // Presence commands
+            commands::get_current_presence,
+            // Export commands
+            commands::export_check_ins,
+            commands::export_attendance_report,
+            commands::get_timesheet_template,
+            commands::save_timesheet_template,
+            commands::export_monthly_timesheet,
+            commands::export_employee_pdf_report,
+            commands::export_team_pdf_report,
+            // Backup commands
+            commands::create_backup,
+            commands::restore_backup,
+            // Audit commands
+            commands::get_audit_log,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActionType {
@@ -10,7 +10,11 @@ pub struct ActionType {
     pub action_role: i32,
     pub requires_pair: bool,
     pub pair_action_id: Option<i32>,
+    #[serde(default)]
+    pub is_paid_break: bool,  // 仅用于临时事件：带薪休息不从工作时长中扣除
     pub is_active: bool,
+    #[serde(default)]
+    pub archived_at: Option<String>,  // 已归档：停用且保留历史引用
     pub created_at: String,
 }
 
@@ -23,13 +27,38 @@ pub struct CreateActionTypeRequest {
     pub action_role: i32,
     pub requires_pair: bool,
     pub pair_action_id: Option<i32>,
+    pub is_paid_break: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateActionTypeRequest {
     pub id: i32,
+    pub name: Option<String>,
+    pub action_role: Option<i32>,
+    pub requires_pair: Option<bool>,
+    /// 不传表示不修改，传 null 表示取消配对
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub pair_action_id: Option<Option<i32>>,
     pub button_text: Option<String>,
     pub button_color: Option<String>,
     pub display_order: Option<i32>,
+    pub is_paid_break: Option<bool>,
     pub is_active: Option<bool>,
 }
+
+/// Result of merging one action type into another
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeActionTypesResult {
+    pub moved_check_ins: i32,
+    pub moved_rules: i32,
+    pub repointed_action_types: i32,
+}
+
+/// Tell a missing field (`None`) from an explicit null (`Some(None)`)
+pub(crate) fn deserialize_nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    /// 执行操作的用户，未登录时为空
+    pub actor_id: Option<i32>,
+    /// 操作时的用户名快照
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_table: String,
+    pub target_id: Option<i32>,
+    pub before_data: Option<Value>,
+    pub after_data: Option<Value>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub actor_id: Option<i32>,
+    pub action: Option<String>,
+    pub target_table: Option<String>,
+    pub target_id: Option<i32>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub page: i32,
+    pub page_size: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedAuditLog {
+    pub data: Vec<AuditLogEntry>,
+    pub total: usize,
+    pub page: i32,
+    pub page_size: i32,
+    pub total_pages: i32,
+}
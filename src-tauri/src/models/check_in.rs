@@ -9,9 +9,14 @@ pub struct CheckIn {
     pub status: String,
     pub pair_check_in_id: Option<i32>,
     pub duration_minutes: Option<i32>,
+    pub net_minutes: Option<i32>,
     pub note: Option<String>,
     pub is_late: bool,
     pub is_early_leave: bool,
+    #[serde(default)]
+    pub is_overtime_break: bool,
+    pub overtime_minutes: Option<i32>,
+    pub overtime_category: Option<OvertimeCategory>,
     pub is_manual: bool,
     pub created_at: String,
 }
@@ -30,6 +35,13 @@ pub struct ManualCheckInRequest {
     pub note: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct CheckInResponse {
+    #[serde(flatten)]
+    pub check_in: CheckIn,
+    pub warnings: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CheckInWithType {
     #[serde(flatten)]
@@ -37,4 +49,4 @@ pub struct CheckInWithType {
     pub action_type: Option<ActionType>,
 }
 
-use super::ActionType;
+use super::{ActionType, OvertimeCategory};
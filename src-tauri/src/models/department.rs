@@ -0,0 +1,32 @@
+use super::action_type::deserialize_nullable;
+use serde::{Deserialize, Serialize};
+
+/// A department, or a team when it has a parent department
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Department {
+    pub id: i32,
+    pub name: String,
+    pub parent_id: Option<i32>,
+    /// 部门负责人（团队经理）
+    pub manager_id: Option<i32>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDepartmentRequest {
+    pub name: String,
+    pub parent_id: Option<i32>,
+    pub manager_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateDepartmentRequest {
+    pub id: i32,
+    pub name: Option<String>,
+    /// 不传表示不修改，传 null 表示改为顶级部门
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub parent_id: Option<Option<i32>>,
+    /// 不传表示不修改，传 null 表示取消负责人
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub manager_id: Option<Option<i32>>,
+}
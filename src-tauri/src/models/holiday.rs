@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Holiday {
+    pub id: i32,
+    pub holiday_date: String,
+    pub name: String,
+    pub is_workday: bool,  // true 表示调休上班日（原本是休息日）
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateHolidayRequest {
+    pub holiday_date: String,
+    pub name: String,
+    pub is_workday: Option<bool>,
+}
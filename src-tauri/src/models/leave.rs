@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Leave {
+    pub id: i32,
+    pub user_id: i32,
+    pub start_date: String,
+    pub end_date: String,
+    pub leave_type: String,  // 例如：年假、病假、事假
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateLeaveRequest {
+    pub user_id: i32,
+    pub start_date: String,
+    pub end_date: String,
+    pub leave_type: String,
+    pub note: Option<String>,
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 登录失败计数的对象：用户名或设备
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleScope {
+    User,
+    Device,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginThrottle {
+    pub id: i32,
+    pub scope: ThrottleScope,
+    /// 小写的用户名或设备标识
+    pub key: String,
+    pub failed_count: i32,
+    pub last_failed_at: Option<String>,
+    pub locked_until: Option<String>,
+    pub created_at: String,
+}
+
+/// 登录限制策略，保存在 system_config 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginThrottlePolicy {
+    /// 连续失败多少次后锁定
+    pub max_failures: i32,
+    pub lockout_minutes: i64,
+    /// 第一次失败后的等待秒数，之后每次翻倍
+    pub backoff_seconds: i64,
+}
+
+impl Default for LoginThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            max_failures: 5,
+            lockout_minutes: 15,
+            backoff_seconds: 1,
+        }
+    }
+}
+
+impl LoginThrottlePolicy {
+    pub const MAX_FAILURES_KEY: &'static str = "login_max_failures";
+    pub const LOCKOUT_MINUTES_KEY: &'static str = "login_lockout_minutes";
+    pub const BACKOFF_SECONDS_KEY: &'static str = "login_backoff_seconds";
+
+    /// Build the policy from system_config values, falling back to defaults
+    pub fn from_config(config: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        let number = |key: &str| config.get(key).and_then(|v| v.trim().parse::<i64>().ok());
+        Self {
+            max_failures: number(Self::MAX_FAILURES_KEY)
+                .filter(|v| *v > 0)
+                .map(|v| v as i32)
+                .unwrap_or(defaults.max_failures),
+            lockout_minutes: number(Self::LOCKOUT_MINUTES_KEY)
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.lockout_minutes),
+            backoff_seconds: number(Self::BACKOFF_SECONDS_KEY)
+                .filter(|v| *v >= 0)
+                .unwrap_or(defaults.backoff_seconds),
+        }
+    }
+}
@@ -3,8 +3,28 @@ pub mod user;
 pub mod action_type;
 pub mod check_in;
 pub mod time_rule;
+pub mod holiday;
+pub mod leave;
+pub mod overtime;
+pub mod system_config;
+pub mod audit;
+pub mod department;
+pub mod password;
+pub mod login_throttle;
+pub mod two_factor;
+pub mod registration;
 
 pub use user::*;
 pub use action_type::*;
 pub use check_in::*;
 pub use time_rule::*;
+pub use holiday::*;
+pub use leave::*;
+pub use overtime::*;
+pub use system_config::*;
+pub use audit::*;
+pub use department::*;
+pub use password::*;
+pub use login_throttle::*;
+pub use two_factor::*;
+pub use registration::*;
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OvertimeCategory {
+    Workday,   // 工作日延时加班
+    RestDay,   // 休息日加班
+    Holiday,   // 法定节假日加班
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    Floor,
+    Nearest,
+    Ceil,
+}
+
+/// 加班计算规则，保存在 system_config 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OvertimeSettings {
+    pub rounding_minutes: i32,   // 取整单位，例如 30 表示按半小时计
+    pub rounding_mode: RoundingMode,
+    pub min_minutes: i32,        // 不足该时长不计加班
+}
+
+impl Default for OvertimeSettings {
+    fn default() -> Self {
+        Self {
+            rounding_minutes: 30,
+            rounding_mode: RoundingMode::Floor,
+            min_minutes: 30,
+        }
+    }
+}
+
+impl OvertimeSettings {
+    pub const ROUNDING_MINUTES_KEY: &'static str = "overtime_rounding_minutes";
+    pub const ROUNDING_MODE_KEY: &'static str = "overtime_rounding_mode";
+    pub const MIN_MINUTES_KEY: &'static str = "overtime_min_minutes";
+
+    /// Build settings from system_config values, falling back to defaults
+    pub fn from_config(config: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        Self {
+            rounding_minutes: config
+                .get(Self::ROUNDING_MINUTES_KEY)
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|v: &i32| *v > 0)
+                .unwrap_or(defaults.rounding_minutes),
+            rounding_mode: match config.get(Self::ROUNDING_MODE_KEY).map(|v| v.trim()) {
+                Some("nearest") => RoundingMode::Nearest,
+                Some("ceil") => RoundingMode::Ceil,
+                Some("floor") => RoundingMode::Floor,
+                _ => defaults.rounding_mode,
+            },
+            min_minutes: config
+                .get(Self::MIN_MINUTES_KEY)
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|v: &i32| *v >= 0)
+                .unwrap_or(defaults.min_minutes),
+        }
+    }
+}
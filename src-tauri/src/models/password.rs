@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 密码强度与更换策略，保存在 system_config 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// 密码有效天数，0 表示不过期
+    pub max_age_days: i64,
+    /// 管理员重置的临时密码有效小时数
+    pub temporary_password_hours: i64,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_uppercase: false,
+            require_lowercase: false,
+            require_digit: true,
+            require_symbol: false,
+            max_age_days: 0,
+            temporary_password_hours: 72,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub const MIN_LENGTH_KEY: &'static str = "password_min_length";
+    pub const REQUIRE_UPPERCASE_KEY: &'static str = "password_require_uppercase";
+    pub const REQUIRE_LOWERCASE_KEY: &'static str = "password_require_lowercase";
+    pub const REQUIRE_DIGIT_KEY: &'static str = "password_require_digit";
+    pub const REQUIRE_SYMBOL_KEY: &'static str = "password_require_symbol";
+    pub const MAX_AGE_DAYS_KEY: &'static str = "password_max_age_days";
+    pub const TEMPORARY_PASSWORD_HOURS_KEY: &'static str = "temporary_password_hours";
+
+    /// Build the policy from system_config values, falling back to defaults
+    pub fn from_config(config: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        let flag = |key: &str, default: bool| match config.get(key).map(|v| v.trim()) {
+            Some("true") => true,
+            Some("false") => false,
+            _ => default,
+        };
+        Self {
+            min_length: config
+                .get(Self::MIN_LENGTH_KEY)
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|v: &usize| *v > 0)
+                .unwrap_or(defaults.min_length),
+            require_uppercase: flag(Self::REQUIRE_UPPERCASE_KEY, defaults.require_uppercase),
+            require_lowercase: flag(Self::REQUIRE_LOWERCASE_KEY, defaults.require_lowercase),
+            require_digit: flag(Self::REQUIRE_DIGIT_KEY, defaults.require_digit),
+            require_symbol: flag(Self::REQUIRE_SYMBOL_KEY, defaults.require_symbol),
+            max_age_days: config
+                .get(Self::MAX_AGE_DAYS_KEY)
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|v: &i64| *v >= 0)
+                .unwrap_or(defaults.max_age_days),
+            temporary_password_hours: config
+                .get(Self::TEMPORARY_PASSWORD_HOURS_KEY)
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|v: &i64| *v > 0)
+                .unwrap_or(defaults.temporary_password_hours),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PasswordResetResult {
+    pub user_id: i32,
+    pub username: String,
+    /// 只显示一次，用户登录后须立即修改
+    pub temporary_password: String,
+    pub expires_at: String,
+}
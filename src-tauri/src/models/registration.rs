@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationMode {
+    /// 任何人可注册，可限制邮箱域名或要求审批
+    #[default]
+    Open,
+    /// 须填写管理员发放的邀请码
+    InviteOnly,
+    /// 只能由管理员创建账号
+    Closed,
+}
+
+/// 自助注册设置，保存在 system_config 中；登录页据此显示注册表单
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistrationSettings {
+    pub mode: RegistrationMode,
+    /// 开放注册时允许的邮箱域名，为空不限制
+    pub email_domains: Vec<String>,
+    /// 开放注册的账号须审批后才能登录；凭邀请码注册的账号无须审批
+    pub requires_approval: bool,
+}
+
+impl RegistrationSettings {
+    pub const MODE_KEY: &'static str = "registration_mode";
+    pub const EMAIL_DOMAINS_KEY: &'static str = "registration_email_domains";
+    pub const REQUIRES_APPROVAL_KEY: &'static str = "registration_requires_approval";
+
+    /// Build the settings from system_config values, falling back to open registration
+    pub fn from_config(config: &HashMap<String, String>) -> Self {
+        let mode = match config.get(Self::MODE_KEY).map(|v| v.trim()) {
+            Some("invite_only") => RegistrationMode::InviteOnly,
+            Some("closed") => RegistrationMode::Closed,
+            _ => RegistrationMode::Open,
+        };
+        let email_domains = config
+            .get(Self::EMAIL_DOMAINS_KEY)
+            .map(|v| {
+                v.split([',', '，', ' '])
+                    .map(|d| d.trim().trim_start_matches('@').to_lowercase())
+                    .filter(|d| !d.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            mode,
+            email_domains,
+            requires_approval: config
+                .get(Self::REQUIRES_APPROVAL_KEY)
+                .is_some_and(|v| v.trim() == "true"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InviteCode {
+    pub id: i32,
+    pub code: String,
+    /// 凭此邀请码注册的员工加入的部门
+    pub department_id: Option<i32>,
+    /// 为空表示在有效期内不限次数
+    pub max_uses: Option<i32>,
+    pub use_count: i32,
+    pub expires_at: Option<String>,
+    pub revoked: bool,
+    pub created_by: Option<i32>,
+    pub created_at: String,
+}
+
+/// 一次性邀请码或有效期内可多次使用的邀请码，两者至少选一
+#[derive(Debug, Deserialize)]
+pub struct CreateInviteCodeRequest {
+    pub department_id: Option<i32>,
+    pub max_uses: Option<i32>,
+    pub expires_in_days: Option<i64>,
+}
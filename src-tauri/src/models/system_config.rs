@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemConfig {
+    pub id: i32,
+    pub config_key: String,
+    pub config_value: String,
+    pub config_desc: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSystemConfigRequest {
+    pub config_key: String,
+    pub config_value: String,
+    pub config_desc: Option<String>,
+}
@@ -1,3 +1,4 @@
+use super::action_type::deserialize_nullable;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,8 +9,16 @@ pub struct TimeRule {
     pub expected_start_time: Option<String>,  // 仅用于主进程
     pub expected_end_time: Option<String>,    // 仅用于主进程
     pub max_duration_minutes: Option<i32>,    // 仅用于临时事件
+    pub daily_max_total_minutes: Option<i32>, // 仅用于临时事件：每日累计时长上限
+    pub daily_max_count: Option<i32>,         // 仅用于临时事件：每日次数上限
+    pub rest_days: Option<Vec<i32>>,          // 仅用于主进程：每周休息日（1=周一 … 7=周日）
     pub timezone: String,
     pub is_active: bool,
+    #[serde(default)]
+    pub archived_at: Option<String>,
+    /// 部门排班；为空时适用于全公司
+    #[serde(default)]
+    pub department_id: Option<i32>,
     pub created_at: String,
 }
 
@@ -20,15 +29,32 @@ pub struct CreateTimeRuleRequest {
     pub expected_start_time: Option<String>,
     pub expected_end_time: Option<String>,
     pub max_duration_minutes: Option<i32>,
+    pub daily_max_total_minutes: Option<i32>,
+    pub daily_max_count: Option<i32>,
+    pub rest_days: Option<Vec<i32>>,
     pub timezone: Option<String>,
+    pub department_id: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateTimeRuleRequest {
     pub id: i32,
     pub rule_name: Option<String>,
-    pub expected_start_time: Option<String>,
-    pub expected_end_time: Option<String>,
-    pub max_duration_minutes: Option<i32>,
+    /// 以下可选字段：不传表示不修改，传 null 表示清除
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub expected_start_time: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub expected_end_time: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub max_duration_minutes: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub daily_max_total_minutes: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub daily_max_count: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub rest_days: Option<Option<Vec<i32>>>,
     pub is_active: Option<bool>,
+    /// 不传表示不修改，传 null 表示改为全公司规则
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub department_id: Option<Option<i32>>,
 }
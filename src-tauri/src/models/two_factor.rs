@@ -0,0 +1,19 @@
+use super::user::User;
+use serde::Serialize;
+
+/// 开始启用两步验证时返回，扫码或手动输入密钥后用验证码确认
+#[derive(Debug, Serialize)]
+pub struct TwoFactorSetup {
+    pub secret: String,
+    /// otpauth:// 地址，验证器应用扫码后添加账号
+    pub provisioning_uri: String,
+    /// 二维码 SVG
+    pub qr_svg: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TwoFactorEnabled {
+    pub user: User,
+    /// 只显示一次，每个恢复码只能使用一次
+    pub recovery_codes: Vec<String>,
+}
@@ -1,5 +1,10 @@
+use super::action_type::deserialize_nullable;
 use serde::{Deserialize, Serialize};
 
+/// User columns holding credentials: never written to the audit log and
+/// left out of backups unless explicitly requested
+pub const SECRET_USER_COLUMNS: &[&str] = &["password_hash", "totp_secret", "totp_recovery_codes"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: i32,
@@ -7,14 +12,97 @@ pub struct User {
     #[serde(skip_serializing)]
     pub password_hash: String,
     pub full_name: String,
+    /// Kept in sync with `role` for older clients: true only for super admins
     pub is_admin: bool,
+    #[serde(default)]
+    pub role: Role,
+    /// Set for provisioned accounts, reset passwords and expired passwords until the password is changed
+    #[serde(default)]
+    pub must_change_password: bool,
+    #[serde(default)]
+    pub password_changed_at: Option<String>,
+    /// Temporary passwords issued by an admin reset stop working after this time
+    #[serde(default)]
+    pub temporary_password_expires_at: Option<String>,
+    /// Base32 TOTP secret; pending until `totp_enabled` is set
+    #[serde(skip_serializing, default)]
+    pub totp_secret: Option<String>,
+    #[serde(default)]
+    pub totp_enabled: bool,
+    /// Hashes of the unused recovery codes
+    #[serde(skip_serializing, default)]
+    pub totp_recovery_codes: Vec<String>,
+    /// Last accepted time step, so a code cannot be used twice
+    #[serde(skip_serializing, default)]
+    pub totp_last_step: Option<i64>,
+    /// Not stored: set when the session opens for an admin who must enroll in two-factor authentication
+    #[serde(default, skip_deserializing)]
+    pub two_factor_setup_required: bool,
+    /// Self-registered accounts awaiting approval cannot log in
+    #[serde(default)]
+    pub approval_status: ApprovalStatus,
+    /// Deactivated users cannot log in but keep their history
+    #[serde(default = "default_active")]
+    pub is_active: bool,
+    #[serde(default)]
+    pub deactivated_at: Option<String>,
+    #[serde(default)]
+    pub deactivation_reason: Option<String>,
+    #[serde(default)]
+    pub department_id: Option<i32>,
+    #[serde(default)]
+    pub employee_no: Option<String>,
+    /// Days before the hire date are not counted as absent
+    #[serde(default)]
+    pub hire_date: Option<String>,
+    /// Days after the termination date are not counted as absent
+    #[serde(default)]
+    pub termination_date: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    /// Main process rule replacing the department and company schedule
+    #[serde(default)]
+    pub default_schedule_rule_id: Option<i32>,
     pub created_at: String,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    #[default]
+    Approved,
+    Rejected,
+}
+
+/// What a user may see and change; enforced by every command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// 只能查看和打卡自己的记录
+    #[default]
+    Employee,
+    /// 查看并审批所负责部门及下属团队成员的记录
+    Manager,
+    /// 查看和修改全部考勤记录，不能修改配置
+    Hr,
+    /// 管理全部数据和配置
+    SuperAdmin,
+}
+
+fn default_active() -> bool {
+    true
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
+    /// 本机生成的设备标识，用于按设备限制登录失败次数
+    #[serde(default)]
+    pub device_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,6 +110,54 @@ pub struct RegisterRequest {
     pub username: String,
     pub password: String,
     pub full_name: String,
+    /// 开放注册限制邮箱域名时必填
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub invite_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub full_name: String,
+    #[serde(default)]
+    pub role: Role,
+    pub department_id: Option<i32>,
+    pub employee_no: Option<String>,
+    pub hire_date: Option<String>,
+    pub termination_date: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub default_schedule_rule_id: Option<i32>,
+}
+
+/// Profile fields; not sent means unchanged, null clears the field
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserProfileRequest {
+    pub user_id: i32,
+    pub full_name: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub employee_no: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub hire_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub termination_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub email: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub phone: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub default_schedule_rule_id: Option<Option<i32>>,
+}
+
+/// Fields employees may change on their own profile
+#[derive(Debug, Deserialize)]
+pub struct UpdateMyProfileRequest {
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub email: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub phone: Option<Option<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,3 +165,11 @@ pub struct LoginResponse {
     pub user: User,
     pub token: String,
 }
+
+/// 启用两步验证的账号在密码正确后还须输入验证码
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum LoginResult {
+    SignedIn(Box<LoginResponse>),
+    TwoFactorRequired,
+}
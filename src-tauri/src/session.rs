@@ -0,0 +1,58 @@
+// Session module
+use crate::models::User;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long the two-factor code may be entered after the password was accepted
+const PENDING_LOGIN_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Password accepted, waiting for the two-factor code
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    pub user_id: i32,
+    pub username: String,
+    pub device_id: Option<String>,
+    started_at: Instant,
+}
+
+/// User signed in to this app instance, shared by all windows
+#[derive(Default)]
+pub struct Session {
+    user: Mutex<Option<User>>,
+    pending_login: Mutex<Option<PendingLogin>>,
+}
+
+impl Session {
+    pub fn set(&self, user: User) {
+        *self.pending_login.lock().unwrap() = None;
+        *self.user.lock().unwrap() = Some(user);
+    }
+
+    pub fn clear(&self) {
+        *self.pending_login.lock().unwrap() = None;
+        *self.user.lock().unwrap() = None;
+    }
+
+    pub fn current(&self) -> Option<User> {
+        self.user.lock().unwrap().clone()
+    }
+
+    pub fn start_pending_login(&self, user: &User, device_id: Option<String>) {
+        *self.user.lock().unwrap() = None;
+        *self.pending_login.lock().unwrap() = Some(PendingLogin {
+            user_id: user.id,
+            username: user.username.clone(),
+            device_id,
+            started_at: Instant::now(),
+        });
+    }
+
+    /// Login waiting for its two-factor code, if it has not timed out
+    pub fn pending_login(&self) -> Option<PendingLogin> {
+        let mut pending = self.pending_login.lock().unwrap();
+        if pending.as_ref().is_some_and(|p| p.started_at.elapsed() > PENDING_LOGIN_TTL) {
+            *pending = None;
+        }
+        pending.clone()
+    }
+}
@@ -0,0 +1,761 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use crate::models::{ActionType, CheckIn, Department, Holiday, Leave, OvertimeCategory, TimeRule, User};
+use crate::utils::department::schedule_scopes;
+use crate::utils::overtime::{classify_day, DayKind};
+use crate::utils::time::{calculate_duration, expected_end_datetime, parse_date, parse_time};
+use crate::utils::worktime::{break_minutes, net_work_minutes};
+
+/// Everything the statistics engine needs besides the check-ins themselves
+pub struct AttendanceContext<'a> {
+    pub action_types: &'a [ActionType],
+    /// Main process rule providing expected times and weekly rest days
+    pub schedule: Option<&'a TimeRule>,
+    pub holidays: &'a [Holiday],
+    /// Leave records of the employee being summarized
+    pub leaves: &'a [Leave],
+    pub timezone: &'a str,
+    /// Days after this date are not counted as absent
+    pub today: NaiveDate,
+    /// Employment period of the employee; days outside it are not counted as absent
+    pub hired_on: Option<NaiveDate>,
+    pub terminated_on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DayStatus {
+    Present,
+    Late,
+    EarlyLeave,
+    Absent,
+    Leave,
+    Holiday,
+    RestDay,
+    Upcoming,
+    /// Before the hire date or after the termination date
+    NotEmployed,
+}
+
+/// Attendance of one employee on one workday.
+/// A shift belongs to the day it started on, so cross-day shifts are counted once.
+#[derive(Debug, Clone, Serialize)]
+pub struct DaySummary {
+    pub date: String,
+    pub day_kind: DayKind,
+    pub status: DayStatus,
+    pub shift_count: i32,
+    /// First clock-in of the day ("HH:MM")
+    pub first_in: Option<String>,
+    /// Last clock-out of the day's shifts, "(+1)" when it falls on the next day
+    pub last_out: Option<String>,
+    pub is_late: bool,
+    pub is_early_leave: bool,
+    pub late_minutes: i32,
+    pub early_leave_minutes: i32,
+    pub work_minutes: i32,
+    pub break_minutes: i32,
+    pub workday_overtime_minutes: i32,
+    pub rest_day_overtime_minutes: i32,
+    pub holiday_overtime_minutes: i32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttendanceSummary {
+    pub scheduled_days: i32,
+    pub attended_days: i32,
+    pub absent_days: i32,
+    pub leave_days: i32,
+    pub late_days: i32,
+    pub early_leave_days: i32,
+    pub late_minutes: i32,
+    pub early_leave_minutes: i32,
+    pub net_work_minutes: i32,
+    pub break_minutes: i32,
+    pub overtime_minutes: i32,
+    pub workday_overtime_minutes: i32,
+    pub rest_day_overtime_minutes: i32,
+    pub holiday_overtime_minutes: i32,
+}
+
+/// The work schedule is the active rule of a main process action:
+/// the 上班 rule when there is one, otherwise the 下班 rule.
+/// `scopes` lists the departments whose rules apply, most specific first (see `schedule_scopes`).
+pub fn find_schedule_rule<'a>(
+    rules: &'a [TimeRule],
+    action_types: &[ActionType],
+    scopes: &[Option<i32>],
+) -> Option<&'a TimeRule> {
+    scopes.iter().find_map(|scope| {
+        [1, 2].iter().find_map(|role| {
+            rules.iter().find(|rule| {
+                rule.is_active
+                    && rule.department_id == *scope
+                    && role_of(action_types, rule.action_type_id) == Some(*role)
+            })
+        })
+    })
+}
+
+/// Work schedule of an employee: the employee's default schedule when set,
+/// otherwise the schedule of its department or the company. Shared by the
+/// statistics and by the overtime stored at clock-out so both agree on rest days.
+pub fn employee_schedule<'a>(
+    rules: &'a [TimeRule],
+    action_types: &[ActionType],
+    departments: &[Department],
+    user: &User,
+) -> Option<&'a TimeRule> {
+    user.default_schedule_rule_id
+        .and_then(|id| rules.iter().find(|rule| rule.id == id && rule.is_active))
+        .or_else(|| {
+            let scopes = schedule_scopes(departments, user.department_id);
+            find_schedule_rule(rules, action_types, &scopes)
+        })
+}
+
+/// A main process (上班 → 下班) with the breaks taken inside it
+struct Shift<'a> {
+    start: &'a CheckIn,
+    end: Option<&'a CheckIn>,
+    start_time: DateTime<Tz>,
+    end_time: Option<DateTime<Tz>>,
+    breaks: Vec<&'a CheckIn>,
+}
+
+fn role_of(action_types: &[ActionType], action_type_id: i32) -> Option<i32> {
+    action_types
+        .iter()
+        .find(|at| at.id == action_type_id)
+        .map(|at| at.action_role)
+}
+
+fn is_on_leave(leaves: &[Leave], date: NaiveDate) -> bool {
+    leaves.iter().any(|leave| {
+        match (parse_date(&leave.start_date), parse_date(&leave.end_date)) {
+            (Some(start), Some(end)) => start <= date && date <= end,
+            _ => false,
+        }
+    })
+}
+
+/// Group raw check-ins into shifts: every main start record with its paired end
+/// record and the temporary events that started while the shift was running.
+fn build_shifts<'a>(check_ins: &'a [CheckIn], ctx: &AttendanceContext) -> Vec<Shift<'a>> {
+    let mut shifts: Vec<Shift<'a>> = check_ins
+        .iter()
+        .filter(|c| role_of(ctx.action_types, c.action_type_id) == Some(1))
+        .filter_map(|start| {
+            let start_time = parse_time(&start.check_time, ctx.timezone)?;
+            let end = check_ins.iter().find(|c| {
+                role_of(ctx.action_types, c.action_type_id) == Some(2)
+                    && (Some(c.id) == start.pair_check_in_id || c.pair_check_in_id == Some(start.id))
+            });
+            let end_time = end.and_then(|e| parse_time(&e.check_time, ctx.timezone));
+            Some(Shift { start, end, start_time, end_time, breaks: Vec::new() })
+        })
+        .collect();
+    shifts.sort_by_key(|s| s.start_time);
+
+    for check_in in check_ins {
+        if role_of(ctx.action_types, check_in.action_type_id) != Some(3) {
+            continue;
+        }
+        let Some(break_time) = parse_time(&check_in.check_time, ctx.timezone) else {
+            continue;
+        };
+        if let Some(shift) = shifts.iter_mut().find(|s| {
+            break_time >= s.start_time && s.end_time.is_none_or(|end| break_time <= end)
+        }) {
+            shift.breaks.push(check_in);
+        }
+    }
+
+    shifts
+}
+
+impl Shift<'_> {
+    fn break_minutes(&self, action_types: &[ActionType]) -> (i32, i32) {
+        break_minutes(self.breaks.iter().copied(), action_types)
+    }
+
+    /// Net minutes stored on the end record, or derived from the gross duration
+    /// for records created before net working time existed
+    fn net_minutes(&self, action_types: &[ActionType]) -> i32 {
+        let Some(end) = self.end else {
+            return 0;
+        };
+        if let Some(net) = end.net_minutes {
+            return net.max(0);
+        }
+        let gross = end
+            .duration_minutes
+            .or_else(|| self.end_time.map(|t| calculate_duration(&self.start_time, &t)))
+            .unwrap_or(0);
+        let (_, unpaid) = self.break_minutes(action_types);
+        net_work_minutes(gross, unpaid)
+    }
+
+    fn late_minutes(&self, schedule: Option<&TimeRule>) -> i32 {
+        if !self.start.is_late {
+            return 0;
+        }
+        let expected_start = schedule
+            .and_then(|rule| rule.expected_start_time.as_deref())
+            .and_then(|s| NaiveTime::parse_from_str(s, "%H:%M:%S").ok())
+            .and_then(|t| {
+                self.start_time
+                    .date_naive()
+                    .and_time(t)
+                    .and_local_timezone(self.start_time.timezone())
+                    .single()
+            });
+        expected_start
+            .map(|expected| calculate_duration(&expected, &self.start_time).max(0))
+            .unwrap_or(0)
+    }
+
+    fn early_leave_minutes(&self, schedule: Option<&TimeRule>) -> i32 {
+        let (Some(end), Some(end_time), Some(rule)) = (self.end, self.end_time, schedule) else {
+            return 0;
+        };
+        if !end.is_early_leave {
+            return 0;
+        }
+        rule.expected_end_time
+            .as_deref()
+            .and_then(|expected_end| {
+                expected_end_datetime(
+                    self.start_time.date_naive(),
+                    rule.expected_start_time.as_deref(),
+                    expected_end,
+                    self.start_time.timezone(),
+                )
+            })
+            .map(|expected| calculate_duration(&end_time, &expected).max(0))
+            .unwrap_or(0)
+    }
+}
+
+/// Build the per-day attendance of one employee between two dates (inclusive)
+pub fn summarize_days(
+    check_ins: &[CheckIn],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    ctx: &AttendanceContext,
+) -> Vec<DaySummary> {
+    let shifts = build_shifts(check_ins, ctx);
+    let rest_days = ctx
+        .schedule
+        .and_then(|rule| rule.rest_days.clone())
+        .unwrap_or_default();
+
+    let mut days = Vec::new();
+    let mut date = start_date;
+    while date <= end_date {
+        let day_kind = classify_day(date, &rest_days, ctx.holidays);
+        let mut day = DaySummary {
+            date: date.format("%Y-%m-%d").to_string(),
+            day_kind,
+            status: DayStatus::Absent,
+            shift_count: 0,
+            first_in: None,
+            last_out: None,
+            is_late: false,
+            is_early_leave: false,
+            late_minutes: 0,
+            early_leave_minutes: 0,
+            work_minutes: 0,
+            break_minutes: 0,
+            workday_overtime_minutes: 0,
+            rest_day_overtime_minutes: 0,
+            holiday_overtime_minutes: 0,
+        };
+
+        for shift in shifts.iter().filter(|s| s.start_time.date_naive() == date) {
+            let (paid, unpaid) = shift.break_minutes(ctx.action_types);
+            day.shift_count += 1;
+            if day.first_in.is_none() {
+                day.first_in = Some(shift.start_time.format("%H:%M").to_string());
+            }
+            if let Some(end_time) = shift.end_time {
+                let next_day = if end_time.date_naive() > date { "(+1)" } else { "" };
+                day.last_out = Some(format!("{}{}", end_time.format("%H:%M"), next_day));
+            }
+            day.is_late |= shift.start.is_late;
+            day.is_early_leave |= shift.end.is_some_and(|e| e.is_early_leave);
+            day.late_minutes += shift.late_minutes(ctx.schedule);
+            day.early_leave_minutes += shift.early_leave_minutes(ctx.schedule);
+            day.work_minutes += shift.net_minutes(ctx.action_types);
+            day.break_minutes += paid + unpaid;
+
+            if let Some(end) = shift.end {
+                let minutes = end.overtime_minutes.unwrap_or(0);
+                match end.overtime_category {
+                    Some(OvertimeCategory::Workday) => day.workday_overtime_minutes += minutes,
+                    Some(OvertimeCategory::RestDay) => day.rest_day_overtime_minutes += minutes,
+                    Some(OvertimeCategory::Holiday) => day.holiday_overtime_minutes += minutes,
+                    None => {}
+                }
+            }
+        }
+
+        day.status = if day.shift_count > 0 {
+            if day.is_late {
+                DayStatus::Late
+            } else if day.is_early_leave {
+                DayStatus::EarlyLeave
+            } else {
+                DayStatus::Present
+            }
+        } else if ctx.hired_on.is_some_and(|d| date < d) || ctx.terminated_on.is_some_and(|d| date > d) {
+            DayStatus::NotEmployed
+        } else {
+            match day_kind {
+                DayKind::Holiday => DayStatus::Holiday,
+                DayKind::RestDay => DayStatus::RestDay,
+                DayKind::Workday if is_on_leave(ctx.leaves, date) => DayStatus::Leave,
+                DayKind::Workday if date > ctx.today => DayStatus::Upcoming,
+                DayKind::Workday => DayStatus::Absent,
+            }
+        };
+
+        days.push(day);
+        date += Duration::days(1);
+    }
+
+    days
+}
+
+/// Aggregate per-day attendance into totals
+pub fn summarize_attendance(days: &[DaySummary], today: NaiveDate) -> AttendanceSummary {
+    let mut summary = AttendanceSummary::default();
+
+    for day in days {
+        let is_past = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").is_ok_and(|d| d <= today);
+        if day.day_kind == DayKind::Workday && is_past && day.status != DayStatus::NotEmployed {
+            summary.scheduled_days += 1;
+        }
+        if day.shift_count > 0 {
+            summary.attended_days += 1;
+        }
+        match day.status {
+            DayStatus::Absent => summary.absent_days += 1,
+            DayStatus::Leave => summary.leave_days += 1,
+            _ => {}
+        }
+        if day.is_late {
+            summary.late_days += 1;
+        }
+        if day.is_early_leave {
+            summary.early_leave_days += 1;
+        }
+        summary.late_minutes += day.late_minutes;
+        summary.early_leave_minutes += day.early_leave_minutes;
+        summary.net_work_minutes += day.work_minutes;
+        summary.break_minutes += day.break_minutes;
+        summary.workday_overtime_minutes += day.workday_overtime_minutes;
+        summary.rest_day_overtime_minutes += day.rest_day_overtime_minutes;
+        summary.holiday_overtime_minutes += day.holiday_overtime_minutes;
+    }
+
+    summary.overtime_minutes = summary.workday_overtime_minutes
+        + summary.rest_day_overtime_minutes
+        + summary.holiday_overtime_minutes;
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TZ: &str = "Asia/Phnom_Penh";
+
+    fn action_type(id: i32, role: i32, is_paid_break: bool) -> ActionType {
+        ActionType {
+            id,
+            name: format!("action-{}", id),
+            button_text: format!("action-{}", id),
+            button_color: "#000000".to_string(),
+            display_order: id,
+            action_role: role,
+            requires_pair: true,
+            pair_action_id: None,
+            is_paid_break,
+            is_active: true,
+            archived_at: None,
+            created_at: String::new(),
+        }
+    }
+
+    // 1 上班, 2 下班, 3 午餐(不带薪), 4 回座, 5 上厕所(带薪)
+    fn action_types() -> Vec<ActionType> {
+        vec![
+            action_type(1, 1, false),
+            action_type(2, 2, false),
+            action_type(3, 3, false),
+            action_type(4, 4, false),
+            action_type(5, 3, true),
+        ]
+    }
+
+    fn schedule() -> TimeRule {
+        TimeRule {
+            id: 1,
+            rule_name: "标准班".to_string(),
+            action_type_id: 1,
+            expected_start_time: Some("09:00:00".to_string()),
+            expected_end_time: Some("18:00:00".to_string()),
+            max_duration_minutes: None,
+            daily_max_total_minutes: None,
+            daily_max_count: None,
+            rest_days: Some(vec![6, 7]),
+            timezone: TZ.to_string(),
+            is_active: true,
+            archived_at: None,
+            department_id: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn holiday(date: &str) -> Holiday {
+        Holiday {
+            id: 1,
+            holiday_date: date.to_string(),
+            name: "节日".to_string(),
+            is_workday: false,
+            created_at: String::new(),
+        }
+    }
+
+    /// Builds timelines the same way create_check_in stores them:
+    /// both records of a pair carry the duration, flags sit on the relevant record
+    struct Timeline {
+        check_ins: Vec<CheckIn>,
+    }
+
+    impl Timeline {
+        fn new() -> Self {
+            Self { check_ins: Vec::new() }
+        }
+
+        fn record(&mut self, action_type_id: i32, time: &str) -> usize {
+            let id = self.check_ins.len() as i32 + 1;
+            self.check_ins.push(CheckIn {
+                id,
+                user_id: 1,
+                action_type_id,
+                check_time: time.to_string(),
+                status: "completed".to_string(),
+                pair_check_in_id: None,
+                duration_minutes: None,
+                net_minutes: None,
+                note: None,
+                is_late: false,
+                is_early_leave: false,
+                is_overtime_break: false,
+                overtime_minutes: None,
+                overtime_category: None,
+                is_manual: false,
+                created_at: time.to_string(),
+            });
+            self.check_ins.len() - 1
+        }
+
+        fn pair(&mut self, start_action: i32, start: &str, end_action: i32, end: &str) -> (usize, usize) {
+            let s = self.record(start_action, start);
+            let e = self.record(end_action, end);
+            let start_time = parse_time(start, TZ).unwrap();
+            let end_time = parse_time(end, TZ).unwrap();
+            let duration = calculate_duration(&start_time, &end_time);
+            let (start_id, end_id) = (self.check_ins[s].id, self.check_ins[e].id);
+            self.check_ins[s].pair_check_in_id = Some(end_id);
+            self.check_ins[s].duration_minutes = Some(duration);
+            self.check_ins[e].pair_check_in_id = Some(start_id);
+            self.check_ins[e].duration_minutes = Some(duration);
+            (s, e)
+        }
+
+        fn shift(&mut self, start: &str, end: &str) -> (usize, usize) {
+            self.pair(1, start, 2, end)
+        }
+
+        fn lunch(&mut self, start: &str, end: &str) {
+            self.pair(3, start, 4, end);
+        }
+
+        fn toilet(&mut self, start: &str, end: &str) {
+            self.pair(5, start, 4, end);
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn leave(start: &str, end: &str) -> Leave {
+        Leave {
+            id: 1,
+            user_id: 1,
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            leave_type: "年假".to_string(),
+            note: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn run(timeline: &Timeline, start: &str, end: &str, holidays: &[Holiday], today: &str) -> (Vec<DaySummary>, AttendanceSummary) {
+        run_with_leaves(timeline, start, end, holidays, &[], today)
+    }
+
+    fn run_with_leaves(
+        timeline: &Timeline,
+        start: &str,
+        end: &str,
+        holidays: &[Holiday],
+        leaves: &[Leave],
+        today: &str,
+    ) -> (Vec<DaySummary>, AttendanceSummary) {
+        let action_types = action_types();
+        let schedule = schedule();
+        let ctx = AttendanceContext {
+            action_types: &action_types,
+            schedule: Some(&schedule),
+            holidays,
+            leaves,
+            timezone: TZ,
+            today: date(today),
+            hired_on: None,
+            terminated_on: None,
+        };
+        let days = summarize_days(&timeline.check_ins, date(start), date(end), &ctx);
+        let summary = summarize_attendance(&days, date(today));
+        (days, summary)
+    }
+
+    #[test]
+    fn regular_week_with_late_early_leave_and_absence() {
+        // 2026-10-12 is a Monday
+        let mut t = Timeline::new();
+        t.shift("2026-10-12 08:55:00", "2026-10-12 18:05:00");
+        t.lunch("2026-10-12 12:00:00", "2026-10-12 12:45:00");
+        t.toilet("2026-10-12 15:00:00", "2026-10-12 15:10:00");
+
+        let (late_start, _) = t.shift("2026-10-13 09:20:00", "2026-10-13 18:00:00");
+        t.check_ins[late_start].is_late = true;
+
+        // 2026-10-14 absent
+
+        let (_, early_end) = t.shift("2026-10-15 09:00:00", "2026-10-15 17:30:00");
+        t.check_ins[early_end].is_early_leave = true;
+
+        t.shift("2026-10-16 09:00:00", "2026-10-16 18:00:00");
+
+        let (days, summary) = run(&t, "2026-10-12", "2026-10-18", &[], "2026-10-18");
+
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[0].status, DayStatus::Present);
+        assert_eq!(days[1].status, DayStatus::Late);
+        assert_eq!(days[2].status, DayStatus::Absent);
+        assert_eq!(days[3].status, DayStatus::EarlyLeave);
+        assert_eq!(days[5].status, DayStatus::RestDay);
+        assert_eq!(days[6].status, DayStatus::RestDay);
+
+        assert_eq!(summary.scheduled_days, 5);
+        assert_eq!(summary.attended_days, 4);
+        assert_eq!(summary.absent_days, 1);
+        assert_eq!(summary.late_days, 1);
+        assert_eq!(summary.late_minutes, 20);
+        assert_eq!(summary.early_leave_days, 1);
+        assert_eq!(summary.early_leave_minutes, 30);
+
+        // Monday: 550 gross - 45 unpaid lunch, the paid toilet break is kept
+        assert_eq!(days[0].work_minutes, 505);
+        assert_eq!(days[0].break_minutes, 55);
+        assert_eq!(summary.net_work_minutes, 505 + 520 + 510 + 540);
+        assert_eq!(summary.break_minutes, 55);
+    }
+
+    #[test]
+    fn paired_records_are_counted_once() {
+        // Both the start and end records carry duration_minutes
+        let mut t = Timeline::new();
+        t.shift("2026-10-12 09:00:00", "2026-10-12 18:00:00");
+
+        let (_, summary) = run(&t, "2026-10-12", "2026-10-12", &[], "2026-10-12");
+
+        assert_eq!(summary.attended_days, 1);
+        assert_eq!(summary.net_work_minutes, 540);
+    }
+
+    #[test]
+    fn stored_net_minutes_take_precedence() {
+        let mut t = Timeline::new();
+        let (_, end) = t.shift("2026-10-12 09:00:00", "2026-10-12 18:00:00");
+        t.check_ins[end].net_minutes = Some(480);
+
+        let (_, summary) = run(&t, "2026-10-12", "2026-10-12", &[], "2026-10-12");
+
+        assert_eq!(summary.net_work_minutes, 480);
+    }
+
+    #[test]
+    fn cross_day_shift_belongs_to_its_start_day() {
+        // Night shift Monday 20:00 → Tuesday 04:00, nothing on Tuesday
+        let mut t = Timeline::new();
+        t.shift("2026-10-12 20:00:00", "2026-10-13 04:00:00");
+        t.lunch("2026-10-13 00:30:00", "2026-10-13 01:00:00");
+
+        let (days, summary) = run(&t, "2026-10-12", "2026-10-13", &[], "2026-10-13");
+
+        assert_eq!(days[0].shift_count, 1);
+        assert_eq!(days[0].first_in.as_deref(), Some("20:00"));
+        assert_eq!(days[0].last_out.as_deref(), Some("04:00(+1)"));
+        assert_eq!(days[0].work_minutes, 450);
+        assert_eq!(days[0].break_minutes, 30);
+        assert_eq!(days[1].shift_count, 0);
+        assert_eq!(days[1].status, DayStatus::Absent);
+        assert_eq!(summary.attended_days, 1);
+    }
+
+    #[test]
+    fn overtime_is_taken_from_end_records_by_category() {
+        let mut t = Timeline::new();
+        let (_, workday_end) = t.shift("2026-10-12 09:00:00", "2026-10-12 20:00:00");
+        t.check_ins[workday_end].overtime_minutes = Some(120);
+        t.check_ins[workday_end].overtime_category = Some(OvertimeCategory::Workday);
+
+        // Saturday
+        let (_, rest_end) = t.shift("2026-10-17 10:00:00", "2026-10-17 14:00:00");
+        t.check_ins[rest_end].overtime_minutes = Some(240);
+        t.check_ins[rest_end].overtime_category = Some(OvertimeCategory::RestDay);
+
+        let (days, summary) = run(&t, "2026-10-12", "2026-10-18", &[], "2026-10-18");
+
+        assert_eq!(days[5].status, DayStatus::Present);
+        assert_eq!(summary.workday_overtime_minutes, 120);
+        assert_eq!(summary.rest_day_overtime_minutes, 240);
+        assert_eq!(summary.holiday_overtime_minutes, 0);
+        assert_eq!(summary.overtime_minutes, 360);
+    }
+
+    #[test]
+    fn holidays_and_future_days_are_not_absences() {
+        let t = Timeline::new();
+        let holidays = vec![holiday("2026-10-13")];
+
+        let (days, summary) = run(&t, "2026-10-12", "2026-10-16", &holidays, "2026-10-14");
+
+        assert_eq!(days[0].status, DayStatus::Absent);
+        assert_eq!(days[1].status, DayStatus::Holiday);
+        assert_eq!(days[2].status, DayStatus::Absent);
+        assert_eq!(days[3].status, DayStatus::Upcoming);
+        assert_eq!(days[4].status, DayStatus::Upcoming);
+        assert_eq!(summary.scheduled_days, 2);
+        assert_eq!(summary.absent_days, 2);
+    }
+
+    #[test]
+    fn leave_days_are_not_absences() {
+        let mut t = Timeline::new();
+        t.shift("2026-10-12 09:00:00", "2026-10-12 18:00:00");
+        let leaves = vec![leave("2026-10-13", "2026-10-14")];
+
+        let (days, summary) = run_with_leaves(&t, "2026-10-12", "2026-10-15", &[], &leaves, "2026-10-15");
+
+        assert_eq!(days[1].status, DayStatus::Leave);
+        assert_eq!(days[2].status, DayStatus::Leave);
+        assert_eq!(days[3].status, DayStatus::Absent);
+        assert_eq!(summary.leave_days, 2);
+        assert_eq!(summary.absent_days, 1);
+    }
+
+    #[test]
+    fn days_outside_employment_are_not_absences() {
+        let mut t = Timeline::new();
+        t.shift("2026-10-13 09:00:00", "2026-10-13 18:00:00");
+        let action_types = action_types();
+        let schedule = schedule();
+        let ctx = AttendanceContext {
+            action_types: &action_types,
+            schedule: Some(&schedule),
+            holidays: &[],
+            leaves: &[],
+            timezone: TZ,
+            today: date("2026-10-16"),
+            hired_on: Some(date("2026-10-13")),
+            terminated_on: Some(date("2026-10-14")),
+        };
+
+        let days = summarize_days(&t.check_ins, date("2026-10-12"), date("2026-10-16"), &ctx);
+        let summary = summarize_attendance(&days, date("2026-10-16"));
+
+        assert_eq!(days[0].status, DayStatus::NotEmployed);
+        assert_eq!(days[1].status, DayStatus::Present);
+        assert_eq!(days[2].status, DayStatus::Absent);
+        assert_eq!(days[3].status, DayStatus::NotEmployed);
+        assert_eq!(summary.scheduled_days, 2);
+        assert_eq!(summary.absent_days, 1);
+    }
+
+    #[test]
+    fn ongoing_shift_counts_as_attended_without_minutes() {
+        let mut t = Timeline::new();
+        let start = t.record(1, "2026-10-12 09:00:00");
+        t.check_ins[start].status = "ongoing".to_string();
+
+        let (days, summary) = run(&t, "2026-10-12", "2026-10-12", &[], "2026-10-12");
+
+        assert_eq!(days[0].status, DayStatus::Present);
+        assert_eq!(summary.attended_days, 1);
+        assert_eq!(summary.net_work_minutes, 0);
+    }
+
+    #[test]
+    fn employee_schedule_prefers_clock_in_rule_per_scope() {
+        let user = |department_id: Option<i32>, default_schedule_rule_id: Option<i32>| -> User {
+            serde_json::from_value(serde_json::json!({
+                "id": 1,
+                "username": "alice",
+                "password_hash": "",
+                "full_name": "alice",
+                "is_admin": false,
+                "created_at": "",
+                "department_id": department_id,
+                "default_schedule_rule_id": default_schedule_rule_id,
+            }))
+            .unwrap()
+        };
+        let rule = |id: i32, action_type_id: i32, department_id: Option<i32>, is_active: bool| TimeRule {
+            id,
+            action_type_id,
+            department_id,
+            is_active,
+            ..schedule()
+        };
+        let departments = vec![Department {
+            id: 10,
+            name: "研发部".to_string(),
+            parent_id: None,
+            manager_id: None,
+            created_at: String::new(),
+        }];
+        // 下班规则排在前面也以上班规则为准；部门规则优先于全公司规则
+        let rules = vec![
+            rule(1, 2, None, true),
+            rule(2, 1, None, true),
+            rule(3, 2, Some(10), true),
+            rule(4, 1, None, false),
+        ];
+        let types = action_types();
+        let pick = |user: &User| employee_schedule(&rules, &types, &departments, user).map(|r| r.id);
+
+        assert_eq!(pick(&user(None, None)), Some(2));
+        assert_eq!(pick(&user(Some(10), None)), Some(3));
+        assert_eq!(pick(&user(Some(10), Some(1))), Some(1));
+        // 停用的默认排班不生效
+        assert_eq!(pick(&user(None, Some(4))), Some(2));
+    }
+}
@@ -0,0 +1,636 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use crate::models::{ActionType, Holiday, TimeRule};
+use crate::utils::config::{find_config_issues, introduced_issues, ConfigIssue, NEW_ENTITY_ID};
+
+/// Version written into exported documents; newer documents are refused on import
+pub const CONFIG_FORMAT_VERSION: u32 = 1;
+
+const DEFAULT_TIMEZONE: &str = "Asia/Phnom_Penh";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFileFormat {
+    Json,
+    Toml,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+/// Action type as stored in a configuration document; the pair is referenced by name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionTypeEntry {
+    pub name: String,
+    pub button_text: String,
+    pub button_color: String,
+    pub display_order: i32,
+    pub action_role: i32,
+    pub requires_pair: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pair_action: Option<String>,
+    #[serde(default)]
+    pub is_paid_break: bool,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+/// Time rule as stored in a configuration document; the action type is referenced by name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeRuleEntry {
+    pub rule_name: String,
+    pub action_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_start_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_end_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_max_total_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_max_count: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rest_days: Option<Vec<i32>>,
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HolidayEntry {
+    pub holiday_date: String,
+    pub name: String,
+    #[serde(default)]
+    pub is_workday: bool,
+}
+
+/// Company configuration document: action types, time rules, holiday calendar and settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompanyConfig {
+    pub format_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<String>,
+    #[serde(default)]
+    pub action_types: Vec<ActionTypeEntry>,
+    #[serde(default)]
+    pub time_rules: Vec<TimeRuleEntry>,
+    #[serde(default)]
+    pub holidays: Vec<HolidayEntry>,
+    #[serde(default)]
+    pub settings: BTreeMap<String, String>,
+}
+
+/// Configuration currently stored in the deployment
+pub struct CurrentConfig<'a> {
+    pub action_types: &'a [ActionType],
+    pub rules: &'a [TimeRule],
+    pub holidays: &'a [Holiday],
+    pub settings: &'a HashMap<String, String>,
+}
+
+impl ActionTypeEntry {
+    fn from_action_type(at: &ActionType, names: &HashMap<i32, &str>) -> Self {
+        Self {
+            name: at.name.clone(),
+            button_text: at.button_text.clone(),
+            button_color: at.button_color.clone(),
+            display_order: at.display_order,
+            action_role: at.action_role,
+            requires_pair: at.requires_pair,
+            pair_action: at.pair_action_id.and_then(|id| names.get(&id)).map(|n| n.to_string()),
+            is_paid_break: at.is_paid_break,
+            is_active: at.is_active,
+        }
+    }
+}
+
+impl TimeRuleEntry {
+    fn from_time_rule(rule: &TimeRule, action_type: &str) -> Self {
+        Self {
+            rule_name: rule.rule_name.clone(),
+            action_type: action_type.to_string(),
+            expected_start_time: rule.expected_start_time.clone(),
+            expected_end_time: rule.expected_end_time.clone(),
+            max_duration_minutes: rule.max_duration_minutes,
+            daily_max_total_minutes: rule.daily_max_total_minutes,
+            daily_max_count: rule.daily_max_count,
+            rest_days: rule.rest_days.clone().filter(|days| !days.is_empty()),
+            timezone: rule.timezone.clone(),
+            is_active: rule.is_active,
+        }
+    }
+}
+
+impl HolidayEntry {
+    fn from_holiday(holiday: &Holiday) -> Self {
+        Self {
+            holiday_date: holiday.holiday_date.clone(),
+            name: holiday.name.clone(),
+            is_workday: holiday.is_workday,
+        }
+    }
+}
+
+fn name_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Build the document from the stored configuration; archived entries are left out
+pub fn build_company_config(current: &CurrentConfig, exported_at: String) -> Result<CompanyConfig, String> {
+    let action_types: Vec<&ActionType> = current
+        .action_types
+        .iter()
+        .filter(|at| at.archived_at.is_none())
+        .collect();
+
+    let mut seen = HashSet::new();
+    for at in &action_types {
+        if !seen.insert(name_key(&at.name)) {
+            return Err(format!("打卡类型名称「{}」重复，请先改名再导出", at.name));
+        }
+    }
+
+    let names: HashMap<i32, &str> = action_types.iter().map(|at| (at.id, at.name.as_str())).collect();
+    // 部门排班依赖本公司的组织架构，不随配置文档导出
+    let time_rules = current
+        .rules
+        .iter()
+        .filter(|rule| rule.archived_at.is_none() && rule.department_id.is_none())
+        .filter_map(|rule| {
+            names
+                .get(&rule.action_type_id)
+                .map(|name| TimeRuleEntry::from_time_rule(rule, name))
+        })
+        .collect();
+
+    Ok(CompanyConfig {
+        format_version: CONFIG_FORMAT_VERSION,
+        exported_at: Some(exported_at),
+        action_types: action_types
+            .iter()
+            .map(|at| ActionTypeEntry::from_action_type(at, &names))
+            .collect(),
+        time_rules,
+        holidays: current.holidays.iter().map(HolidayEntry::from_holiday).collect(),
+        settings: current.settings.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+    })
+}
+
+pub fn serialize_company_config(config: &CompanyConfig, format: ConfigFileFormat) -> Result<String, String> {
+    match format {
+        ConfigFileFormat::Json => serde_json::to_string_pretty(config)
+            .map_err(|e| format!("Failed to serialize config: {}", e)),
+        ConfigFileFormat::Toml => {
+            toml::to_string_pretty(config).map_err(|e| format!("Failed to serialize config: {}", e))
+        }
+    }
+}
+
+pub fn parse_company_config(content: &str, format: ConfigFileFormat) -> Result<CompanyConfig, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let config: CompanyConfig = match format {
+        ConfigFileFormat::Json => {
+            serde_json::from_str(content).map_err(|e| format!("无法解析 JSON 配置: {}", e))?
+        }
+        ConfigFileFormat::Toml => {
+            toml::from_str(content).map_err(|e| format!("无法解析 TOML 配置: {}", e))?
+        }
+    };
+
+    if config.format_version > CONFIG_FORMAT_VERSION {
+        return Err(format!(
+            "配置文件版本 {} 高于当前支持的版本 {}，请先升级应用",
+            config.format_version, CONFIG_FORMAT_VERSION
+        ));
+    }
+    Ok(config)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSection {
+    ActionType,
+    TimeRule,
+    Holiday,
+    Setting,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Create,
+    Update,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub current: Value,
+    pub imported: Value,
+}
+
+/// One entry of the import preview
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    pub section: ConfigSection,
+    pub name: String,
+    pub kind: ChangeKind,
+    pub fields: Vec<FieldChange>,
+}
+
+#[derive(Debug, Default)]
+pub struct ConfigImportPlan {
+    pub changes: Vec<ConfigChange>,
+    /// Inconsistencies the import would introduce
+    pub issues: Vec<ConfigIssue>,
+    /// Problems in the document itself, such as references to unknown action types
+    pub errors: Vec<String>,
+}
+
+impl ConfigImportPlan {
+    pub fn can_apply(&self) -> bool {
+        self.errors.is_empty() && self.issues.is_empty()
+    }
+}
+
+/// Compare two entries field by field, using their serialized form
+fn field_changes<T: Serialize>(current: Option<&T>, imported: &T) -> (ChangeKind, Vec<FieldChange>) {
+    let to_map = |value: &T| match serde_json::to_value(value) {
+        Ok(Value::Object(map)) => map,
+        _ => Default::default(),
+    };
+    let imported = to_map(imported);
+    let Some(current) = current.map(to_map) else {
+        return (ChangeKind::Create, Vec::new());
+    };
+
+    let fields: Vec<FieldChange> = imported
+        .iter()
+        .filter(|(field, value)| current.get(*field).unwrap_or(&Value::Null) != *value)
+        .map(|(field, value)| FieldChange {
+            field: field.clone(),
+            current: current.get(field).cloned().unwrap_or(Value::Null),
+            imported: value.clone(),
+        })
+        .collect();
+    let kind = if fields.is_empty() { ChangeKind::Unchanged } else { ChangeKind::Update };
+    (kind, fields)
+}
+
+/// Preview an import: what would be created or updated, and whether the result stays consistent.
+///
+/// Action types are matched by name and time rules by action type and rule name, the same
+/// way the database function applies the document. Nothing is deleted.
+pub fn plan_company_config_import(config: &CompanyConfig, current: &CurrentConfig) -> ConfigImportPlan {
+    let mut plan = ConfigImportPlan::default();
+
+    let live_types: Vec<&ActionType> = current
+        .action_types
+        .iter()
+        .filter(|at| at.archived_at.is_none())
+        .collect();
+    let mut existing_by_name: HashMap<String, &ActionType> = HashMap::new();
+    for at in &live_types {
+        existing_by_name.entry(name_key(&at.name)).or_insert(at);
+    }
+    let existing_names: HashMap<i32, &str> = live_types.iter().map(|at| (at.id, at.name.as_str())).collect();
+
+    let mut doc_names = HashSet::new();
+    for entry in &config.action_types {
+        if !doc_names.insert(name_key(&entry.name)) {
+            plan.errors.push(format!("打卡类型「{}」在配置文件中重复", entry.name));
+        }
+    }
+    let known_type = |name: &str| doc_names.contains(&name_key(name)) || existing_by_name.contains_key(&name_key(name));
+
+    // 合并后的打卡类型：已有的按名称更新，新建的使用互不相同的负数 ID
+    let mut merged_types: Vec<ActionType> = current.action_types.to_vec();
+    let mut ids_by_name: HashMap<String, i32> = existing_by_name.iter().map(|(k, at)| (k.clone(), at.id)).collect();
+    let mut next_new_id = NEW_ENTITY_ID;
+    for entry in &config.action_types {
+        let existing = existing_by_name.get(&name_key(&entry.name));
+        let current_entry = existing.map(|at| ActionTypeEntry::from_action_type(at, &existing_names));
+        let (kind, fields) = field_changes(current_entry.as_ref(), entry);
+        plan.changes.push(ConfigChange {
+            section: ConfigSection::ActionType,
+            name: entry.name.clone(),
+            kind,
+            fields,
+        });
+
+        if let Some(pair) = entry.pair_action.as_deref().filter(|pair| !known_type(pair)) {
+            plan.errors.push(format!("打卡类型「{}」配对的「{}」不存在", entry.name, pair));
+        }
+
+        let id = match existing {
+            Some(at) => at.id,
+            None => {
+                let id = next_new_id;
+                next_new_id -= 1;
+                ids_by_name.insert(name_key(&entry.name), id);
+                id
+            }
+        };
+        let merged = ActionType {
+            id,
+            name: entry.name.clone(),
+            button_text: entry.button_text.clone(),
+            button_color: entry.button_color.clone(),
+            display_order: entry.display_order,
+            action_role: entry.action_role,
+            requires_pair: entry.requires_pair,
+            pair_action_id: None,
+            is_paid_break: entry.is_paid_break,
+            is_active: entry.is_active,
+            archived_at: None,
+            created_at: String::new(),
+        };
+        match merged_types.iter_mut().find(|at| at.id == id) {
+            Some(slot) => *slot = merged,
+            None => merged_types.push(merged),
+        }
+    }
+    // 所有类型就位后再解析配对，允许引用文件中排在后面的类型
+    for entry in &config.action_types {
+        let id = ids_by_name[&name_key(&entry.name)];
+        let pair_id = entry.pair_action.as_deref().and_then(|pair| ids_by_name.get(&name_key(pair)).copied());
+        if let Some(at) = merged_types.iter_mut().find(|at| at.id == id) {
+            at.pair_action_id = pair_id;
+        }
+    }
+
+    let mut merged_rules: Vec<TimeRule> = current.rules.to_vec();
+    for entry in &config.time_rules {
+        let Some(&action_type_id) = ids_by_name.get(&name_key(&entry.action_type)) else {
+            plan.errors.push(format!("时间规则「{}」适用的打卡类型「{}」不存在", entry.rule_name, entry.action_type));
+            continue;
+        };
+        let existing = current
+            .rules
+            .iter()
+            .filter(|rule| rule.archived_at.is_none() && rule.department_id.is_none())
+            .find(|rule| rule.action_type_id == action_type_id && rule.rule_name == entry.rule_name);
+        let current_entry = existing.map(|rule| {
+            TimeRuleEntry::from_time_rule(rule, existing_names.get(&rule.action_type_id).copied().unwrap_or_default())
+        });
+        let (kind, fields) = field_changes(current_entry.as_ref(), entry);
+        plan.changes.push(ConfigChange {
+            section: ConfigSection::TimeRule,
+            name: format!("{} / {}", entry.action_type, entry.rule_name),
+            kind,
+            fields,
+        });
+
+        let merged = TimeRule {
+            id: existing.map(|rule| rule.id).unwrap_or_else(|| {
+                let id = next_new_id;
+                next_new_id -= 1;
+                id
+            }),
+            rule_name: entry.rule_name.clone(),
+            action_type_id,
+            expected_start_time: entry.expected_start_time.clone(),
+            expected_end_time: entry.expected_end_time.clone(),
+            max_duration_minutes: entry.max_duration_minutes,
+            daily_max_total_minutes: entry.daily_max_total_minutes,
+            daily_max_count: entry.daily_max_count,
+            rest_days: entry.rest_days.clone(),
+            timezone: entry.timezone.clone(),
+            is_active: entry.is_active,
+            archived_at: None,
+            department_id: None,
+            created_at: String::new(),
+        };
+        match merged_rules.iter_mut().find(|rule| rule.id == merged.id) {
+            Some(slot) => *slot = merged,
+            None => merged_rules.push(merged),
+        }
+    }
+
+    for entry in &config.holidays {
+        if chrono::NaiveDate::parse_from_str(&entry.holiday_date, "%Y-%m-%d").is_err() {
+            plan.errors.push(format!("节假日「{}」的日期格式无效: {}", entry.name, entry.holiday_date));
+            continue;
+        }
+        let existing = current
+            .holidays
+            .iter()
+            .find(|h| h.holiday_date.get(..10) == Some(entry.holiday_date.as_str()))
+            .map(HolidayEntry::from_holiday);
+        let (kind, fields) = field_changes(existing.as_ref(), entry);
+        plan.changes.push(ConfigChange {
+            section: ConfigSection::Holiday,
+            name: format!("{} {}", entry.holiday_date, entry.name),
+            kind,
+            fields,
+        });
+    }
+
+    for (key, value) in &config.settings {
+        let (kind, fields) = match current.settings.get(key) {
+            None => (ChangeKind::Create, Vec::new()),
+            Some(current_value) if current_value == value => (ChangeKind::Unchanged, Vec::new()),
+            Some(current_value) => (
+                ChangeKind::Update,
+                vec![FieldChange {
+                    field: "config_value".to_string(),
+                    current: Value::String(current_value.clone()),
+                    imported: Value::String(value.clone()),
+                }],
+            ),
+        };
+        plan.changes.push(ConfigChange {
+            section: ConfigSection::Setting,
+            name: key.clone(),
+            kind,
+            fields,
+        });
+    }
+
+    let before = find_config_issues(current.action_types, current.rules);
+    plan.issues = introduced_issues(&before, find_config_issues(&merged_types, &merged_rules));
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action_type(id: i32, name: &str, role: i32, pair_action_id: Option<i32>) -> ActionType {
+        ActionType {
+            id,
+            name: name.to_string(),
+            button_text: name.to_string(),
+            button_color: "#3b82f6".to_string(),
+            display_order: id,
+            action_role: role,
+            requires_pair: true,
+            pair_action_id,
+            is_paid_break: false,
+            is_active: true,
+            archived_at: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn entry(name: &str, role: i32, pair_action: Option<&str>) -> ActionTypeEntry {
+        ActionTypeEntry {
+            name: name.to_string(),
+            button_text: name.to_string(),
+            button_color: "#3b82f6".to_string(),
+            display_order: 1,
+            action_role: role,
+            requires_pair: true,
+            pair_action: pair_action.map(str::to_string),
+            is_paid_break: false,
+            is_active: true,
+        }
+    }
+
+    fn rule_entry(rule_name: &str, action_type: &str) -> TimeRuleEntry {
+        TimeRuleEntry {
+            rule_name: rule_name.to_string(),
+            action_type: action_type.to_string(),
+            expected_start_time: None,
+            expected_end_time: None,
+            max_duration_minutes: None,
+            daily_max_total_minutes: None,
+            daily_max_count: None,
+            rest_days: None,
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            is_active: true,
+        }
+    }
+
+    fn document(action_types: Vec<ActionTypeEntry>, time_rules: Vec<TimeRuleEntry>) -> CompanyConfig {
+        CompanyConfig {
+            format_version: CONFIG_FORMAT_VERSION,
+            exported_at: None,
+            action_types,
+            time_rules,
+            holidays: Vec::new(),
+            settings: BTreeMap::new(),
+        }
+    }
+
+    fn messages(plan: &ConfigImportPlan) -> Vec<String> {
+        plan.issues.iter().map(ConfigIssue::describe).collect()
+    }
+
+    #[test]
+    fn export_then_import_changes_nothing() {
+        let action_types = vec![
+            action_type(10, "上班", 1, Some(11)),
+            action_type(11, "下班", 2, Some(10)),
+            action_type(12, "午餐", 3, Some(13)),
+            action_type(13, "回座", 4, Some(12)),
+        ];
+        let rules = vec![TimeRule {
+            id: 1,
+            rule_name: "标准班".to_string(),
+            action_type_id: 10,
+            expected_start_time: Some("09:00:00".to_string()),
+            expected_end_time: Some("18:00:00".to_string()),
+            max_duration_minutes: None,
+            daily_max_total_minutes: None,
+            daily_max_count: None,
+            rest_days: Some(vec![6, 7]),
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            is_active: true,
+            archived_at: None,
+            department_id: None,
+            created_at: String::new(),
+        }];
+        let holidays = vec![Holiday {
+            id: 1,
+            holiday_date: "2026-01-01".to_string(),
+            name: "元旦".to_string(),
+            is_workday: false,
+            created_at: String::new(),
+        }];
+        let settings = HashMap::from([("overtime_min_minutes".to_string(), "30".to_string())]);
+        let current = CurrentConfig { action_types: &action_types, rules: &rules, holidays: &holidays, settings: &settings };
+
+        let exported = build_company_config(&current, "2026-10-19T00:00:00+07:00".to_string()).unwrap();
+        assert_eq!(exported.action_types[0].pair_action.as_deref(), Some("下班"));
+        for format in [ConfigFileFormat::Json, ConfigFileFormat::Toml] {
+            let content = serialize_company_config(&exported, format).unwrap();
+            let imported = parse_company_config(&content, format).unwrap();
+            assert_eq!(imported, exported);
+
+            let plan = plan_company_config_import(&imported, &current);
+            assert!(plan.can_apply());
+            assert_eq!(plan.changes.len(), 7);
+            assert!(plan.changes.iter().all(|c| c.kind == ChangeKind::Unchanged));
+        }
+    }
+
+    #[test]
+    fn pair_may_reference_a_later_type() {
+        // 已有的「下班」ID 与文档来源库不同，新建类型使用负数 ID
+        let mut existing = action_type(7, "下班", 2, None);
+        existing.display_order = 1;
+        let action_types = vec![existing];
+        let settings = HashMap::new();
+        let current = CurrentConfig { action_types: &action_types, rules: &[], holidays: &[], settings: &settings };
+
+        let config = document(
+            vec![
+                entry("上班", 1, Some("下班")),
+                entry("下班", 2, Some("上班")),
+                entry("午餐", 3, Some("回座")),
+                entry("回座", 4, Some("午餐")),
+            ],
+            Vec::new(),
+        );
+        let plan = plan_company_config_import(&config, &current);
+        assert!(plan.can_apply(), "{:?} {:?}", plan.errors, messages(&plan));
+        let kinds: Vec<_> = plan.changes.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![ChangeKind::Create, ChangeKind::Update, ChangeKind::Create, ChangeKind::Create]);
+        assert_eq!(plan.changes[1].fields.iter().map(|f| f.field.as_str()).collect::<Vec<_>>(), vec!["pair_action"]);
+
+        // 配对按名称解析到文档中排在后面的类型，角色不符同样能被发现
+        let config = document(vec![entry("上班", 1, Some("午餐")), entry("午餐", 3, None), entry("回座", 4, None)], Vec::new());
+        let plan = plan_company_config_import(&config, &current);
+        assert!(plan.errors.is_empty());
+        assert_eq!(messages(&plan), vec!["打卡类型「上班」: 不能与临时事件类型「午餐」配对"]);
+    }
+
+    #[test]
+    fn unknown_action_type_references_are_errors() {
+        let settings = HashMap::new();
+        let current = CurrentConfig { action_types: &[], rules: &[], holidays: &[], settings: &settings };
+
+        let config = document(vec![entry("上班", 1, Some("收工"))], vec![rule_entry("午休", "午餐")]);
+        let plan = plan_company_config_import(&config, &current);
+        assert_eq!(
+            plan.errors,
+            vec!["打卡类型「上班」配对的「收工」不存在", "时间规则「午休」适用的打卡类型「午餐」不存在"]
+        );
+        assert!(!plan.can_apply());
+    }
+
+    #[test]
+    fn import_introducing_config_issue_is_refused() {
+        let action_types = vec![action_type(1, "午餐", 3, Some(2)), action_type(2, "回座", 4, Some(1))];
+        let settings = HashMap::new();
+        let current = CurrentConfig { action_types: &action_types, rules: &[], holidays: &[], settings: &settings };
+
+        let mut rule = rule_entry("午休", "午餐");
+        rule.expected_start_time = Some("12:00:00".to_string());
+        rule.max_duration_minutes = Some(60);
+        let plan = plan_company_config_import(&document(Vec::new(), vec![rule]), &current);
+        assert!(plan.errors.is_empty());
+        assert_eq!(messages(&plan), vec!["时间规则「午休」: 临时事件类型的规则不能设置上班时间"]);
+        assert!(!plan.can_apply());
+    }
+}
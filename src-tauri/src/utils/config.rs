@@ -0,0 +1,315 @@
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use crate::models::{ActionType, TimeRule};
+
+/// Id given to an entity that is about to be created, while checking it against the rest
+pub const NEW_ENTITY_ID: i32 = -1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigEntity {
+    ActionType,
+    TimeRule,
+}
+
+/// One inconsistency in the action type and time rule configuration
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct ConfigIssue {
+    pub entity: ConfigEntity,
+    pub id: i32,
+    pub name: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    pub fn describe(&self) -> String {
+        let kind = match self.entity {
+            ConfigEntity::ActionType => "打卡类型",
+            ConfigEntity::TimeRule => "时间规则",
+        };
+        format!("{}「{}」: {}", kind, self.name, self.message)
+    }
+}
+
+/// Role an action type may be paired with: 上班 ↔ 下班, 临时事件 ↔ 回座
+pub fn paired_role(action_role: i32) -> Option<i32> {
+    match action_role {
+        1 => Some(2),
+        2 => Some(1),
+        3 => Some(4),
+        4 => Some(3),
+        _ => None,
+    }
+}
+
+pub fn role_name(action_role: i32) -> &'static str {
+    match action_role {
+        1 => "上班",
+        2 => "下班",
+        3 => "临时事件",
+        4 => "回座",
+        _ => "未知",
+    }
+}
+
+fn is_valid_time(value: &str) -> bool {
+    NaiveTime::parse_from_str(value, "%H:%M:%S").is_ok() || NaiveTime::parse_from_str(value, "%H:%M").is_ok()
+}
+
+/// Check the active action types and time rules against each other.
+///
+/// Inactive (including archived) entries are only checked as pairing targets,
+/// since they no longer take part in check-ins.
+pub fn find_config_issues(action_types: &[ActionType], rules: &[TimeRule]) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let by_id: HashMap<i32, &ActionType> = action_types.iter().map(|at| (at.id, at)).collect();
+    let active_roles: HashSet<i32> = action_types
+        .iter()
+        .filter(|at| at.is_active)
+        .map(|at| at.action_role)
+        .collect();
+
+    let mut action_issue = |at: &ActionType, message: String| {
+        issues.push(ConfigIssue {
+            entity: ConfigEntity::ActionType,
+            id: at.id,
+            name: at.name.clone(),
+            message,
+        });
+    };
+
+    let mut seen_names: HashMap<String, &str> = HashMap::new();
+    for at in action_types.iter().filter(|at| at.is_active) {
+        if at.name.trim().is_empty() {
+            action_issue(at, "名称不能为空".to_string());
+        } else if let Some(first) = seen_names.get(&at.name.trim().to_lowercase()) {
+            action_issue(at, format!("名称与「{}」重复", first));
+        } else {
+            seen_names.insert(at.name.trim().to_lowercase(), &at.name);
+        }
+
+        let Some(counterpart) = paired_role(at.action_role) else {
+            action_issue(at, format!("无效的打卡角色: {}", at.action_role));
+            continue;
+        };
+        // 下班依赖上班、临时事件依赖回座；上班和回座可以先单独存在，便于逐个创建
+        let required_role = match at.action_role {
+            2 => Some(1),
+            3 => Some(4),
+            _ => None,
+        };
+        if let Some(required_role) = required_role.filter(|role| !active_roles.contains(role)) {
+            action_issue(
+                at,
+                format!("没有启用的{}类型与之对应", role_name(required_role)),
+            );
+        }
+
+        if let Some(pair_id) = at.pair_action_id {
+            match by_id.get(&pair_id) {
+                _ if pair_id == at.id => action_issue(at, "不能与自身配对".to_string()),
+                None => action_issue(at, "配对的打卡类型不存在".to_string()),
+                Some(pair) if !pair.is_active => {
+                    action_issue(at, format!("配对的打卡类型「{}」已停用", pair.name))
+                }
+                Some(pair) if pair.action_role != counterpart => action_issue(
+                    at,
+                    format!("不能与{}类型「{}」配对", role_name(pair.action_role), pair.name),
+                ),
+                Some(_) => {}
+            }
+        }
+
+        if at.is_paid_break && at.action_role != 3 {
+            action_issue(at, "只有临时事件可以设为带薪休息".to_string());
+        }
+    }
+
+    let mut rule_issue = |rule: &TimeRule, message: String| {
+        issues.push(ConfigIssue {
+            entity: ConfigEntity::TimeRule,
+            id: rule.id,
+            name: rule.rule_name.clone(),
+            message,
+        });
+    };
+
+    // 部门排班与全公司规则可以并存，同一范围内每个类型只能有一条生效规则
+    let mut rule_by_action: HashMap<(i32, Option<i32>), &str> = HashMap::new();
+    for rule in rules.iter().filter(|r| r.is_active) {
+        if rule.timezone.parse::<Tz>().is_err() {
+            rule_issue(rule, format!("无效的时区: {}", rule.timezone));
+        }
+
+        let Some(at) = by_id.get(&rule.action_type_id) else {
+            rule_issue(rule, "适用的打卡类型不存在".to_string());
+            continue;
+        };
+        if !at.is_active {
+            rule_issue(rule, format!("适用的打卡类型「{}」已停用", at.name));
+            continue;
+        }
+        if let Some(first) = rule_by_action.insert((rule.action_type_id, rule.department_id), &rule.rule_name) {
+            rule_issue(rule, format!("与规则「{}」适用于同一打卡类型和范围", first));
+        }
+
+        let main_fields = [
+            ("上班时间", rule.expected_start_time.is_some()),
+            ("下班时间", rule.expected_end_time.is_some()),
+            ("休息日", rule.rest_days.as_ref().is_some_and(|days| !days.is_empty())),
+        ];
+        let event_fields = [
+            ("最大时长", rule.max_duration_minutes.is_some()),
+            ("每日累计时长上限", rule.daily_max_total_minutes.is_some()),
+            ("每日次数上限", rule.daily_max_count.is_some()),
+        ];
+        let misplaced: Vec<&str> = match at.action_role {
+            1 | 2 => event_fields.iter().filter(|(_, set)| *set).map(|(label, _)| *label).collect(),
+            3 => main_fields.iter().filter(|(_, set)| *set).map(|(label, _)| *label).collect(),
+            _ => main_fields
+                .iter()
+                .chain(&event_fields)
+                .filter(|(_, set)| *set)
+                .map(|(label, _)| *label)
+                .collect(),
+        };
+        if !misplaced.is_empty() {
+            rule_issue(
+                rule,
+                format!("{}类型的规则不能设置{}", role_name(at.action_role), misplaced.join("、")),
+            );
+        }
+
+        for (label, value) in [("上班时间", &rule.expected_start_time), ("下班时间", &rule.expected_end_time)] {
+            if let Some(value) = value.as_deref().filter(|v| !is_valid_time(v)) {
+                rule_issue(rule, format!("{}格式无效: {}", label, value));
+            }
+        }
+        for (label, value) in [
+            ("最大时长", rule.max_duration_minutes),
+            ("每日累计时长上限", rule.daily_max_total_minutes),
+            ("每日次数上限", rule.daily_max_count),
+        ] {
+            if value.is_some_and(|v| v <= 0) {
+                rule_issue(rule, format!("{}必须大于 0", label));
+            }
+        }
+        if let Some(rest_days) = &rule.rest_days {
+            if rest_days.iter().any(|d| !(1..=7).contains(d)) {
+                rule_issue(rule, "休息日只能是 1（周一）到 7（周日）".to_string());
+            }
+        }
+    }
+
+    issues
+}
+
+/// Issues of the changed configuration that the current one does not have
+pub fn introduced_issues(before: &[ConfigIssue], after: Vec<ConfigIssue>) -> Vec<ConfigIssue> {
+    let before: HashSet<&ConfigIssue> = before.iter().collect();
+    after.into_iter().filter(|issue| !before.contains(issue)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action_type(id: i32, role: i32) -> ActionType {
+        ActionType {
+            id,
+            name: format!("action-{}", id),
+            button_text: format!("action-{}", id),
+            button_color: "#000000".to_string(),
+            display_order: id,
+            action_role: role,
+            requires_pair: true,
+            pair_action_id: None,
+            is_paid_break: false,
+            is_active: true,
+            archived_at: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn rule(id: i32, action_type_id: i32) -> TimeRule {
+        TimeRule {
+            id,
+            rule_name: format!("rule-{}", id),
+            action_type_id,
+            expected_start_time: None,
+            expected_end_time: None,
+            max_duration_minutes: None,
+            daily_max_total_minutes: None,
+            daily_max_count: None,
+            rest_days: None,
+            timezone: "Asia/Phnom_Penh".to_string(),
+            is_active: true,
+            archived_at: None,
+            department_id: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn standard() -> (Vec<ActionType>, Vec<TimeRule>) {
+        let mut types = vec![action_type(1, 1), action_type(2, 2), action_type(3, 3), action_type(4, 4)];
+        types[0].pair_action_id = Some(2);
+        types[2].pair_action_id = Some(4);
+
+        let mut schedule = rule(1, 1);
+        schedule.expected_start_time = Some("09:00:00".to_string());
+        schedule.expected_end_time = Some("18:00".to_string());
+        schedule.rest_days = Some(vec![6, 7]);
+        let mut lunch = rule(2, 3);
+        lunch.max_duration_minutes = Some(60);
+        (types, vec![schedule, lunch])
+    }
+
+    #[test]
+    fn standard_configuration_is_consistent() {
+        let (types, rules) = standard();
+        assert!(find_config_issues(&types, &rules).is_empty());
+    }
+
+    #[test]
+    fn reports_missing_counterpart_bad_pairing_and_misplaced_rule_fields() {
+        let (mut types, mut rules) = standard();
+        types[0].is_active = false;
+        types[2].pair_action_id = Some(2);
+        rules[1].expected_start_time = Some("09:00:00".to_string());
+        let mut closing = rule(3, 2);
+        closing.max_duration_minutes = Some(30);
+        rules.push(closing);
+
+        let issues = find_config_issues(&types, &rules);
+        let messages: Vec<(i32, &str)> = issues.iter().map(|i| (i.id, i.message.as_str())).collect();
+        assert_eq!(
+            messages,
+            vec![
+                (2, "没有启用的上班类型与之对应"),
+                (3, "不能与下班类型「action-2」配对"),
+                (1, "适用的打卡类型「action-1」已停用"),
+                (2, "临时事件类型的规则不能设置上班时间"),
+                (3, "下班类型的规则不能设置最大时长"),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_new_issues_block_a_change() {
+        let (mut types, rules) = standard();
+        types[3].is_paid_break = true;
+        let before = find_config_issues(&types, &rules);
+        assert_eq!(before.len(), 1);
+
+        types.push(action_type(NEW_ENTITY_ID, 2));
+        assert!(introduced_issues(&before, find_config_issues(&types, &rules)).is_empty());
+
+        types[1].is_active = false;
+        types.pop();
+        let introduced = introduced_issues(&before, find_config_issues(&types, &rules));
+        assert_eq!(introduced.len(), 1);
+        assert_eq!(introduced[0].message, "配对的打卡类型「action-2」已停用");
+    }
+}
@@ -1,3 +1,4 @@
+use rand::Rng;
 use sha2::{Sha256, Digest};
 
 /// Hash password using SHA256
@@ -11,3 +12,14 @@ pub fn hash_password(password: &str) -> String {
 pub fn verify_password(password: &str, hash: &str) -> bool {
     hash_password(password) == hash
 }
+
+/// Characters of generated passwords; look-alikes such as 0/O and 1/l/I are left out
+const PASSWORD_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnpqrstuvwxyz23456789";
+
+/// Generate a random password for a new account
+pub fn generate_password(length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| PASSWORD_ALPHABET[rng.gen_range(0..PASSWORD_ALPHABET.len())] as char)
+        .collect()
+}
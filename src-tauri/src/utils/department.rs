@@ -0,0 +1,139 @@
+use crate::models::{ActionType, Department, TimeRule, User};
+use std::collections::HashSet;
+
+/// Rule scopes that apply to members of a department, most specific first:
+/// the department itself, each parent up to the top level, then company-wide (`None`)
+pub fn schedule_scopes(departments: &[Department], department_id: Option<i32>) -> Vec<Option<i32>> {
+    let mut scopes = Vec::new();
+    let mut seen = HashSet::new();
+    let mut current = department_id;
+    while let Some(id) = current {
+        // 数据被改坏出现环时停止
+        if !seen.insert(id) {
+            break;
+        }
+        scopes.push(Some(id));
+        current = departments.iter().find(|d| d.id == id).and_then(|d| d.parent_id);
+    }
+    scopes.push(None);
+    scopes
+}
+
+/// A department and all teams below it
+pub fn department_subtree(departments: &[Department], root: i32) -> Vec<i32> {
+    let mut ids = vec![root];
+    let mut index = 0;
+    while index < ids.len() {
+        let parent = ids[index];
+        for child in departments.iter().filter(|d| d.parent_id == Some(parent)) {
+            if !ids.contains(&child.id) {
+                ids.push(child.id);
+            }
+        }
+        index += 1;
+    }
+    ids
+}
+
+/// Departments a manager is responsible for, including all teams below them
+pub fn managed_departments(departments: &[Department], manager_id: i32) -> Vec<i32> {
+    let mut ids = Vec::new();
+    for department in departments.iter().filter(|d| d.manager_id == Some(manager_id)) {
+        for id in department_subtree(departments, department.id) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+/// Whether moving `department_id` under `new_parent` would make it its own ancestor
+pub fn creates_cycle(departments: &[Department], department_id: i32, new_parent: i32) -> bool {
+    department_subtree(departments, department_id).contains(&new_parent)
+}
+
+/// Active rule of an action type for a member of the department;
+/// the closest department rule wins over parent departments and the company rule
+pub fn effective_rule<'a>(
+    rules: &'a [TimeRule],
+    departments: &[Department],
+    department_id: Option<i32>,
+    action_type_id: i32,
+) -> Option<&'a TimeRule> {
+    schedule_scopes(departments, department_id).into_iter().find_map(|scope| {
+        rules.iter().find(|rule| {
+            rule.is_active && rule.action_type_id == action_type_id && rule.department_id == scope
+        })
+    })
+}
+
+/// Active rule of an action type for an employee. The employee's default schedule
+/// replaces the 上班/下班 rules; other actions follow the department rules.
+pub fn employee_rule<'a>(
+    rules: &'a [TimeRule],
+    action_types: &[ActionType],
+    departments: &[Department],
+    user: &User,
+    action_type_id: i32,
+) -> Option<&'a TimeRule> {
+    let is_main_process = |id: i32| {
+        action_types
+            .iter()
+            .any(|at| at.id == id && matches!(at.action_role, 1 | 2))
+    };
+    user.default_schedule_rule_id
+        .and_then(|id| rules.iter().find(|rule| rule.id == id && rule.is_active))
+        .filter(|rule| {
+            rule.action_type_id == action_type_id
+                || (is_main_process(action_type_id) && is_main_process(rule.action_type_id))
+        })
+        .or_else(|| effective_rule(rules, departments, user.department_id, action_type_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn department(id: i32, parent_id: Option<i32>) -> Department {
+        Department {
+            id,
+            name: format!("department-{}", id),
+            parent_id,
+            manager_id: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn rule(id: i32, department_id: Option<i32>) -> TimeRule {
+        TimeRule {
+            id,
+            rule_name: format!("rule-{}", id),
+            action_type_id: 1,
+            expected_start_time: Some("09:00:00".to_string()),
+            expected_end_time: Some("18:00:00".to_string()),
+            max_duration_minutes: None,
+            daily_max_total_minutes: None,
+            daily_max_count: None,
+            rest_days: None,
+            timezone: "Asia/Phnom_Penh".to_string(),
+            is_active: true,
+            archived_at: None,
+            department_id,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn team_inherits_closest_department_rule() {
+        // 1 公司部门 → 2 团队 → 3 小组
+        let departments = vec![department(1, None), department(2, Some(1)), department(3, Some(2))];
+        let rules = vec![rule(10, None), rule(11, Some(1))];
+
+        assert_eq!(effective_rule(&rules, &departments, Some(3), 1).map(|r| r.id), Some(11));
+        assert_eq!(effective_rule(&rules, &departments, None, 1).map(|r| r.id), Some(10));
+        assert_eq!(department_subtree(&departments, 1), vec![1, 2, 3]);
+        assert!(creates_cycle(&departments, 1, 3));
+        assert!(!creates_cycle(&departments, 3, 1));
+    }
+}
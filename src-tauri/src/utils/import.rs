@@ -0,0 +1,659 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use crate::models::{ActionType, CheckIn, Department, Holiday, OvertimeSettings, TimeRule, User};
+use crate::utils::attendance::employee_schedule;
+use crate::utils::department::employee_rule;
+use crate::utils::overtime::{calculate_overtime, classify_day};
+use crate::utils::quota::{is_overtime_break, summarize_break_usage};
+use crate::utils::time::{calculate_duration, format_time, is_early_leave, is_late_strict, parse_time};
+use crate::utils::worktime::{break_minutes, net_work_minutes};
+
+/// One data row of an attendance CSV; `line` is the 1-based line number in the file
+#[derive(Debug, Clone)]
+pub struct ImportRow {
+    pub line: usize,
+    pub username: String,
+    pub action: String,
+    pub timestamp: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRowError {
+    pub line: usize,
+    pub username: String,
+    pub message: String,
+}
+
+/// Everything the planner checks rows against
+pub struct ImportReference<'a> {
+    pub users: &'a [User],
+    pub action_types: &'a [ActionType],
+    /// Active time rules, including department schedules and default schedules of users
+    pub rules: &'a [TimeRule],
+    pub departments: &'a [Department],
+    pub holidays: &'a [Holiday],
+    pub overtime_settings: &'a OvertimeSettings,
+    /// Records already stored for the imported users, used to skip duplicates
+    pub existing: &'a [CheckIn],
+    pub timezone: &'a str,
+}
+
+/// A check-in to insert; `pair_index` points at its partner in [`ImportPlan::records`]
+#[derive(Debug, Clone)]
+pub struct PlannedCheckIn {
+    pub line: usize,
+    pub username: String,
+    pub check_in: CheckIn,
+    pub pair_index: Option<usize>,
+}
+
+impl PlannedCheckIn {
+    /// Insert payload for `import_check_in_batch`; `pair_offset` is the position of the
+    /// partner within the same batch, turned into `pair_check_in_id` by the database
+    pub fn to_json(&self, pair_offset: Option<usize>) -> Value {
+        let c = &self.check_in;
+        json!({
+            "user_id": c.user_id,
+            "action_type_id": c.action_type_id,
+            "check_time": c.check_time,
+            "status": c.status,
+            "pair_offset": pair_offset,
+            "duration_minutes": c.duration_minutes,
+            "net_minutes": c.net_minutes,
+            "note": c.note,
+            "is_late": c.is_late,
+            "is_early_leave": c.is_early_leave,
+            "is_overtime_break": c.is_overtime_break,
+            "overtime_minutes": c.overtime_minutes,
+            "overtime_category": c.overtime_category,
+            "is_manual": c.is_manual,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportPlan {
+    pub records: Vec<PlannedCheckIn>,
+    pub errors: Vec<ImportRowError>,
+}
+
+pub const USERNAME_HEADERS: &[&str] = &["username", "user", "用户名", "账号"];
+const ACTION_HEADERS: &[&str] = &["action", "action_type", "打卡类型", "动作"];
+const TIMESTAMP_HEADERS: &[&str] = &["timestamp", "time", "check_time", "打卡时间", "时间"];
+const NOTE_HEADERS: &[&str] = &["note", "备注"];
+
+/// A non-empty data row of a [`CsvSheet`]; `line` is the 1-based line number in the file
+pub struct CsvRow {
+    pub line: usize,
+    record: ::csv::StringRecord,
+}
+
+impl CsvRow {
+    pub fn field(&self, col: usize) -> String {
+        self.record.get(col).unwrap_or("").to_string()
+    }
+}
+
+/// An uploaded CSV file whose header row decides the column order (English or Chinese names)
+pub struct CsvSheet {
+    headers: ::csv::StringRecord,
+    pub rows: Vec<CsvRow>,
+    /// Lines that could not be read at all
+    pub errors: Vec<ImportRowError>,
+}
+
+impl CsvSheet {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut reader = ::csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(::csv::Trim::All)
+            .from_reader(content.trim_start_matches('\u{feff}').as_bytes());
+
+        let headers = reader
+            .headers()
+            .map_err(|e| format!("无法读取表头: {}", e))?
+            .clone();
+
+        let mut rows = Vec::new();
+        let mut errors = Vec::new();
+        for (index, record) in reader.records().enumerate() {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    errors.push(ImportRowError {
+                        line: e.position().map(|p| p.line() as usize).unwrap_or(index + 2),
+                        username: String::new(),
+                        message: format!("无法解析该行: {}", e),
+                    });
+                    continue;
+                }
+            };
+            if record.iter().all(|field| field.is_empty()) {
+                continue;
+            }
+            let line = record.position().map(|p| p.line() as usize).unwrap_or(index + 2);
+            rows.push(CsvRow { line, record });
+        }
+
+        Ok(Self { headers, rows, errors })
+    }
+
+    /// Position of the column titled with any of `names`
+    pub fn column(&self, names: &[&str]) -> Option<usize> {
+        self.headers
+            .iter()
+            .position(|h| names.iter().any(|n| h.eq_ignore_ascii_case(n)))
+    }
+
+    pub fn required_column(&self, names: &[&str]) -> Result<usize, String> {
+        self.column(names).ok_or_else(|| format!("缺少列: {}", names[0]))
+    }
+}
+
+/// Read the CSV rows of an attendance import
+pub fn parse_import_csv(content: &str) -> Result<(Vec<ImportRow>, Vec<ImportRowError>), String> {
+    let sheet = CsvSheet::parse(content)?;
+    let username_col = sheet.required_column(USERNAME_HEADERS)?;
+    let action_col = sheet.required_column(ACTION_HEADERS)?;
+    let timestamp_col = sheet.required_column(TIMESTAMP_HEADERS)?;
+    let note_col = sheet.column(NOTE_HEADERS);
+
+    let rows = sheet
+        .rows
+        .iter()
+        .map(|row| ImportRow {
+            line: row.line,
+            username: row.field(username_col),
+            action: row.field(action_col),
+            timestamp: row.field(timestamp_col),
+            note: note_col.map(|col| row.field(col)).filter(|note| !note.is_empty()),
+        })
+        .collect();
+
+    Ok((rows, sheet.errors))
+}
+
+/// Parse a timestamp in the formats the check-in tables use, plus the usual spreadsheet variants
+fn parse_import_time(value: &str, timezone: &str) -> Option<DateTime<Tz>> {
+    if let Some(time) = parse_time(value, timezone) {
+        return Some(time);
+    }
+
+    let tz: Tz = timezone.parse().ok()?;
+    ["%Y-%m-%d %H:%M", "%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value.trim(), format).ok())
+        .and_then(|naive| tz.from_local_datetime(&naive).single())
+}
+
+/// Ongoing main process and break of one employee while replaying the file
+#[derive(Default)]
+struct PairingState {
+    main_start: Option<usize>,
+    break_start: Option<usize>,
+}
+
+/// Replay the rows through the check-in pairing rules.
+///
+/// Rows are ordered per employee by time. A row breaking a rule (for example 下班 without
+/// 上班) is reported and skipped; starts left without an end are reported and dropped,
+/// so no imported record stays ongoing.
+pub fn plan_import(mut rows: Vec<ImportRow>, reference: &ImportReference) -> ImportPlan {
+    let mut plan = ImportPlan::default();
+    let tz = reference.timezone;
+
+    let users: HashMap<String, &User> = reference
+        .users
+        .iter()
+        .map(|u| (u.username.to_lowercase(), u))
+        .collect();
+    let find_action = |name: &str| {
+        reference.action_types.iter().find(|at| {
+            at.name.eq_ignore_ascii_case(name) || at.button_text.eq_ignore_ascii_case(name)
+        })
+    };
+    let rule_of = |action_type_id: i32, user: &User| {
+        employee_rule(reference.rules, reference.action_types, reference.departments, user, action_type_id)
+    };
+
+    let mut seen: HashSet<(i32, i32, String)> = reference
+        .existing
+        .iter()
+        .filter_map(|c| {
+            let time = parse_time(&c.check_time, tz)?;
+            Some((c.user_id, c.action_type_id, format_time(&time)))
+        })
+        .collect();
+
+    // 解析时间后按员工、时间排序
+    let mut parsed = Vec::new();
+    for row in rows.drain(..) {
+        let error = |message: String| ImportRowError {
+            line: row.line,
+            username: row.username.clone(),
+            message,
+        };
+        let Some(user) = users.get(&row.username.to_lowercase()) else {
+            plan.errors.push(error(format!("用户不存在: {}", row.username)));
+            continue;
+        };
+        let Some(action_type) = find_action(&row.action) else {
+            plan.errors.push(error(format!("未知的打卡类型: {}", row.action)));
+            continue;
+        };
+        let Some(time) = parse_import_time(&row.timestamp, tz) else {
+            plan.errors.push(error(format!("时间格式无效: {}", row.timestamp)));
+            continue;
+        };
+        parsed.push((row, *user, action_type, time));
+    }
+    parsed.sort_by_key(|a| (a.1.id, a.3, a.0.line));
+
+    let mut states: HashMap<i32, PairingState> = HashMap::new();
+    for (row, user, action_type, time) in parsed {
+        let error = |message: &str| ImportRowError {
+            line: row.line,
+            username: row.username.clone(),
+            message: message.to_string(),
+        };
+        let check_time = format_time(&time);
+        if !seen.insert((user.id, action_type.id, check_time.clone())) {
+            plan.errors.push(error("重复记录，已存在相同时间的打卡"));
+            continue;
+        }
+
+        let state = states.entry(user.id).or_default();
+        let rule = rule_of(action_type.id, user);
+        let index = plan.records.len();
+        let mut check_in = CheckIn {
+            id: -(index as i32) - 1,
+            user_id: user.id,
+            action_type_id: action_type.id,
+            check_time: check_time.clone(),
+            status: "ongoing".to_string(),
+            pair_check_in_id: None,
+            duration_minutes: None,
+            net_minutes: None,
+            note: row.note.clone(),
+            is_late: false,
+            is_early_leave: false,
+            is_overtime_break: false,
+            overtime_minutes: None,
+            overtime_category: None,
+            is_manual: true,
+            created_at: String::new(),
+        };
+        let mut pair_index = None;
+
+        match action_type.action_role {
+            1 => {
+                if state.main_start.is_some() {
+                    plan.errors.push(error("存在未完成的上班记录，请先打下班卡"));
+                    continue;
+                }
+                check_in.is_late = rule
+                    .and_then(|r| r.expected_start_time.as_deref())
+                    .is_some_and(|expected| is_late_strict(&time, expected, tz));
+                state.main_start = Some(index);
+            }
+            2 => {
+                let Some(start_index) = state.main_start else {
+                    plan.errors.push(error("未找到进行中的上班记录"));
+                    continue;
+                };
+                if state.break_start.is_some() {
+                    plan.errors.push(error("临时事件未结束，请先打回座"));
+                    continue;
+                }
+                let start = &plan.records[start_index].check_in;
+                let Some(start_time) = parse_time(&start.check_time, tz) else {
+                    continue;
+                };
+                let duration = calculate_duration(&start_time, &time);
+
+                // 班次内已完成的临时事件
+                let shift_records: Vec<CheckIn> = plan.records[start_index..]
+                    .iter()
+                    .map(|p| p.check_in.clone())
+                    .collect();
+                let (_, unpaid) = break_minutes(&shift_records, reference.action_types);
+                let net_minutes = net_work_minutes(duration, unpaid);
+
+                check_in.status = "completed".to_string();
+                check_in.duration_minutes = Some(duration);
+                check_in.net_minutes = Some(net_minutes);
+                check_in.is_early_leave = rule
+                    .and_then(|r| r.expected_end_time.as_deref())
+                    .is_some_and(|expected| is_early_leave(&start_time, &time, expected, tz));
+
+                let schedule = employee_schedule(reference.rules, reference.action_types, reference.departments, user);
+                let rest_days = schedule.and_then(|r| r.rest_days.clone()).unwrap_or_default();
+                let day_kind = classify_day(start_time.date_naive(), &rest_days, reference.holidays);
+                if let Some((minutes, category)) = calculate_overtime(
+                    &start_time,
+                    &time,
+                    schedule.and_then(|r| r.expected_start_time.as_deref()),
+                    schedule.and_then(|r| r.expected_end_time.as_deref()),
+                    net_minutes,
+                    day_kind,
+                    reference.overtime_settings,
+                ) {
+                    check_in.overtime_minutes = Some(minutes);
+                    check_in.overtime_category = Some(category);
+                }
+
+                let start = &mut plan.records[start_index];
+                start.check_in.status = "completed".to_string();
+                start.check_in.duration_minutes = Some(duration);
+                start.pair_index = Some(index);
+                pair_index = Some(start_index);
+                state.main_start = None;
+            }
+            3 => {
+                if state.main_start.is_none() {
+                    plan.errors.push(error("请先打上班卡再进行其他操作"));
+                    continue;
+                }
+                if state.break_start.is_some() {
+                    plan.errors.push(error("存在未完成的临时事件，请先打回座"));
+                    continue;
+                }
+                state.break_start = Some(index);
+            }
+            4 => {
+                let Some(start_index) = state.break_start else {
+                    plan.errors.push(error("未找到对应的临时事件开始记录"));
+                    continue;
+                };
+                let start = &plan.records[start_index].check_in;
+                let Some(start_time) = parse_time(&start.check_time, tz) else {
+                    continue;
+                };
+                let duration = calculate_duration(&start_time, &time);
+
+                let is_overtime = match rule_of(start.action_type_id, user) {
+                    Some(break_rule) => {
+                        let same_day: Vec<CheckIn> = plan
+                            .records
+                            .iter()
+                            .map(|p| &p.check_in)
+                            .filter(|c| c.user_id == user.id && c.check_time.get(..10) == start.check_time.get(..10))
+                            .cloned()
+                            .collect();
+                        let usage = summarize_break_usage(&same_day, start.action_type_id, Some(start.id));
+                        is_overtime_break(break_rule, &usage, duration)
+                    }
+                    None => false,
+                };
+
+                check_in.status = "completed".to_string();
+                check_in.duration_minutes = Some(duration);
+                check_in.is_overtime_break = is_overtime;
+
+                let start = &mut plan.records[start_index];
+                start.check_in.status = "completed".to_string();
+                start.check_in.duration_minutes = Some(duration);
+                start.check_in.is_overtime_break = is_overtime;
+                start.pair_index = Some(index);
+                pair_index = Some(start_index);
+                state.break_start = None;
+            }
+            _ => {
+                plan.errors.push(error("未知的打卡类型"));
+                continue;
+            }
+        }
+
+        plan.records.push(PlannedCheckIn {
+            line: row.line,
+            username: row.username.clone(),
+            check_in,
+            pair_index,
+        });
+    }
+
+    drop_unpaired_starts(&mut plan, reference.action_types);
+    plan.errors.sort_by_key(|e| e.line);
+    plan
+}
+
+/// Split the records into batches of about `batch_size`, never separating a start from
+/// its end, so each batch can be inserted and paired in one transaction
+pub fn import_batches(records: &[PlannedCheckIn], batch_size: usize) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut batch_start = 0;
+    // 已读记录中配对对象的最大下标，超过当前位置说明有一对记录跨在边界上
+    let mut reach = 0;
+    for (index, record) in records.iter().enumerate() {
+        reach = reach.max(record.pair_index.unwrap_or(index));
+        if reach <= index && index + 1 - batch_start >= batch_size.max(1) {
+            batches.push(batch_start..index + 1);
+            batch_start = index + 1;
+        }
+    }
+    if batch_start < records.len() {
+        batches.push(batch_start..records.len());
+    }
+    batches
+}
+
+/// Remove starts that never got an end, together with the breaks recorded inside
+/// a shift that was never clocked out, and re-point the pair indices
+fn drop_unpaired_starts(plan: &mut ImportPlan, action_types: &[ActionType]) {
+    let records = std::mem::take(&mut plan.records);
+    let mut new_index = vec![None; records.len()];
+    let mut kept = Vec::with_capacity(records.len());
+
+    // 记录按员工和时间排序，未下班的上班记录之后该员工的记录都在这个班次内
+    let mut open_shifts: HashMap<i32, usize> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        let is_main_start = action_types
+            .iter()
+            .any(|at| at.id == record.check_in.action_type_id && at.action_role == 1);
+        if is_main_start && record.check_in.status == "ongoing" {
+            open_shifts.entry(record.check_in.user_id).or_insert(index);
+        }
+    }
+
+    for (old, record) in records.into_iter().enumerate() {
+        let message = if record.check_in.status == "ongoing" {
+            "缺少对应的结束记录"
+        } else if open_shifts.get(&record.check_in.user_id).is_some_and(|start| old > *start) {
+            "所在班次缺少下班记录"
+        } else {
+            new_index[old] = Some(kept.len());
+            kept.push(record);
+            continue;
+        };
+        plan.errors.push(ImportRowError {
+            line: record.line,
+            username: record.username.clone(),
+            message: message.to_string(),
+        });
+    }
+
+    for record in kept.iter_mut() {
+        record.pair_index = record.pair_index.and_then(|old| new_index[old]);
+    }
+    plan.records = kept;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OvertimeCategory;
+
+    const TZ: &str = "Asia/Phnom_Penh";
+
+    fn action_type(id: i32, name: &str, role: i32) -> ActionType {
+        ActionType {
+            id,
+            name: name.to_string(),
+            button_text: name.to_string(),
+            button_color: "#000000".to_string(),
+            display_order: id,
+            action_role: role,
+            requires_pair: true,
+            pair_action_id: None,
+            is_paid_break: false,
+            is_active: true,
+            archived_at: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn schedule() -> TimeRule {
+        TimeRule {
+            id: 1,
+            rule_name: "标准班".to_string(),
+            action_type_id: 1,
+            expected_start_time: Some("09:00:00".to_string()),
+            expected_end_time: Some("18:00:00".to_string()),
+            max_duration_minutes: None,
+            daily_max_total_minutes: None,
+            daily_max_count: None,
+            rest_days: Some(vec![6, 7]),
+            timezone: TZ.to_string(),
+            is_active: true,
+            archived_at: None,
+            department_id: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn plan(csv: &str) -> ImportPlan {
+        let users: Vec<User> = ["alice", "bob"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                serde_json::from_value(json!({
+                    "id": i + 1,
+                    "username": name,
+                    "password_hash": "",
+                    "full_name": name,
+                    "is_admin": false,
+                    "created_at": "",
+                }))
+                .unwrap()
+            })
+            .collect();
+        let action_types = vec![
+            action_type(1, "上班", 1),
+            action_type(2, "下班", 2),
+            action_type(3, "午餐", 3),
+            action_type(4, "回座", 4),
+        ];
+        let (rows, errors) = parse_import_csv(csv).unwrap();
+        assert!(errors.is_empty());
+        plan_import(
+            rows,
+            &ImportReference {
+                users: &users,
+                action_types: &action_types,
+                rules: &[schedule()],
+                departments: &[],
+                holidays: &[],
+                overtime_settings: &OvertimeSettings::default(),
+                existing: &[],
+                timezone: TZ,
+            },
+        )
+    }
+
+    fn messages(plan: &ImportPlan) -> Vec<(usize, &str)> {
+        plan.errors.iter().map(|e| (e.line, e.message.as_str())).collect()
+    }
+
+    #[test]
+    fn normal_day_is_paired_with_lateness_and_overtime() {
+        // 2026-10-12 周一
+        let plan = plan(
+            "username,action,timestamp\n\
+             alice,上班,2026-10-12 09:10:00\n\
+             alice,下班,2026-10-12 18:30:00\n",
+        );
+        assert!(plan.errors.is_empty());
+        let [start, end] = &plan.records[..] else { panic!("expected two records") };
+        assert_eq!((start.pair_index, end.pair_index), (Some(1), Some(0)));
+        assert!(start.check_in.is_late);
+        assert_eq!(start.check_in.status, "completed");
+        assert_eq!(end.check_in.duration_minutes, Some(560));
+        assert_eq!(end.check_in.overtime_minutes, Some(30));
+        assert_eq!(end.check_in.overtime_category, Some(OvertimeCategory::Workday));
+    }
+
+    #[test]
+    fn unpaired_rows_are_reported_and_dropped() {
+        let plan = plan(
+            "username,action,timestamp\n\
+             bob,下班,2026-10-12 18:00\n\
+             alice,上班,2026-10-12 09:00\n\
+             alice,午餐,2026-10-12 12:00\n\
+             alice,回座,2026-10-12 12:30\n\
+             bob,回座,2026-10-12 19:00\n",
+        );
+        // 未下班班次内已配对的临时事件没有所属班次，同样不导入
+        assert!(plan.records.is_empty());
+        assert_eq!(
+            messages(&plan),
+            vec![
+                (2, "未找到进行中的上班记录"),
+                (3, "缺少对应的结束记录"),
+                (4, "所在班次缺少下班记录"),
+                (5, "所在班次缺少下班记录"),
+                (6, "未找到对应的临时事件开始记录"),
+            ]
+        );
+    }
+
+    #[test]
+    fn break_nests_inside_shift() {
+        let plan = plan(
+            "username,action,timestamp\n\
+             alice,上班,2026-10-12 09:00\n\
+             alice,午餐,2026-10-12 12:00\n\
+             alice,午餐,2026-10-12 12:10\n\
+             alice,回座,2026-10-12 12:45\n\
+             alice,下班,2026-10-12 18:00\n",
+        );
+        // 临时事件未结束时不能再开始另一个
+        assert_eq!(messages(&plan), vec![(4, "存在未完成的临时事件，请先打回座")]);
+        let pairs: Vec<_> = plan.records.iter().map(|r| r.pair_index).collect();
+        assert_eq!(pairs, vec![Some(3), Some(2), Some(1), Some(0)]);
+        assert_eq!(plan.records[3].check_in.duration_minutes, Some(540));
+        // 不带薪的午餐从净工时中扣除
+        assert_eq!(plan.records[3].check_in.net_minutes, Some(495));
+    }
+
+    #[test]
+    fn batches_keep_pairs_together() {
+        let plan = plan(
+            "username,action,timestamp\n\
+             alice,上班,2026-10-12 09:00\n\
+             alice,午餐,2026-10-12 12:00\n\
+             alice,回座,2026-10-12 12:45\n\
+             alice,下班,2026-10-12 18:00\n\
+             alice,上班,2026-10-13 09:00\n\
+             alice,下班,2026-10-13 18:00\n\
+             bob,上班,2026-10-12 09:00\n\
+             bob,下班,2026-10-12 18:00\n",
+        );
+        assert_eq!(plan.records.len(), 8);
+
+        // 第一天的上下班跨过 2 条和 3 条的边界，整天放在同一批次
+        assert_eq!(import_batches(&plan.records, 3), vec![0..4, 4..8]);
+        assert_eq!(import_batches(&plan.records, 2), vec![0..4, 4..6, 6..8]);
+        assert_eq!(import_batches(&plan.records, 5), vec![0..6, 6..8]);
+        assert_eq!(import_batches(&plan.records, 1000), vec![0..8]);
+        for batch in import_batches(&plan.records, 3) {
+            assert!(plan.records[batch.clone()]
+                .iter()
+                .all(|r| r.pair_index.is_some_and(|pair| batch.contains(&pair))));
+        }
+    }
+}
@@ -1,3 +1,18 @@
 // Utilities module
 pub mod crypto;
 pub mod time;
+pub mod quota;
+pub mod overtime;
+pub mod worktime;
+pub mod attendance;
+pub mod import;
+pub mod provisioning;
+pub mod config;
+pub mod company_config;
+pub mod department;
+pub mod permissions;
+pub mod profile;
+pub mod password;
+pub mod throttle;
+pub mod totp;
+pub mod registration;
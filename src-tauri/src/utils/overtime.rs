@@ -0,0 +1,170 @@
+use chrono::{DateTime, Datelike, NaiveDate};
+use chrono_tz::Tz;
+use serde::Serialize;
+use crate::models::{Holiday, OvertimeCategory, OvertimeSettings, RoundingMode};
+use crate::utils::time::expected_end_datetime;
+
+/// Kind of a calendar day for attendance purposes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DayKind {
+    Workday,
+    RestDay,
+    Holiday,
+}
+
+/// Classify a date using the weekly rest days of the schedule and the holiday calendar.
+/// A holiday entry marked `is_workday` turns a rest day into a working day (调休).
+pub fn classify_day(date: NaiveDate, rest_days: &[i32], holidays: &[Holiday]) -> DayKind {
+    let date_str = date.format("%Y-%m-%d").to_string();
+    if let Some(holiday) = holidays.iter().find(|h| h.holiday_date.starts_with(&date_str)) {
+        return if holiday.is_workday {
+            DayKind::Workday
+        } else {
+            DayKind::Holiday
+        };
+    }
+
+    let weekday = date.weekday().number_from_monday() as i32;
+    if rest_days.contains(&weekday) {
+        DayKind::RestDay
+    } else {
+        DayKind::Workday
+    }
+}
+
+/// Apply the minimum threshold and rounding rules to raw overtime minutes
+pub fn round_overtime(minutes: i32, settings: &OvertimeSettings) -> i32 {
+    if minutes <= 0 || minutes < settings.min_minutes {
+        return 0;
+    }
+
+    let block = settings.rounding_minutes.max(1);
+    match settings.rounding_mode {
+        RoundingMode::Floor => minutes / block * block,
+        RoundingMode::Nearest => (minutes + block / 2) / block * block,
+        RoundingMode::Ceil => (minutes + block - 1) / block * block,
+    }
+}
+
+/// Calculate the overtime of a completed shift.
+///
+/// On workdays only the time after the expected end counts; cross-day schedules
+/// (expected end not after expected start) end on the day after the shift starts.
+/// On rest days and holidays all worked minutes count.
+pub fn calculate_overtime(
+    start_time: &DateTime<Tz>,
+    end_time: &DateTime<Tz>,
+    expected_start_time: Option<&str>,
+    expected_end_time: Option<&str>,
+    worked_minutes: i32,
+    day_kind: DayKind,
+    settings: &OvertimeSettings,
+) -> Option<(i32, OvertimeCategory)> {
+    let (raw_minutes, category) = match day_kind {
+        DayKind::RestDay => (worked_minutes, OvertimeCategory::RestDay),
+        DayKind::Holiday => (worked_minutes, OvertimeCategory::Holiday),
+        DayKind::Workday => {
+            let expected_end_dt = expected_end_datetime(
+                start_time.date_naive(),
+                expected_start_time,
+                expected_end_time?,
+                start_time.timezone(),
+            )?;
+
+            let extra = end_time.signed_duration_since(expected_end_dt).num_minutes() as i32;
+            (extra.min(worked_minutes), OvertimeCategory::Workday)
+        }
+    };
+
+    let minutes = round_overtime(raw_minutes, settings);
+    if minutes > 0 {
+        Some((minutes, category))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const TZ: Tz = chrono_tz::Asia::Phnom_Penh;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+        TZ.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn holiday(date: &str, is_workday: bool) -> Holiday {
+        Holiday {
+            id: 1,
+            holiday_date: date.to_string(),
+            name: "国庆".to_string(),
+            is_workday,
+            created_at: String::new(),
+        }
+    }
+
+    fn settings(rounding_minutes: i32, rounding_mode: RoundingMode, min_minutes: i32) -> OvertimeSettings {
+        OvertimeSettings { rounding_minutes, rounding_mode, min_minutes }
+    }
+
+    #[test]
+    fn rounding_and_threshold() {
+        let floor = settings(30, RoundingMode::Floor, 30);
+        assert_eq!(round_overtime(29, &floor), 0);
+        assert_eq!(round_overtime(30, &floor), 30);
+        assert_eq!(round_overtime(59, &floor), 30);
+
+        let nearest = settings(30, RoundingMode::Nearest, 0);
+        assert_eq!(round_overtime(44, &nearest), 30);
+        assert_eq!(round_overtime(45, &nearest), 60);
+
+        let ceil = settings(15, RoundingMode::Ceil, 0);
+        assert_eq!(round_overtime(1, &ceil), 15);
+        assert_eq!(round_overtime(15, &ceil), 15);
+        assert_eq!(round_overtime(0, &ceil), 0);
+        assert_eq!(round_overtime(-5, &ceil), 0);
+    }
+
+    #[test]
+    fn classify_rest_days_and_holidays() {
+        let rest_days = [6, 7];
+        // 2026-10-17 周六，2026-10-19 周一
+        let saturday = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        assert_eq!(classify_day(monday, &rest_days, &[]), DayKind::Workday);
+        assert_eq!(classify_day(saturday, &rest_days, &[]), DayKind::RestDay);
+
+        let holidays = [holiday("2026-10-19", false), holiday("2026-10-17", true)];
+        assert_eq!(classify_day(monday, &rest_days, &holidays), DayKind::Holiday);
+        // 调休上班日
+        assert_eq!(classify_day(saturday, &rest_days, &holidays), DayKind::Workday);
+    }
+
+    #[test]
+    fn overtime_after_expected_end() {
+        let floor = settings(30, RoundingMode::Floor, 30);
+        let start = at(2026, 10, 19, 9, 0);
+
+        let result = calculate_overtime(&start, &at(2026, 10, 19, 19, 10), Some("09:00:00"), Some("18:00:00"), 550, DayKind::Workday, &floor);
+        assert_eq!(result, Some((60, OvertimeCategory::Workday)));
+        // 不足起算时长
+        let result = calculate_overtime(&start, &at(2026, 10, 19, 18, 20), Some("09:00:00"), Some("18:00:00"), 500, DayKind::Workday, &floor);
+        assert_eq!(result, None);
+        // 没有下班时间的排班不计工作日加班
+        assert_eq!(calculate_overtime(&start, &at(2026, 10, 19, 20, 0), Some("09:00:00"), None, 600, DayKind::Workday, &floor), None);
+
+        // 跨天班次的下班时间在次日
+        let night = at(2026, 10, 19, 22, 0);
+        let result = calculate_overtime(&night, &at(2026, 10, 20, 7, 0), Some("22:00:00"), Some("06:00:00"), 540, DayKind::Workday, &floor);
+        assert_eq!(result, Some((60, OvertimeCategory::Workday)));
+
+        // 休息日全部工时计为加班
+        let result = calculate_overtime(&start, &at(2026, 10, 19, 13, 0), Some("09:00:00"), Some("18:00:00"), 225, DayKind::RestDay, &floor);
+        assert_eq!(result, Some((210, OvertimeCategory::RestDay)));
+        let result = calculate_overtime(&start, &at(2026, 10, 19, 13, 0), Some("09:00:00"), Some("18:00:00"), 240, DayKind::Holiday, &floor);
+        assert_eq!(result, Some((240, OvertimeCategory::Holiday)));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::models::PasswordPolicy;
+use chrono::{DateTime, Duration, Utc};
+
+/// Check a password chosen by a user against the policy; every unmet requirement is listed.
+/// Generated initial and temporary passwords are not checked, they must be changed at first login.
+pub fn check_password(policy: &PasswordPolicy, password: &str, username: &str) -> Result<(), String> {
+    let mut problems = Vec::new();
+    if password.chars().count() < policy.min_length {
+        problems.push(format!("至少 {} 个字符", policy.min_length));
+    }
+    if policy.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+        problems.push("包含大写字母".to_string());
+    }
+    if policy.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+        problems.push("包含小写字母".to_string());
+    }
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        problems.push("包含数字".to_string());
+    }
+    if policy.require_symbol && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+        problems.push("包含符号".to_string());
+    }
+    if !username.is_empty() && password.to_lowercase().contains(&username.to_lowercase()) {
+        problems.push("不包含用户名".to_string());
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("密码不符合要求：须{}", problems.join("、")))
+    }
+}
+
+/// Whether the password is older than the policy allows; passwords never expire when the limit is 0
+pub fn password_expired(policy: &PasswordPolicy, changed_at: Option<&str>, now: DateTime<Utc>) -> bool {
+    if policy.max_age_days == 0 {
+        return false;
+    }
+    match changed_at.and_then(|t| DateTime::parse_from_rfc3339(t).ok()) {
+        Some(changed_at) => now - changed_at.with_timezone(&Utc) > Duration::days(policy.max_age_days),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_lists_every_unmet_requirement() {
+        let policy = PasswordPolicy {
+            min_length: 10,
+            require_uppercase: true,
+            require_symbol: true,
+            max_age_days: 90,
+            ..PasswordPolicy::default()
+        };
+
+        let error = check_password(&policy, "lilei2026", "lilei").unwrap_err();
+        assert_eq!(error, "密码不符合要求：须至少 10 个字符、包含大写字母、包含符号、不包含用户名");
+        assert!(check_password(&policy, "Spring#2026x", "lilei").is_ok());
+
+        let now = DateTime::parse_from_rfc3339("2026-10-19T00:00:00Z").unwrap().with_timezone(&Utc);
+        assert!(password_expired(&policy, Some("2026-07-01T00:00:00+00:00"), now));
+        assert!(!password_expired(&policy, Some("2026-09-01T00:00:00+00:00"), now));
+        assert!(!password_expired(&PasswordPolicy::default(), Some("2020-01-01T00:00:00+00:00"), now));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::models::Role;
+
+/// Actions that are not tied to one employee's records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// 查看和修改全部员工的考勤记录（补卡、改卡、请假、导入）
+    EditAllAttendance,
+    /// 员工档案：工号、入职/离职日期、联系方式和默认排班
+    EditProfiles,
+    /// 用户账号、角色和部门成员
+    ManageUsers,
+    /// 打卡类型、时间规则、部门、节假日和系统配置
+    ManageConfiguration,
+    ViewAuditLog,
+    ManageBackups,
+}
+
+pub fn role_allows(role: Role, permission: Permission) -> bool {
+    match permission {
+        Permission::EditAllAttendance | Permission::EditProfiles => matches!(role, Role::Hr | Role::SuperAdmin),
+        Permission::ManageUsers
+        | Permission::ManageConfiguration
+        | Permission::ViewAuditLog
+        | Permission::ManageBackups => role == Role::SuperAdmin,
+    }
+}
+
+/// Employees whose attendance records a user may see or change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttendanceScope {
+    All,
+    Users(Vec<i32>),
+}
+
+impl AttendanceScope {
+    /// Scope for viewing: managers see their team and themselves
+    pub fn view(role: Role, user_id: i32, team: Vec<i32>) -> Self {
+        match role {
+            Role::Hr | Role::SuperAdmin => Self::All,
+            Role::Manager => {
+                let mut ids = team;
+                if !ids.contains(&user_id) {
+                    ids.push(user_id);
+                }
+                Self::Users(ids)
+            }
+            Role::Employee => Self::Users(vec![user_id]),
+        }
+    }
+
+    /// Scope for corrections and approvals: managers cannot approve their own records
+    pub fn edit(role: Role, user_id: i32, team: Vec<i32>) -> Self {
+        match role {
+            Role::Hr | Role::SuperAdmin => Self::All,
+            Role::Manager => Self::Users(team.into_iter().filter(|id| *id != user_id).collect()),
+            Role::Employee => Self::Users(Vec::new()),
+        }
+    }
+
+    pub fn allows(&self, user_id: i32) -> bool {
+        match self {
+            Self::All => true,
+            Self::Users(ids) => ids.contains(&user_id),
+        }
+    }
+
+    /// PostgREST filter on `user_id`; `None` when every employee is in scope
+    pub fn user_filter(&self) -> Option<String> {
+        match self {
+            Self::All => None,
+            Self::Users(ids) => Some(format!(
+                "in.({})",
+                ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manager_sees_team_but_approves_only_others() {
+        let view = AttendanceScope::view(Role::Manager, 1, vec![2, 3]);
+        let edit = AttendanceScope::edit(Role::Manager, 1, vec![1, 2, 3]);
+
+        assert!(view.allows(1) && view.allows(3) && !view.allows(4));
+        assert!(!edit.allows(1) && edit.allows(2));
+        assert_eq!(AttendanceScope::edit(Role::Employee, 1, vec![]).user_filter(), Some("in.()".to_string()));
+        assert_eq!(AttendanceScope::view(Role::Hr, 1, vec![]), AttendanceScope::All);
+        assert!(role_allows(Role::Hr, Permission::EditAllAttendance));
+        assert!(!role_allows(Role::Hr, Permission::ManageConfiguration));
+    }
+}
@@ -0,0 +1,102 @@
+use crate::utils::time::parse_date;
+use chrono::{DateTime, NaiveDate};
+use chrono_tz::Tz;
+
+const MAX_EMPLOYEE_NO_LENGTH: usize = 50;
+
+/// Trimmed text, or `None` when it is blank
+pub fn normalize_text(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+pub fn validate_employee_no(employee_no: &str) -> Result<(), String> {
+    if employee_no.chars().count() > MAX_EMPLOYEE_NO_LENGTH {
+        return Err(format!("工号不能超过 {} 个字符", MAX_EMPLOYEE_NO_LENGTH));
+    }
+    if employee_no.chars().any(char::is_whitespace) {
+        return Err("工号不能包含空格".to_string());
+    }
+    Ok(())
+}
+
+pub fn validate_email(email: &str) -> Result<(), String> {
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(format!("邮箱格式不正确: {}", email));
+    }
+    Ok(())
+}
+
+/// Digits with optional `+`, spaces, dashes and parentheses
+pub fn validate_phone(phone: &str) -> Result<(), String> {
+    let allowed = phone
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | ' ' | '(' | ')'));
+    let digits = phone.chars().filter(char::is_ascii_digit).count();
+    if !allowed || !(6..=20).contains(&digits) {
+        return Err(format!("电话号码格式不正确: {}", phone));
+    }
+    Ok(())
+}
+
+/// Both dates must be `YYYY-MM-DD` and the termination date cannot precede the hire date
+pub fn validate_employment_dates(hire_date: Option<&str>, termination_date: Option<&str>) -> Result<(), String> {
+    let hired_on = hire_date
+        .map(|d| parse_date(d).ok_or_else(|| "入职日期格式错误，请使用 YYYY-MM-DD".to_string()))
+        .transpose()?;
+    let terminated_on = termination_date
+        .map(|d| parse_date(d).ok_or_else(|| "离职日期格式错误，请使用 YYYY-MM-DD".to_string()))
+        .transpose()?;
+    if let (Some(hired_on), Some(terminated_on)) = (hired_on, terminated_on) {
+        if terminated_on < hired_on {
+            return Err("离职日期不能早于入职日期".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Last day of employment: the termination date or the company-time date of the
+/// deactivation, whichever comes first
+pub fn employment_end(termination_date: Option<&str>, deactivated_at: Option<&str>, timezone: &str) -> Option<NaiveDate> {
+    let tz: Tz = timezone.parse().ok()?;
+    let deactivated_on = deactivated_at.and_then(|t| {
+        DateTime::parse_from_rfc3339(t)
+            .map(|t| t.with_timezone(&tz).date_naive())
+            .ok()
+            .or_else(|| parse_date(t))
+    });
+    termination_date.and_then(parse_date).into_iter().chain(deactivated_on).min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_contact_fields_and_dates() {
+        assert!(validate_email("li.lei@example.com").is_ok());
+        assert!(validate_email("li.lei@example").is_err());
+        assert!(validate_phone("+855 12-345 678").is_ok());
+        assert!(validate_phone("12a456").is_err());
+        assert!(validate_employment_dates(Some("2026-03-01"), Some("2026-02-28")).is_err());
+        assert!(validate_employment_dates(Some("2026-03-01"), None).is_ok());
+        assert_eq!(normalize_text(Some("  ".to_string())), None);
+
+        // 停用时间按公司时区换算为日期，与离职日期取较早者
+        let tz = "Asia/Phnom_Penh";
+        let date = |d: &str| parse_date(d);
+        assert_eq!(employment_end(None, Some("2026-10-14T18:30:00+00:00"), tz), date("2026-10-15"));
+        assert_eq!(employment_end(Some("2026-10-10"), Some("2026-10-14T08:00:00+00:00"), tz), date("2026-10-10"));
+        assert_eq!(employment_end(Some("2026-10-31"), Some("2026-10-14T08:00:00+00:00"), tz), date("2026-10-14"));
+        assert_eq!(employment_end(None, None, tz), None);
+    }
+}
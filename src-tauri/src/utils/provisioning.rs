@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use std::collections::HashSet;
+use crate::models::User;
+use crate::utils::import::{CsvSheet, ImportRowError, USERNAME_HEADERS};
+
+const FULL_NAME_HEADERS: &[&str] = &["full_name", "name", "姓名"];
+const IS_ADMIN_HEADERS: &[&str] = &["is_admin", "admin", "管理员"];
+
+const MAX_USERNAME_LENGTH: usize = 50;
+
+/// One account to create; `line` is the CSV line or the 1-based position in the JSON array
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProvisionRow {
+    #[serde(default)]
+    pub line: usize,
+    pub username: String,
+    pub full_name: String,
+    #[serde(default)]
+    pub is_admin: bool,
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "" | "false" | "0" | "no" | "n" | "否" => Some(false),
+        "true" | "1" | "yes" | "y" | "是" => Some(true),
+        _ => None,
+    }
+}
+
+/// Read the CSV rows of an account list
+pub fn parse_provision_csv(content: &str) -> Result<(Vec<ProvisionRow>, Vec<ImportRowError>), String> {
+    let sheet = CsvSheet::parse(content)?;
+    let username_col = sheet.required_column(USERNAME_HEADERS)?;
+    let full_name_col = sheet.required_column(FULL_NAME_HEADERS)?;
+    let is_admin_col = sheet.column(IS_ADMIN_HEADERS);
+
+    let mut rows = Vec::new();
+    let mut errors = sheet.errors;
+    for row in &sheet.rows {
+        let is_admin = match is_admin_col.map(|col| row.field(col)) {
+            Some(value) => match parse_bool(&value) {
+                Some(is_admin) => is_admin,
+                None => {
+                    errors.push(ImportRowError {
+                        line: row.line,
+                        username: row.field(username_col),
+                        message: format!("无法识别的管理员标记: {}", value),
+                    });
+                    continue;
+                }
+            },
+            None => false,
+        };
+        rows.push(ProvisionRow {
+            line: row.line,
+            username: row.field(username_col),
+            full_name: row.field(full_name_col),
+            is_admin,
+        });
+    }
+
+    Ok((rows, errors))
+}
+
+/// Read a JSON array of `{username, full_name, is_admin?}` objects
+pub fn parse_provision_json(content: &str) -> Result<Vec<ProvisionRow>, String> {
+    let mut rows: Vec<ProvisionRow> =
+        serde_json::from_str(content).map_err(|e| format!("无法解析 JSON: {}", e))?;
+    for (index, row) in rows.iter_mut().enumerate() {
+        row.line = index + 1;
+        row.username = row.username.trim().to_string();
+        row.full_name = row.full_name.trim().to_string();
+    }
+    Ok(rows)
+}
+
+/// Keep the rows that can be created: valid usernames that are neither taken
+/// nor repeated earlier in the file (compared case-insensitively)
+pub fn validate_provision_rows(
+    rows: Vec<ProvisionRow>,
+    existing_users: &[User],
+) -> (Vec<ProvisionRow>, Vec<ImportRowError>) {
+    let taken: HashSet<String> = existing_users.iter().map(|u| u.username.to_lowercase()).collect();
+    let mut seen = HashSet::new();
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+
+    for row in rows {
+        let message = if row.username.is_empty() {
+            Some("用户名不能为空".to_string())
+        } else if row.username.chars().any(char::is_whitespace) {
+            Some("用户名不能包含空格".to_string())
+        } else if row.username.chars().count() > MAX_USERNAME_LENGTH {
+            Some(format!("用户名不能超过 {} 个字符", MAX_USERNAME_LENGTH))
+        } else if row.full_name.is_empty() {
+            Some("姓名不能为空".to_string())
+        } else if taken.contains(&row.username.to_lowercase()) {
+            Some("用户名已存在".to_string())
+        } else if !seen.insert(row.username.to_lowercase()) {
+            Some("用户名在文件中重复".to_string())
+        } else {
+            None
+        };
+
+        match message {
+            Some(message) => errors.push(ImportRowError {
+                line: row.line,
+                username: row.username,
+                message,
+            }),
+            None => valid.push(row),
+        }
+    }
+
+    (valid, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_columns_follow_header_names() {
+        let csv = "\u{feff}管理员,姓名,用户名\n\
+                   是,王芳,wangfang\n\
+                   ,,\n\
+                   maybe,李雷,lilei\n\
+                   ,韩梅梅,hanmeimei\n";
+        let (rows, errors) = parse_provision_csv(csv).unwrap();
+        let parsed: Vec<_> = rows.iter().map(|r| (r.line, r.username.as_str(), r.full_name.as_str(), r.is_admin)).collect();
+        assert_eq!(parsed, vec![(2, "wangfang", "王芳", true), (5, "hanmeimei", "韩梅梅", false)]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].username.as_str()), (4, "lilei"));
+
+        assert_eq!(parse_provision_csv("username,admin\nli,1\n").unwrap_err(), "缺少列: full_name");
+    }
+}
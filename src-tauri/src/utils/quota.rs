@@ -0,0 +1,169 @@
+use crate::models::{CheckIn, TimeRule};
+
+/// Break usage of one temporary event type within a single day
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BreakUsage {
+    pub count: i32,
+    pub total_minutes: i32,
+}
+
+/// Summarize the breaks (temporary event start records) of an action type,
+/// skipping the record given in `exclude_id`
+pub fn summarize_break_usage(
+    check_ins: &[CheckIn],
+    action_type_id: i32,
+    exclude_id: Option<i32>,
+) -> BreakUsage {
+    check_ins
+        .iter()
+        .filter(|c| c.action_type_id == action_type_id && Some(c.id) != exclude_id)
+        .fold(BreakUsage::default(), |mut usage, c| {
+            usage.count += 1;
+            usage.total_minutes += c.duration_minutes.unwrap_or(0).max(0);
+            usage
+        })
+}
+
+/// Check whether a break of `duration` minutes goes beyond the single-break limit
+/// or the daily allowance, given the usage of the same day before this break
+pub fn is_overtime_break(rule: &TimeRule, usage_before: &BreakUsage, duration: i32) -> bool {
+    if let Some(max_duration) = rule.max_duration_minutes {
+        if duration > max_duration {
+            return true;
+        }
+    }
+    if let Some(max_count) = rule.daily_max_count {
+        if usage_before.count + 1 > max_count {
+            return true;
+        }
+    }
+    if let Some(max_total) = rule.daily_max_total_minutes {
+        if usage_before.total_minutes + duration > max_total {
+            return true;
+        }
+    }
+    false
+}
+
+/// Warnings shown when an employee starts a break, given the usage of the day so far
+pub fn break_quota_warnings(rule: &TimeRule, event_name: &str, usage_before: &BreakUsage) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if let Some(max_count) = rule.daily_max_count {
+        if usage_before.count >= max_count {
+            warnings.push(format!(
+                "今日{}已达{}次上限，本次将计为超额",
+                event_name, max_count
+            ));
+        }
+    }
+
+    if let Some(max_total) = rule.daily_max_total_minutes {
+        let remaining = max_total - usage_before.total_minutes;
+        if remaining <= 0 {
+            warnings.push(format!(
+                "今日{}累计时长已达{}分钟上限，本次将计为超额",
+                event_name, max_total
+            ));
+        } else if rule.max_duration_minutes.is_some_and(|max| remaining < max) {
+            // 剩余额度不足一次的最大时长时提醒
+            warnings.push(format!(
+                "今日{}剩余额度{}分钟，超出部分将计为超额",
+                event_name, remaining
+            ));
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn break_rule(max_duration: Option<i32>, max_total: Option<i32>, max_count: Option<i32>) -> TimeRule {
+        TimeRule {
+            id: 1,
+            rule_name: "午餐".to_string(),
+            action_type_id: 3,
+            expected_start_time: None,
+            expected_end_time: None,
+            max_duration_minutes: max_duration,
+            daily_max_total_minutes: max_total,
+            daily_max_count: max_count,
+            rest_days: None,
+            timezone: "Asia/Phnom_Penh".to_string(),
+            is_active: true,
+            archived_at: None,
+            department_id: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn break_start(id: i32, action_type_id: i32, duration: Option<i32>) -> CheckIn {
+        CheckIn {
+            id,
+            user_id: 1,
+            action_type_id,
+            check_time: "2026-10-19T12:00:00+07:00".to_string(),
+            status: "completed".to_string(),
+            pair_check_in_id: None,
+            duration_minutes: duration,
+            net_minutes: None,
+            note: None,
+            is_late: false,
+            is_early_leave: false,
+            is_overtime_break: false,
+            overtime_minutes: None,
+            overtime_category: None,
+            is_manual: false,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn usage_skips_other_types_and_excluded_record() {
+        // 进行中的休息没有时长，只计次数
+        let check_ins = vec![
+            break_start(1, 3, Some(20)),
+            break_start(2, 3, Some(15)),
+            break_start(3, 5, Some(40)),
+            break_start(4, 3, None),
+        ];
+        let usage = summarize_break_usage(&check_ins, 3, Some(2));
+        assert_eq!((usage.count, usage.total_minutes), (2, 20));
+    }
+
+    #[test]
+    fn daily_count_limit() {
+        let rule = break_rule(None, None, Some(2));
+        let one = BreakUsage { count: 1, total_minutes: 10 };
+        let two = BreakUsage { count: 2, total_minutes: 20 };
+
+        // 第二次正好达到上限，不算超额；第三次超额
+        assert!(!is_overtime_break(&rule, &one, 10));
+        assert!(is_overtime_break(&rule, &two, 1));
+
+        assert!(break_quota_warnings(&rule, "午餐", &one).is_empty());
+        assert_eq!(break_quota_warnings(&rule, "午餐", &two), vec!["今日午餐已达2次上限，本次将计为超额"]);
+    }
+
+    #[test]
+    fn daily_total_and_single_break_limits() {
+        let rule = break_rule(Some(30), Some(60), None);
+        let used = BreakUsage { count: 2, total_minutes: 40 };
+
+        // 累计正好 60 分钟不超额，多 1 分钟超额
+        assert!(!is_overtime_break(&rule, &used, 20));
+        assert!(is_overtime_break(&rule, &used, 21));
+        // 单次正好 30 分钟不超额
+        assert!(!is_overtime_break(&rule, &BreakUsage::default(), 30));
+        assert!(is_overtime_break(&rule, &BreakUsage::default(), 31));
+
+        assert_eq!(break_quota_warnings(&rule, "午餐", &used), vec!["今日午餐剩余额度20分钟，超出部分将计为超额"]);
+        let remaining_full_break = BreakUsage { count: 1, total_minutes: 30 };
+        assert!(break_quota_warnings(&rule, "午餐", &remaining_full_break).is_empty());
+        let exhausted = BreakUsage { count: 3, total_minutes: 60 };
+        assert_eq!(break_quota_warnings(&rule, "午餐", &exhausted), vec!["今日午餐累计时长已达60分钟上限，本次将计为超额"]);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::models::{InviteCode, RegistrationMode, RegistrationSettings};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+
+/// Invite codes are read out or typed by hand, so look-alike characters are left out
+const INVITE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const INVITE_CODE_LENGTH: usize = 8;
+
+pub fn generate_invite_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..INVITE_CODE_LENGTH)
+        .map(|_| INVITE_ALPHABET[rng.gen_range(0..INVITE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Invite code as stored: uppercase, without spaces or dashes
+pub fn normalize_invite_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn email_domain(email: &str) -> Option<String> {
+    email.rsplit_once('@').map(|(_, domain)| domain.trim().to_lowercase())
+}
+
+/// Check a sign-up against the registration settings; applicants with an
+/// invite code are not held to the email domain allowlist.
+pub fn check_registration(
+    settings: &RegistrationSettings,
+    email: Option<&str>,
+    has_invite: bool,
+) -> Result<(), String> {
+    match settings.mode {
+        RegistrationMode::Closed => return Err("暂不开放注册，请联系管理员创建账号".to_string()),
+        RegistrationMode::InviteOnly if !has_invite => return Err("请填写邀请码".to_string()),
+        _ => {}
+    }
+    if has_invite || settings.email_domains.is_empty() {
+        return Ok(());
+    }
+
+    let domain = email
+        .and_then(email_domain)
+        .ok_or_else(|| "请填写公司邮箱".to_string())?;
+    if !settings.email_domains.contains(&domain) {
+        return Err(format!("只允许使用以下域名的邮箱注册：{}", settings.email_domains.join("、")));
+    }
+    Ok(())
+}
+
+/// Why an invite code can no longer be used
+pub fn invite_unusable_reason(invite: &InviteCode, now: DateTime<Utc>) -> Option<&'static str> {
+    if invite.revoked {
+        return Some("已作废");
+    }
+    if invite.max_uses.is_some_and(|max| invite.use_count >= max) {
+        return Some("已用完");
+    }
+    let expired = invite
+        .expires_at
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .is_some_and(|t| t <= now);
+    expired.then_some("已过期")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn domains_apply_only_without_invite() {
+        let config = HashMap::from([
+            (RegistrationSettings::MODE_KEY.to_string(), "open".to_string()),
+            (RegistrationSettings::EMAIL_DOMAINS_KEY.to_string(), "@Example.com, corp.cn".to_string()),
+        ]);
+        let settings = RegistrationSettings::from_config(&config);
+        assert_eq!(settings.email_domains, vec!["example.com", "corp.cn"]);
+
+        assert!(check_registration(&settings, Some("li.lei@EXAMPLE.com"), false).is_ok());
+        assert!(check_registration(&settings, Some("li.lei@gmail.com"), false).is_err());
+        assert_eq!(check_registration(&settings, None, false).unwrap_err(), "请填写公司邮箱");
+        assert!(check_registration(&settings, None, true).is_ok());
+
+        let invite_only = RegistrationSettings { mode: RegistrationMode::InviteOnly, ..settings.clone() };
+        assert_eq!(check_registration(&invite_only, Some("li.lei@example.com"), false).unwrap_err(), "请填写邀请码");
+        let closed = RegistrationSettings { mode: RegistrationMode::Closed, ..settings };
+        assert!(check_registration(&closed, None, true).is_err());
+
+        let invite = InviteCode {
+            id: 1,
+            code: normalize_invite_code("abcd-2345"),
+            department_id: None,
+            max_uses: Some(1),
+            use_count: 1,
+            expires_at: None,
+            revoked: false,
+            created_by: None,
+            created_at: "2026-10-19T00:00:00+00:00".to_string(),
+        };
+        assert_eq!(invite.code, "ABCD2345");
+        assert_eq!(invite_unusable_reason(&invite, Utc::now()), Some("已用完"));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::models::LoginThrottlePolicy;
+use chrono::{DateTime, Duration, Utc};
+
+/// Longest wait between two attempts before the lockout kicks in
+const MAX_BACKOFF_SECONDS: i64 = 300;
+
+/// Failure count of one username or device, as stored in login_throttles
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThrottleState {
+    pub failed_count: i32,
+    pub last_failed_at: Option<DateTime<Utc>>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+/// Why a login attempt is refused before the password is checked
+#[derive(Debug, PartialEq)]
+pub enum LoginBlock {
+    Locked(DateTime<Utc>),
+    Backoff(DateTime<Utc>),
+}
+
+pub fn parse_timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Wait after the given number of consecutive failures, doubling each time
+fn backoff(policy: &LoginThrottlePolicy, failed_count: i32) -> Duration {
+    if failed_count <= 0 || policy.backoff_seconds == 0 {
+        return Duration::zero();
+    }
+    let factor = 1i64 << (failed_count - 1).min(16);
+    Duration::seconds((policy.backoff_seconds * factor).min(MAX_BACKOFF_SECONDS))
+}
+
+/// Whether a new attempt must wait, and until when
+pub fn login_block(policy: &LoginThrottlePolicy, state: &ThrottleState, now: DateTime<Utc>) -> Option<LoginBlock> {
+    if let Some(locked_until) = state.locked_until.filter(|t| *t > now) {
+        return Some(LoginBlock::Locked(locked_until));
+    }
+    let retry_at = state.last_failed_at? + backoff(policy, state.failed_count);
+    (retry_at > now).then_some(LoginBlock::Backoff(retry_at))
+}
+
+/// State after another failed attempt. Reaching the limit locks the key and
+/// starts a fresh count for when the lockout ends.
+pub fn record_failure(policy: &LoginThrottlePolicy, state: &ThrottleState, now: DateTime<Utc>) -> ThrottleState {
+    // 锁定已过期时重新计数
+    let previous = if state.locked_until.is_some_and(|t| t <= now) { 0 } else { state.failed_count };
+    let failed_count = previous + 1;
+    if failed_count >= policy.max_failures {
+        ThrottleState {
+            failed_count: 0,
+            last_failed_at: Some(now),
+            locked_until: Some(now + Duration::minutes(policy.lockout_minutes)),
+        }
+    } else {
+        ThrottleState {
+            failed_count,
+            last_failed_at: Some(now),
+            locked_until: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_back_off_then_lock() {
+        let policy = LoginThrottlePolicy { max_failures: 3, lockout_minutes: 15, backoff_seconds: 2 };
+        let now = DateTime::parse_from_rfc3339("2026-10-19T08:00:00Z").unwrap().with_timezone(&Utc);
+
+        let first = record_failure(&policy, &ThrottleState::default(), now);
+        assert_eq!(first.failed_count, 1);
+        assert_eq!(login_block(&policy, &first, now + Duration::seconds(1)), Some(LoginBlock::Backoff(now + Duration::seconds(2))));
+        assert_eq!(login_block(&policy, &first, now + Duration::seconds(2)), None);
+
+        let later = now + Duration::seconds(10);
+        let second = record_failure(&policy, &first, later);
+        assert_eq!(login_block(&policy, &second, later + Duration::seconds(3)), Some(LoginBlock::Backoff(later + Duration::seconds(4))));
+
+        let third = record_failure(&policy, &second, later + Duration::seconds(10));
+        let locked_until = later + Duration::seconds(10) + Duration::minutes(15);
+        assert_eq!(third.locked_until, Some(locked_until));
+        assert_eq!(login_block(&policy, &third, locked_until - Duration::seconds(1)), Some(LoginBlock::Locked(locked_until)));
+        assert_eq!(login_block(&policy, &third, locked_until), None);
+
+        // 锁定结束后重新计数
+        assert_eq!(record_failure(&policy, &third, locked_until).failed_count, 1);
+    }
+}
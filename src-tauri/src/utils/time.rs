@@ -1,4 +1,4 @@
-use chrono::{DateTime, NaiveDateTime, NaiveTime, TimeZone, Utc, Datelike, Timelike};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Datelike, Timelike};
 use chrono_tz::Tz;
 
 /// Get current time in company timezone
@@ -38,6 +38,13 @@ pub fn parse_time(time_str: &str, timezone: &str) -> Option<DateTime<Tz>> {
     None
 }
 
+/// Parse a date string (YYYY-MM-DD), ignoring any time part after it
+pub fn parse_date(date_str: &str) -> Option<NaiveDate> {
+    let trimmed = date_str.trim();
+    let date_part = trimmed.get(..10).unwrap_or(trimmed);
+    NaiveDate::parse_from_str(date_part, "%Y-%m-%d").ok()
+}
+
 /// Check if time is late (strict - no grace period)
 pub fn is_late_strict(
     check_time: &DateTime<Tz>,
@@ -88,6 +95,27 @@ pub fn is_early_leave(
     false
 }
 
+/// Expected end of a shift worked on `work_date`.
+/// Cross-day schedules (expected end not after expected start) end on the next day.
+pub fn expected_end_datetime(
+    work_date: NaiveDate,
+    expected_start_time: Option<&str>,
+    expected_end_time: &str,
+    tz: Tz,
+) -> Option<DateTime<Tz>> {
+    let expected_end = NaiveTime::parse_from_str(expected_end_time, "%H:%M:%S").ok()?;
+    let crosses_midnight = expected_start_time
+        .and_then(|s| NaiveTime::parse_from_str(s, "%H:%M:%S").ok())
+        .is_some_and(|expected_start| expected_end <= expected_start);
+
+    let end_date = if crosses_midnight {
+        work_date + Duration::days(1)
+    } else {
+        work_date
+    };
+    end_date.and_time(expected_end).and_local_timezone(tz).single()
+}
+
 /// Calculate duration between two datetimes in minutes
 pub fn calculate_duration(start: &DateTime<Tz>, end: &DateTime<Tz>) -> i32 {
     let duration = end.signed_duration_since(*start);
@@ -0,0 +1,30 @@
+use crate::models::{ActionType, CheckIn};
+
+/// Sum the durations of completed breaks, split into (paid, unpaid) minutes.
+/// Used for the net minutes stored at clock-out and by the attendance summaries.
+pub fn break_minutes<'a>(
+    breaks: impl IntoIterator<Item = &'a CheckIn>,
+    action_types: &[ActionType],
+) -> (i32, i32) {
+    breaks
+        .into_iter()
+        .filter_map(|b| {
+            let action_type = action_types.iter().find(|at| at.id == b.action_type_id)?;
+            if action_type.action_role != 3 {
+                return None;
+            }
+            Some((b.duration_minutes?.max(0), action_type.is_paid_break))
+        })
+        .fold((0, 0), |(paid, unpaid), (minutes, is_paid)| {
+            if is_paid {
+                (paid + minutes, unpaid)
+            } else {
+                (paid, unpaid + minutes)
+            }
+        })
+}
+
+/// Net working minutes of a shift: gross duration minus unpaid breaks
+pub fn net_work_minutes(gross_minutes: i32, unpaid_break_minutes: i32) -> i32 {
+    (gross_minutes - unpaid_break_minutes).max(0)
+}
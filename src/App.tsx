@@ -1,14 +1,27 @@
-import { useEffect } from 'react';
+import { useEffect, useState } from 'react';
 import { Toaster } from 'react-hot-toast';
 import { checkUpdate, installUpdate } from '@tauri-apps/api/updater';
 import { LoginPage } from './pages/LoginPage';
 import { CheckInPage } from './pages/CheckInPage';
+import { ChangePasswordModal } from './components/ChangePasswordModal';
+import { TwoFactorModal } from './components/TwoFactorModal';
 import { useAuthStore } from './store/authStore';
+import { authAPI } from './services/api';
 import { useUIStore } from './store/uiStore';
 import './styles/globals.css';
 
 function App() {
   const isAuthenticated = useAuthStore((state) => state.isAuthenticated);
+  const mustChangePassword = useAuthStore((state) => state.user?.must_change_password ?? false);
+  const twoFactorRequired = useAuthStore((state) => state.user?.two_factor_setup_required ?? false);
+  // 启用后还要显示恢复码，由弹窗自己关闭
+  const [twoFactorOpen, setTwoFactorOpen] = useState(false);
+
+  useEffect(() => {
+    if (twoFactorRequired) {
+      setTwoFactorOpen(true);
+    }
+  }, [twoFactorRequired]);
   const currentPage = useUIStore((state) => state.currentPage);
   const setCurrentPage = useUIStore((state) => state.setCurrentPage);
 
@@ -20,6 +33,16 @@ function App() {
     }
   }, [isAuthenticated]);
 
+  // 后端会话只保存在内存中，不能从保存的登录状态恢复：
+  // 页面刷新时后端会话仍在，刷新一下用户信息；应用重启后会话已不存在（或账号已停用），
+  // 此时有意清除保存的登录状态，用户需要重新登录
+  useEffect(() => {
+    const { user, updateUser, logout } = useAuthStore.getState();
+    if (user) {
+      authAPI.getCurrentUser(user.id).then(updateUser).catch(() => logout());
+    }
+  }, []);
+
   // 检查更新
   useEffect(() => {
     const checkForUpdates = async () => {
@@ -50,7 +73,13 @@ function App() {
       return <LoginPage />;
     }
 
-    return <CheckInPage />;
+    return (
+      <>
+        <CheckInPage />
+        <ChangePasswordModal isOpen={mustChangePassword} forced onClose={() => {}} />
+        <TwoFactorModal isOpen={twoFactorOpen && !mustChangePassword} forced onClose={() => setTwoFactorOpen(false)} />
+      </>
+    );
   };
 
   return (
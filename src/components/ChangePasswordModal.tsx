@@ -0,0 +1,121 @@
+import React, { useEffect, useState } from 'react';
+import toast from 'react-hot-toast';
+import { Modal } from './common/Modal';
+import { Input } from './common/Input';
+import { Button } from './common/Button';
+import { authAPI } from '../services/api';
+import { useAuthStore } from '../store/authStore';
+import type { PasswordPolicy } from '../types';
+
+interface ChangePasswordModalProps {
+  isOpen: boolean;
+  // 须修改密码时不能关闭，只能退出登录
+  forced?: boolean;
+  onClose: () => void;
+}
+
+const describePolicy = (policy: PasswordPolicy) => {
+  const rules = [`至少 ${policy.min_length} 个字符`];
+  if (policy.require_uppercase) rules.push('包含大写字母');
+  if (policy.require_lowercase) rules.push('包含小写字母');
+  if (policy.require_digit) rules.push('包含数字');
+  if (policy.require_symbol) rules.push('包含符号');
+  rules.push('不包含用户名');
+  return `密码须${rules.join('、')}`;
+};
+
+export const ChangePasswordModal: React.FC<ChangePasswordModalProps> = ({ isOpen, forced = false, onClose }) => {
+  const updateUser = useAuthStore((state) => state.updateUser);
+  const logout = useAuthStore((state) => state.logout);
+  const [policy, setPolicy] = useState<PasswordPolicy | null>(null);
+  const [oldPassword, setOldPassword] = useState('');
+  const [newPassword, setNewPassword] = useState('');
+  const [confirmPassword, setConfirmPassword] = useState('');
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    if (isOpen) {
+      authAPI.getPasswordPolicy().then(setPolicy).catch(() => setPolicy(null));
+    }
+  }, [isOpen]);
+
+  const reset = () => {
+    setOldPassword('');
+    setNewPassword('');
+    setConfirmPassword('');
+  };
+
+  const handleClose = () => {
+    reset();
+    if (forced) {
+      authAPI.logout().catch(() => {});
+      logout();
+    }
+    onClose();
+  };
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (newPassword !== confirmPassword) {
+      toast.error('两次输入的新密码不一致');
+      return;
+    }
+
+    setSaving(true);
+    try {
+      const user = await authAPI.changePassword({ old_password: oldPassword, new_password: newPassword });
+      updateUser(user);
+      toast.success('密码已修改');
+      reset();
+      onClose();
+    } catch (error: any) {
+      toast.error(error || '修改密码失败');
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <Modal isOpen={isOpen} onClose={handleClose} title={forced ? '请先修改密码' : '修改密码'}>
+      <form onSubmit={handleSubmit} className="space-y-3">
+        {forced && (
+          <p className="text-sm text-amber-600 dark:text-amber-400">
+            您的密码是初始密码、临时密码或已过期，修改后才能继续使用。
+          </p>
+        )}
+        <Input
+          label="原密码"
+          type="password"
+          value={oldPassword}
+          onChange={(e) => setOldPassword(e.target.value)}
+          required
+        />
+        <Input
+          label="新密码"
+          type="password"
+          value={newPassword}
+          onChange={(e) => setNewPassword(e.target.value)}
+          required
+        />
+        <Input
+          label="确认新密码"
+          type="password"
+          value={confirmPassword}
+          onChange={(e) => setConfirmPassword(e.target.value)}
+          required
+        />
+        {policy && (
+          <p className="text-xs text-gray-500 dark:text-gray-400">{describePolicy(policy)}</p>
+        )}
+        <div className="flex justify-end gap-2">
+          <Button type="button" variant="secondary" onClick={handleClose}>
+            {forced ? '退出登录' : '取消'}
+          </Button>
+          <Button type="submit" disabled={saving}>
+            {saving ? '保存中...' : '确定'}
+          </Button>
+        </div>
+      </form>
+    </Modal>
+  );
+};
@@ -0,0 +1,181 @@
+import React, { useEffect, useState } from 'react';
+import toast from 'react-hot-toast';
+import { Modal } from './common/Modal';
+import { Input } from './common/Input';
+import { Button } from './common/Button';
+import { authAPI } from '../services/api';
+import { useAuthStore } from '../store/authStore';
+import type { TwoFactorSetup } from '../types';
+
+interface TwoFactorModalProps {
+  isOpen: boolean;
+  // 公司要求管理员启用时不能关闭，只能退出登录
+  forced?: boolean;
+  onClose: () => void;
+}
+
+export const TwoFactorModal: React.FC<TwoFactorModalProps> = ({ isOpen, forced = false, onClose }) => {
+  const user = useAuthStore((state) => state.user);
+  const updateUser = useAuthStore((state) => state.updateUser);
+  const logout = useAuthStore((state) => state.logout);
+  const [setup, setSetup] = useState<TwoFactorSetup | null>(null);
+  const [recoveryCodes, setRecoveryCodes] = useState<string[]>([]);
+  const [code, setCode] = useState('');
+  const [password, setPassword] = useState('');
+  const [saving, setSaving] = useState(false);
+
+  const enabled = user?.totp_enabled ?? false;
+
+  useEffect(() => {
+    if (!isOpen) {
+      setSetup(null);
+      setRecoveryCodes([]);
+      setCode('');
+      setPassword('');
+    }
+  }, [isOpen]);
+
+  const handleClose = () => {
+    // 恢复码显示后才算完成启用，强制模式下可以关闭
+    if (forced && !enabled) {
+      authAPI.logout().catch(() => {});
+      logout();
+    }
+    onClose();
+  };
+
+  const run = async (action: () => Promise<void>, failure: string) => {
+    setSaving(true);
+    try {
+      await action();
+    } catch (error: any) {
+      toast.error(error || failure);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleBegin = () =>
+    run(async () => {
+      setSetup(await authAPI.beginTwoFactorSetup());
+    }, '生成密钥失败');
+
+  const handleEnable = (e: React.FormEvent) => {
+    e.preventDefault();
+    run(async () => {
+      const result = await authAPI.enableTwoFactor(code);
+      setRecoveryCodes(result.recovery_codes);
+      setSetup(null);
+      setCode('');
+      updateUser(result.user);
+      toast.success('两步验证已启用');
+    }, '启用失败');
+  };
+
+  const handleRegenerate = (e: React.FormEvent) => {
+    e.preventDefault();
+    run(async () => {
+      setRecoveryCodes(await authAPI.regenerateRecoveryCodes(code));
+      setCode('');
+      toast.success('已生成新的恢复码，旧恢复码已作废');
+    }, '生成恢复码失败');
+  };
+
+  const handleDisable = (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!confirm('确定要关闭两步验证吗？')) return;
+    run(async () => {
+      updateUser(await authAPI.disableTwoFactor(password));
+      setPassword('');
+      toast.success('两步验证已关闭');
+    }, '关闭失败');
+  };
+
+  const renderContent = () => {
+    if (recoveryCodes.length > 0) {
+      return (
+        <div className="space-y-3">
+          <p className="text-sm text-amber-600 dark:text-amber-400">
+            请妥善保存以下恢复码，只显示这一次。无法使用验证器时，每个恢复码可代替验证码登录一次。
+          </p>
+          <div className="grid grid-cols-2 gap-2 font-mono text-sm text-gray-900 dark:text-white">
+            {recoveryCodes.map((c) => (
+              <span key={c} className="px-2 py-1 bg-gray-100 dark:bg-gray-700 rounded text-center">{c}</span>
+            ))}
+          </div>
+          <div className="flex justify-end gap-2">
+            <Button variant="secondary" onClick={() => navigator.clipboard.writeText(recoveryCodes.join('\n'))}>
+              复制
+            </Button>
+            <Button onClick={onClose}>我已保存</Button>
+          </div>
+        </div>
+      );
+    }
+
+    if (setup) {
+      return (
+        <form onSubmit={handleEnable} className="space-y-3">
+          <p className="text-sm text-gray-600 dark:text-gray-300">
+            用验证器应用（如 Google Authenticator、Microsoft Authenticator）扫描二维码，或手动输入密钥：
+          </p>
+          <img
+            src={`data:image/svg+xml;utf8,${encodeURIComponent(setup.qr_svg)}`}
+            alt="两步验证二维码"
+            className="mx-auto w-48 h-48 bg-white p-2 rounded"
+          />
+          <p className="font-mono text-xs break-all text-center text-gray-900 dark:text-white">{setup.secret}</p>
+          <Input label="验证码" value={code} onChange={(e) => setCode(e.target.value)} placeholder="6 位验证码" required />
+          <div className="flex justify-end gap-2">
+            <Button type="button" variant="secondary" onClick={handleClose}>
+              {forced ? '退出登录' : '取消'}
+            </Button>
+            <Button type="submit" disabled={saving}>启用</Button>
+          </div>
+        </form>
+      );
+    }
+
+    if (!enabled) {
+      return (
+        <div className="space-y-3">
+          <p className="text-sm text-gray-600 dark:text-gray-300">
+            {forced
+              ? '公司要求管理员账号启用两步验证，启用后才能继续使用。'
+              : '启用后，登录时除密码外还须输入验证器应用上的验证码。'}
+          </p>
+          <div className="flex justify-end gap-2">
+            <Button variant="secondary" onClick={handleClose}>
+              {forced ? '退出登录' : '取消'}
+            </Button>
+            <Button onClick={handleBegin} disabled={saving}>开始设置</Button>
+          </div>
+        </div>
+      );
+    }
+
+    return (
+      <div className="space-y-4">
+        <p className="text-sm text-green-600 dark:text-green-400">两步验证已启用</p>
+        <form onSubmit={handleRegenerate} className="space-y-2">
+          <Input label="重新生成恢复码" value={code} onChange={(e) => setCode(e.target.value)} placeholder="输入当前验证码" required />
+          <div className="flex justify-end">
+            <Button type="submit" variant="secondary" size="sm" disabled={saving}>生成</Button>
+          </div>
+        </form>
+        <form onSubmit={handleDisable} className="space-y-2">
+          <Input label="关闭两步验证" type="password" value={password} onChange={(e) => setPassword(e.target.value)} placeholder="输入登录密码" required />
+          <div className="flex justify-end">
+            <Button type="submit" variant="danger" size="sm" disabled={saving}>关闭</Button>
+          </div>
+        </form>
+      </div>
+    );
+  };
+
+  return (
+    <Modal isOpen={isOpen} onClose={handleClose} title={forced ? '请先启用两步验证' : '两步验证'}>
+      {renderContent()}
+    </Modal>
+  );
+};
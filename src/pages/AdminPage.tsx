@@ -7,7 +7,14 @@ import { Input } from '../components/common/Input';
 import { Pagination } from '../components/common/Pagination';
 import { X, Plus, RefreshCw, Copy, FileText } from 'lucide-react';
 import toast from 'react-hot-toast';
-import type { User, ActionType, TimeRule, CheckIn } from '../types';
+import type { User, Role, ActionType, TimeRule, CheckIn, LoginThrottle, InviteCode, Department } from '../types';
+
+const ROLE_LABELS: Record<Role, string> = {
+  employee: '员工',
+  manager: '团队经理',
+  hr: '人事',
+  super_admin: '超级管理员',
+};
 
 interface AdminPageProps {
   isOpen: boolean;
@@ -20,6 +27,10 @@ export const AdminPage: React.FC<AdminPageProps> = ({ isOpen, onClose, isStandal
 
   // Users
   const [users, setUsers] = useState<User[]>([]);
+  const [lockouts, setLockouts] = useState<LoginThrottle[]>([]);
+  const [inviteCodes, setInviteCodes] = useState<InviteCode[]>([]);
+  const [departments, setDepartments] = useState<Department[]>([]);
+  const [inviteForm, setInviteForm] = useState({ department_id: '', max_uses: '1', expires_in_days: '7' });
   
   // Action Types
   const [actionTypes, setActionTypes] = useState<ActionType[]>([]);
@@ -128,8 +139,17 @@ export const AdminPage: React.FC<AdminPageProps> = ({ isOpen, onClose, isStandal
   const loadData = useCallback(async () => {
     try {
       if (activeTab === 'users') {
-        const data = await adminAPI.getAllUsers();
+        const [data, lockoutData, inviteData, departmentData] = await Promise.all([
+          adminAPI.getAllUsers(),
+          // 只有超级管理员能查看和解除登录锁定、管理邀请码
+          adminAPI.getLoginLockouts().catch(() => [] as LoginThrottle[]),
+          adminAPI.getInviteCodes().catch(() => [] as InviteCode[]),
+          adminAPI.getDepartments().catch(() => [] as Department[]),
+        ]);
         setUsers(data);
+        setLockouts(lockoutData);
+        setInviteCodes(inviteData);
+        setDepartments(departmentData);
       } else if (activeTab === 'actionTypes') {
         const data = await adminAPI.getAllActionTypes();
         setActionTypes(data);
@@ -217,21 +237,117 @@ export const AdminPage: React.FC<AdminPageProps> = ({ isOpen, onClose, isStandal
     }
   }, [reportMonth]);
 
-  const handleDeleteUser = async (userId: number) => {
-    if (!confirm('确定要删除该用户吗？')) return;
+  const handleDeactivateUser = async (userId: number) => {
+    const reason = prompt('确定要停用该用户吗？停用后无法登录，历史记录会保留。\n停用原因（可选）：');
+    if (reason === null) return;
     
     try {
-      await adminAPI.deleteUser(userId);
-      toast.success('删除成功');
+      await adminAPI.deactivateUser(userId, reason);
+      toast.success('已停用');
       loadData();
     } catch (error: any) {
-      toast.error(error || '删除失败');
+      toast.error(error || '停用失败');
     }
   };
 
-  const handleToggleAdmin = async (userId: number, currentStatus: boolean) => {
+  const handleRestoreUser = async (userId: number) => {
     try {
-      await adminAPI.updateUserAdminStatus(userId, !currentStatus);
+      await adminAPI.restoreUser(userId);
+      toast.success('已恢复');
+      loadData();
+    } catch (error: any) {
+      toast.error(error || '恢复失败');
+    }
+  };
+
+  const handleResetPassword = async (user: User) => {
+    if (!confirm(`确定要重置「${user.full_name}」的密码吗？`)) return;
+
+    try {
+      const result = await adminAPI.resetUserPassword(user.id);
+      // 临时密码只显示这一次
+      prompt(
+        `临时密码（${new Date(result.expires_at).toLocaleString()} 前有效，登录后须修改）：`,
+        result.temporary_password
+      );
+      loadData();
+    } catch (error: any) {
+      toast.error(error || '重置失败');
+    }
+  };
+
+  const handleResetTwoFactor = async (user: User) => {
+    if (!confirm(`确定要清除「${user.full_name}」的两步验证吗？清除后该员工可重新设置。`)) return;
+
+    try {
+      await adminAPI.resetUserTwoFactor(user.id);
+      toast.success('已清除两步验证');
+      loadData();
+    } catch (error: any) {
+      toast.error(error || '清除失败');
+    }
+  };
+
+  const handleApproveRegistration = async (userId: number, approve: boolean) => {
+    try {
+      if (approve) {
+        await adminAPI.approveRegistration(userId);
+      } else {
+        await adminAPI.rejectRegistration(userId);
+      }
+      toast.success(approve ? '已通过' : '已拒绝');
+      loadData();
+    } catch (error: any) {
+      toast.error(error || '操作失败');
+    }
+  };
+
+  const handleCreateInviteCode = async () => {
+    try {
+      const invite = await adminAPI.createInviteCode({
+        department_id: inviteForm.department_id ? parseInt(inviteForm.department_id) : undefined,
+        max_uses: inviteForm.max_uses ? parseInt(inviteForm.max_uses) : undefined,
+        expires_in_days: inviteForm.expires_in_days ? parseInt(inviteForm.expires_in_days) : undefined,
+      });
+      toast.success(`邀请码：${invite.code}`);
+      loadData();
+    } catch (error: any) {
+      toast.error(error || '创建失败');
+    }
+  };
+
+  const handleRevokeInviteCode = async (inviteId: number) => {
+    if (!confirm('确定要作废该邀请码吗？')) return;
+
+    try {
+      await adminAPI.revokeInviteCode(inviteId);
+      toast.success('已作废');
+      loadData();
+    } catch (error: any) {
+      toast.error(error || '作废失败');
+    }
+  };
+
+  const inviteStatus = (invite: InviteCode) => {
+    if (invite.revoked) return '已作废';
+    if (invite.max_uses !== null && invite.use_count >= invite.max_uses) return '已用完';
+    if (invite.expires_at && new Date(invite.expires_at) <= new Date()) return '已过期';
+    return '可用';
+  };
+
+  const handleUnlockLogin = async (throttleId: number) => {
+    try {
+      await adminAPI.unlockLogin(throttleId);
+      toast.success('已解锁');
+      loadData();
+    } catch (error: any) {
+      toast.error(error || '解锁失败');
+    }
+  };
+
+  const handleChangeRole = async (userId: number, role: Role) => {
+    try {
+      await adminAPI.setUserRole(userId, role);
       toast.success('更新成功');
       loadData();
     } catch (error: any) {
@@ -383,11 +499,18 @@ export const AdminPage: React.FC<AdminPageProps> = ({ isOpen, onClose, isStandal
         action_type_id: timeRuleForm.action_type_id,
       };
 
+      // 与类型不符的字段传 null，编辑时清除旧值
       if (selectedActionType.action_role === 1 || selectedActionType.action_role === 2) {
         payload.expected_start_time = timeRuleForm.expected_start_time;
         payload.expected_end_time = timeRuleForm.expected_end_time;
+        payload.max_duration_minutes = null;
+        payload.daily_max_total_minutes = null;
+        payload.daily_max_count = null;
       } else if (selectedActionType.action_role === 3) {
         payload.max_duration_minutes = timeRuleForm.max_duration_minutes;
+        payload.expected_start_time = null;
+        payload.expected_end_time = null;
+        payload.rest_days = null;
       }
 
       if (editingTimeRule) {
@@ -839,7 +962,7 @@ export const AdminPage: React.FC<AdminPageProps> = ({ isOpen, onClose, isStandal
                       <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">ID</th>
                       <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">用户名</th>
                       <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">姓名</th>
-                      <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">管理员</th>
+                      <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">角色</th>
                       <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">操作</th>
                     </tr>
                   </thead>
@@ -848,35 +971,204 @@ export const AdminPage: React.FC<AdminPageProps> = ({ isOpen, onClose, isStandal
                       <tr key={user.id}>
                         <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">{user.id}</td>
                         <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">{user.username}</td>
-                        <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">{user.full_name}</td>
+                        <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">
+                          {user.full_name}
+                          {!user.is_active && (
+                            <span
+                              className="ml-2 px-2 py-0.5 rounded text-xs bg-gray-200 text-gray-600"
+                              title={user.deactivation_reason || undefined}
+                            >
+                              已停用
+                            </span>
+                          )}
+                          {user.approval_status !== 'approved' && (
+                            <span className="ml-2 px-2 py-0.5 rounded text-xs bg-amber-100 text-amber-700">
+                              {user.approval_status === 'pending' ? '待审批' : '已拒绝'}
+                            </span>
+                          )}
+                        </td>
                         <td className="px-4 py-2">
-                          <span className={`px-2 py-1 rounded text-sm ${
-                            user.is_admin ? 'bg-blue-100 text-blue-800' : 'bg-gray-100 text-gray-800'
-                          }`}>
-                            {user.is_admin ? '✅ 是' : '❌ 否'}
-                          </span>
+                          <select
+                            value={user.role}
+                            onChange={(e) => handleChangeRole(user.id, e.target.value as Role)}
+                            className="px-2 py-1 border rounded text-sm text-gray-900 bg-white dark:bg-gray-700 dark:border-gray-600 dark:text-white"
+                          >
+                            {(Object.keys(ROLE_LABELS) as Role[]).map((role) => (
+                              <option key={role} value={role}>{ROLE_LABELS[role]}</option>
+                            ))}
+                          </select>
                         </td>
                         <td className="px-4 py-2 space-x-2">
+                          {user.approval_status !== 'approved' && (
+                            <Button
+                              variant="success"
+                              size="sm"
+                              onClick={() => handleApproveRegistration(user.id, true)}
+                            >
+                              通过
+                            </Button>
+                          )}
+                          {user.approval_status === 'pending' && (
+                            <Button
+                              variant="danger"
+                              size="sm"
+                              onClick={() => handleApproveRegistration(user.id, false)}
+                            >
+                              拒绝
+                            </Button>
+                          )}
+                          {user.is_active ? (
+                            <Button
+                              variant="danger"
+                              size="sm"
+                              onClick={() => handleDeactivateUser(user.id)}
+                            >
+                              停用
+                            </Button>
+                          ) : (
+                            <Button
+                              variant="secondary"
+                              size="sm"
+                              onClick={() => handleRestoreUser(user.id)}
+                            >
+                              恢复
+                            </Button>
+                          )}
                           <Button
                             variant="secondary"
                             size="sm"
-                            onClick={() => handleToggleAdmin(user.id, user.is_admin)}
-                          >
-                            {user.is_admin ? '设为管理员' : '取消管理员'}
-                          </Button>
-                          <Button
-                            variant="danger"
-                            size="sm"
-                            onClick={() => handleDeleteUser(user.id)}
+                            onClick={() => handleResetPassword(user)}
                           >
-                            删除
+                            重置密码
                           </Button>
+                          {user.totp_enabled && (
+                            <Button
+                              variant="secondary"
+                              size="sm"
+                              onClick={() => handleResetTwoFactor(user)}
+                            >
+                              清除两步验证
+                            </Button>
+                          )}
                         </td>
                       </tr>
                     ))}
                   </tbody>
                 </table>
               </div>
+
+              <div className="mt-6">
+                <h3 className="text-md font-semibold text-gray-900 dark:text-white mb-2">邀请码</h3>
+                <div className="flex items-end gap-2 mb-3">
+                  <div>
+                    <label className="block text-sm text-gray-700 dark:text-gray-300 mb-1">加入部门</label>
+                    <select
+                      value={inviteForm.department_id}
+                      onChange={(e) => setInviteForm({ ...inviteForm, department_id: e.target.value })}
+                      className="px-2 py-2 border rounded text-sm text-gray-900 bg-white dark:bg-gray-700 dark:border-gray-600 dark:text-white"
+                    >
+                      <option value="">不指定</option>
+                      {departments.map((d) => (
+                        <option key={d.id} value={d.id}>{d.name}</option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="w-28">
+                    <Input
+                      label="使用次数"
+                      type="number"
+                      min={1}
+                      value={inviteForm.max_uses}
+                      onChange={(e) => setInviteForm({ ...inviteForm, max_uses: e.target.value })}
+                      placeholder="不限"
+                    />
+                  </div>
+                  <div className="w-28">
+                    <Input
+                      label="有效天数"
+                      type="number"
+                      min={1}
+                      value={inviteForm.expires_in_days}
+                      onChange={(e) => setInviteForm({ ...inviteForm, expires_in_days: e.target.value })}
+                      placeholder="不限"
+                    />
+                  </div>
+                  <Button size="sm" onClick={handleCreateInviteCode}>
+                    <Plus size={16} />
+                    <span>生成邀请码</span>
+                  </Button>
+                </div>
+                {inviteCodes.length > 0 && (
+                  <table className="w-full">
+                    <thead className="bg-gray-50 dark:bg-gray-700">
+                      <tr>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">邀请码</th>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">部门</th>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">已使用</th>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">有效期至</th>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">状态</th>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">操作</th>
+                      </tr>
+                    </thead>
+                    <tbody className="divide-y dark:divide-gray-700">
+                      {inviteCodes.map((invite) => (
+                        <tr key={invite.id}>
+                          <td className="px-4 py-2 text-sm font-mono text-gray-900 dark:text-white">{invite.code}</td>
+                          <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">
+                            {departments.find((d) => d.id === invite.department_id)?.name || '-'}
+                          </td>
+                          <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">
+                            {invite.use_count}{invite.max_uses !== null ? ` / ${invite.max_uses}` : ''}
+                          </td>
+                          <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">
+                            {invite.expires_at ? new Date(invite.expires_at).toLocaleString() : '-'}
+                          </td>
+                          <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">{inviteStatus(invite)}</td>
+                          <td className="px-4 py-2">
+                            {!invite.revoked && (
+                              <Button variant="secondary" size="sm" onClick={() => handleRevokeInviteCode(invite.id)}>
+                                作废
+                              </Button>
+                            )}
+                          </td>
+                        </tr>
+                      ))}
+                    </tbody>
+                  </table>
+                )}
+              </div>
+
+              {lockouts.length > 0 && (
+                <div className="mt-6">
+                  <h3 className="text-md font-semibold text-gray-900 dark:text-white mb-2">登录锁定</h3>
+                  <table className="w-full">
+                    <thead className="bg-gray-50 dark:bg-gray-700">
+                      <tr>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">类型</th>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">用户名/设备</th>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">锁定至</th>
+                        <th className="px-4 py-2 text-left text-sm font-medium text-gray-700 dark:text-gray-300">操作</th>
+                      </tr>
+                    </thead>
+                    <tbody className="divide-y dark:divide-gray-700">
+                      {lockouts.map((lockout) => (
+                        <tr key={lockout.id}>
+                          <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">{lockout.scope === 'user' ? '用户名' : '设备'}</td>
+                          <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">{lockout.key}</td>
+                          <td className="px-4 py-2 text-sm text-gray-900 dark:text-white">
+                            {lockout.locked_until && new Date(lockout.locked_until).toLocaleString()}
+                          </td>
+                          <td className="px-4 py-2">
+                            <Button variant="secondary" size="sm" onClick={() => handleUnlockLogin(lockout.id)}>
+                              解锁
+                            </Button>
+                          </td>
+                        </tr>
+                      ))}
+                    </tbody>
+                  </table>
+                </div>
+              )}
             </div>
           )}
 
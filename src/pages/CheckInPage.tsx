@@ -2,9 +2,11 @@ import React, { useEffect, useState } from 'react';
 import { useAuthStore } from '../store/authStore';
 import { useCheckInStore } from '../store/checkinStore';
 import { useUIStore } from '../store/uiStore';
-import { checkinAPI } from '../services/api';
+import { authAPI, checkinAPI } from '../services/api';
 import { Button } from '../components/common/Button';
-import { LogOut, RefreshCw, Settings, Clock, Calendar, Coffee, Briefcase, Moon, Sun, Download } from 'lucide-react';
+import { ChangePasswordModal } from '../components/ChangePasswordModal';
+import { TwoFactorModal } from '../components/TwoFactorModal';
+import { LogOut, RefreshCw, Settings, Clock, Calendar, Coffee, Briefcase, Moon, Sun, Download, KeyRound, ShieldCheck } from 'lucide-react';
 import { checkUpdate, installUpdate } from '@tauri-apps/api/updater';
 import toast from 'react-hot-toast';
 import { format } from 'date-fns';
@@ -22,6 +24,8 @@ export const CheckInPage: React.FC = () => {
   
   const [loading, setLoading] = useState(false);
   const [currentTime, setCurrentTime] = useState(new Date());
+  const [showChangePassword, setShowChangePassword] = useState(false);
+  const [showTwoFactor, setShowTwoFactor] = useState(false);
   
   // 金边时区常量
   const PHNOM_PENH_TIMEZONE = 'Asia/Phnom_Penh';
@@ -68,6 +72,7 @@ export const CheckInPage: React.FC = () => {
       
       const actionType = actionTypes.find(t => t.id === actionTypeId);
       toast.success(`✓ ${actionType?.button_text || '打卡'}成功！`);
+      checkIn.warnings.forEach(warning => toast(warning, { icon: '⚠️' }));
       
       await loadData();
     } catch (error: any) {
@@ -76,6 +81,7 @@ export const CheckInPage: React.FC = () => {
   };
 
   const handleLogout = () => {
+    authAPI.logout().catch(() => {});
     logout();
     setCurrentPage('login');
   };
@@ -196,7 +202,7 @@ export const CheckInPage: React.FC = () => {
                 <Download size={14} />
                 <span>检查更新</span>
               </Button>
-              {user?.is_admin && (
+              {user && user.role !== 'employee' && (
                 <Button
                   variant="secondary"
                   size="sm"
@@ -206,6 +212,14 @@ export const CheckInPage: React.FC = () => {
                   <span>管理</span>
                 </Button>
               )}
+              <Button variant="secondary" size="sm" onClick={() => setShowChangePassword(true)}>
+                <KeyRound size={14} />
+                <span>修改密码</span>
+              </Button>
+              <Button variant="secondary" size="sm" onClick={() => setShowTwoFactor(true)}>
+                <ShieldCheck size={14} />
+                <span>两步验证</span>
+              </Button>
               <Button variant="secondary" size="sm" onClick={loadData} disabled={loading}>
                 <RefreshCw size={14} className={loading ? 'animate-spin' : ''} />
               </Button>
@@ -412,6 +426,9 @@ export const CheckInPage: React.FC = () => {
           )}
         </div>
       </main>
+
+      <ChangePasswordModal isOpen={showChangePassword} onClose={() => setShowChangePassword(false)} />
+      <TwoFactorModal isOpen={showTwoFactor} onClose={() => setShowTwoFactor(false)} />
     </div>
   );
 };
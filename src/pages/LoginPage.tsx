@@ -1,9 +1,10 @@
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
 import { authAPI } from '../services/api';
 import { useAuthStore } from '../store/authStore';
 import { useUIStore } from '../store/uiStore';
-import { Clock, User, Lock, Sparkles } from 'lucide-react';
+import { Clock, User, Lock, Sparkles, ShieldCheck, Mail, Ticket } from 'lucide-react';
 import toast from 'react-hot-toast';
+import type { RegistrationSettings } from '../types';
 
 export const LoginPage: React.FC = () => {
   const [isLogin, setIsLogin] = useState(true);
@@ -11,10 +12,23 @@ export const LoginPage: React.FC = () => {
   const [password, setPassword] = useState('');
   const [fullName, setFullName] = useState('');
   const [loading, setLoading] = useState(false);
+  const [twoFactorPending, setTwoFactorPending] = useState(false);
+  const [code, setCode] = useState('');
+  const [email, setEmail] = useState('');
+  const [inviteCode, setInviteCode] = useState('');
+  const [registration, setRegistration] = useState<RegistrationSettings | null>(null);
 
   const login = useAuthStore((state) => state.login);
   const setCurrentPage = useUIStore((state) => state.setCurrentPage);
 
+  // 关闭注册时只显示登录
+  const registrationOpen = registration?.mode !== 'closed';
+  const inviteRequired = registration?.mode === 'invite_only';
+
+  useEffect(() => {
+    authAPI.getRegistrationSettings().then(setRegistration).catch(() => setRegistration(null));
+  }, []);
+
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
     
@@ -43,16 +57,32 @@ export const LoginPage: React.FC = () => {
 
     try {
       if (isLogin) {
-        const response = await authAPI.login({ username, password });
-        login(response);
-        toast.success(`欢迎回来，${response.user.full_name}！`);
+        const result = twoFactorPending
+          ? await authAPI.verifyLoginCode(code)
+          : await authAPI.login({ username, password });
+        if ('status' in result && result.status === 'two_factor_required') {
+          setTwoFactorPending(true);
+          return;
+        }
+        setTwoFactorPending(false);
+        setCode('');
+        login(result);
+        toast.success(`欢迎回来，${result.user.full_name}！`);
         setCurrentPage('checkin');
       } else {
-        await authAPI.register({ username, password, full_name: fullName });
-        toast.success('注册成功！请登录');
+        const user = await authAPI.register({
+          username,
+          password,
+          full_name: fullName,
+          email: email.trim() || undefined,
+          invite_code: inviteCode.trim() || undefined,
+        });
+        toast.success(user.approval_status === 'pending' ? '注册成功，请等待管理员审批后登录' : '注册成功！请登录');
         setIsLogin(true);
         setPassword('');
         setFullName('');
+        setEmail('');
+        setInviteCode('');
       }
     } catch (error: any) {
       console.error('Login/Register error:', error);
@@ -107,84 +137,163 @@ export const LoginPage: React.FC = () => {
             >
               登录
             </button>
-            <button
-              className={`flex-1 py-3 font-semibold rounded-lg transition-all duration-300 ${
-                !isLogin
-                  ? 'bg-gradient-to-r from-indigo-600 to-purple-600 text-white shadow-lg'
-                  : 'text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-gray-200'
-              }`}
-              onClick={() => setIsLogin(false)}
-            >
-              注册
-            </button>
+            {registrationOpen && (
+              <button
+                className={`flex-1 py-3 font-semibold rounded-lg transition-all duration-300 ${
+                  !isLogin
+                    ? 'bg-gradient-to-r from-indigo-600 to-purple-600 text-white shadow-lg'
+                    : 'text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-gray-200'
+                }`}
+                onClick={() => setIsLogin(false)}
+              >
+                注册
+              </button>
+            )}
           </div>
 
           <form onSubmit={handleSubmit} className="space-y-5">
-            {/* 用户名输入 */}
-            <div className="group">
-              <label className="block text-sm font-semibold text-gray-700 dark:text-gray-300 mb-2">
-                用户名
-              </label>
-              <div className="relative">
-                <div className="absolute inset-y-0 left-0 pl-4 flex items-center pointer-events-none">
-                  <User className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
-                </div>
-                <input
-                  type="text"
-                  value={username}
-                  onChange={(e) => setUsername(e.target.value)}
-                  placeholder="请输入用户名"
-                  required
-                  autoComplete="username"
-                  className="w-full pl-12 pr-4 py-3.5 bg-gray-50 dark:bg-gray-800 border-2 border-gray-200 dark:border-gray-700 rounded-xl focus:outline-none focus:border-indigo-500 focus:bg-white dark:focus:bg-gray-900 transition-all duration-300 text-gray-900 dark:text-white placeholder-gray-400"
-                />
-              </div>
-              <p className="mt-2 text-xs text-gray-500 dark:text-gray-400">至少3个字符</p>
-            </div>
-
-            {/* 密码输入 */}
-            <div className="group">
-              <label className="block text-sm font-semibold text-gray-700 dark:text-gray-300 mb-2">
-                密码
-              </label>
-              <div className="relative">
-                <div className="absolute inset-y-0 left-0 pl-4 flex items-center pointer-events-none">
-                  <Lock className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
-                </div>
-                <input
-                  type="password"
-                  value={password}
-                  onChange={(e) => setPassword(e.target.value)}
-                  placeholder="请输入密码"
-                  required
-                  autoComplete={isLogin ? 'current-password' : 'new-password'}
-                  className="w-full pl-12 pr-4 py-3.5 bg-gray-50 dark:bg-gray-800 border-2 border-gray-200 dark:border-gray-700 rounded-xl focus:outline-none focus:border-indigo-500 focus:bg-white dark:focus:bg-gray-900 transition-all duration-300 text-gray-900 dark:text-white placeholder-gray-400"
-                />
-              </div>
-              <p className="mt-2 text-xs text-gray-500 dark:text-gray-400">至少6个字符</p>
-            </div>
-
-            {/* 真实姓名输入 (仅注册时显示) */}
-            {!isLogin && (
+            {/* 两步验证：密码正确后输入验证器上的验证码或恢复码 */}
+            {twoFactorPending ? (
               <div className="group animate-fadeIn">
                 <label className="block text-sm font-semibold text-gray-700 dark:text-gray-300 mb-2">
-                  真实姓名
+                  验证码
                 </label>
                 <div className="relative">
                   <div className="absolute inset-y-0 left-0 pl-4 flex items-center pointer-events-none">
-                    <User className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
+                    <ShieldCheck className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
                   </div>
                   <input
                     type="text"
-                    value={fullName}
-                    onChange={(e) => setFullName(e.target.value)}
-                    placeholder="请输入真实姓名"
+                    value={code}
+                    onChange={(e) => setCode(e.target.value)}
+                    placeholder="请输入验证器上的 6 位验证码"
                     required
-                    autoComplete="name"
+                    autoFocus
+                    autoComplete="one-time-code"
                     className="w-full pl-12 pr-4 py-3.5 bg-gray-50 dark:bg-gray-800 border-2 border-gray-200 dark:border-gray-700 rounded-xl focus:outline-none focus:border-indigo-500 focus:bg-white dark:focus:bg-gray-900 transition-all duration-300 text-gray-900 dark:text-white placeholder-gray-400"
                   />
                 </div>
+                <p className="mt-2 text-xs text-gray-500 dark:text-gray-400">
+                  无法使用验证器时可输入恢复码；
+                  <button type="button" onClick={() => { setTwoFactorPending(false); setCode(''); }} className="text-indigo-600 hover:text-purple-600 font-semibold">
+                    返回重新登录
+                  </button>
+                </p>
               </div>
+            ) : (
+              <>
+                {/* 用户名输入 */}
+                <div className="group">
+                  <label className="block text-sm font-semibold text-gray-700 dark:text-gray-300 mb-2">
+                    用户名
+                  </label>
+                  <div className="relative">
+                    <div className="absolute inset-y-0 left-0 pl-4 flex items-center pointer-events-none">
+                      <User className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
+                    </div>
+                    <input
+                      type="text"
+                      value={username}
+                      onChange={(e) => setUsername(e.target.value)}
+                      placeholder="请输入用户名"
+                      required
+                      autoComplete="username"
+                      className="w-full pl-12 pr-4 py-3.5 bg-gray-50 dark:bg-gray-800 border-2 border-gray-200 dark:border-gray-700 rounded-xl focus:outline-none focus:border-indigo-500 focus:bg-white dark:focus:bg-gray-900 transition-all duration-300 text-gray-900 dark:text-white placeholder-gray-400"
+                    />
+                  </div>
+                  <p className="mt-2 text-xs text-gray-500 dark:text-gray-400">至少3个字符</p>
+                </div>
+
+                {/* 密码输入 */}
+                <div className="group">
+                  <label className="block text-sm font-semibold text-gray-700 dark:text-gray-300 mb-2">
+                    密码
+                  </label>
+                  <div className="relative">
+                    <div className="absolute inset-y-0 left-0 pl-4 flex items-center pointer-events-none">
+                      <Lock className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
+                    </div>
+                    <input
+                      type="password"
+                      value={password}
+                      onChange={(e) => setPassword(e.target.value)}
+                      placeholder="请输入密码"
+                      required
+                      autoComplete={isLogin ? 'current-password' : 'new-password'}
+                      className="w-full pl-12 pr-4 py-3.5 bg-gray-50 dark:bg-gray-800 border-2 border-gray-200 dark:border-gray-700 rounded-xl focus:outline-none focus:border-indigo-500 focus:bg-white dark:focus:bg-gray-900 transition-all duration-300 text-gray-900 dark:text-white placeholder-gray-400"
+                    />
+                  </div>
+                  <p className="mt-2 text-xs text-gray-500 dark:text-gray-400">至少6个字符</p>
+                </div>
+
+                {/* 真实姓名输入 (仅注册时显示) */}
+                {!isLogin && (
+                  <div className="group animate-fadeIn">
+                    <label className="block text-sm font-semibold text-gray-700 dark:text-gray-300 mb-2">
+                      真实姓名
+                    </label>
+                    <div className="relative">
+                      <div className="absolute inset-y-0 left-0 pl-4 flex items-center pointer-events-none">
+                        <User className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
+                      </div>
+                      <input
+                        type="text"
+                        value={fullName}
+                        onChange={(e) => setFullName(e.target.value)}
+                        placeholder="请输入真实姓名"
+                        required
+                        autoComplete="name"
+                        className="w-full pl-12 pr-4 py-3.5 bg-gray-50 dark:bg-gray-800 border-2 border-gray-200 dark:border-gray-700 rounded-xl focus:outline-none focus:border-indigo-500 focus:bg-white dark:focus:bg-gray-900 transition-all duration-300 text-gray-900 dark:text-white placeholder-gray-400"
+                      />
+                    </div>
+                  </div>
+                )}
+
+                {/* 邮箱 (限制邮箱域名时须填写) */}
+                {!isLogin && (registration?.email_domains.length ?? 0) > 0 && !inviteCode.trim() && (
+                  <div className="group animate-fadeIn">
+                    <label className="block text-sm font-semibold text-gray-700 dark:text-gray-300 mb-2">
+                      公司邮箱
+                    </label>
+                    <div className="relative">
+                      <div className="absolute inset-y-0 left-0 pl-4 flex items-center pointer-events-none">
+                        <Mail className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
+                      </div>
+                      <input
+                        type="email"
+                        value={email}
+                        onChange={(e) => setEmail(e.target.value)}
+                        placeholder={`@${registration?.email_domains.join('、@')}`}
+                        required
+                        autoComplete="email"
+                        className="w-full pl-12 pr-4 py-3.5 bg-gray-50 dark:bg-gray-800 border-2 border-gray-200 dark:border-gray-700 rounded-xl focus:outline-none focus:border-indigo-500 focus:bg-white dark:focus:bg-gray-900 transition-all duration-300 text-gray-900 dark:text-white placeholder-gray-400"
+                      />
+                    </div>
+                  </div>
+                )}
+
+                {/* 邀请码 */}
+                {!isLogin && (
+                  <div className="group animate-fadeIn">
+                    <label className="block text-sm font-semibold text-gray-700 dark:text-gray-300 mb-2">
+                      邀请码
+                    </label>
+                    <div className="relative">
+                      <div className="absolute inset-y-0 left-0 pl-4 flex items-center pointer-events-none">
+                        <Ticket className="h-5 w-5 text-gray-400 group-focus-within:text-indigo-600 transition-colors" />
+                      </div>
+                      <input
+                        type="text"
+                        value={inviteCode}
+                        onChange={(e) => setInviteCode(e.target.value)}
+                        placeholder={inviteRequired ? '请输入管理员发放的邀请码' : '选填'}
+                        required={inviteRequired}
+                        className="w-full pl-12 pr-4 py-3.5 bg-gray-50 dark:bg-gray-800 border-2 border-gray-200 dark:border-gray-700 rounded-xl focus:outline-none focus:border-indigo-500 focus:bg-white dark:focus:bg-gray-900 transition-all duration-300 text-gray-900 dark:text-white placeholder-gray-400"
+                      />
+                    </div>
+                  </div>
+                )}
+              </>
             )}
 
             {/* 提交按钮 */}
@@ -216,7 +325,7 @@ export const LoginPage: React.FC = () => {
           {/* 底部提示 */}
           <div className="mt-6 text-center text-sm text-gray-600 dark:text-gray-400">
             {isLogin ? (
-              <p>还没有账号？<button onClick={() => setIsLogin(false)} className="text-indigo-600 hover:text-purple-600 font-semibold ml-1 transition-colors">立即注册</button></p>
+              registrationOpen && <p>还没有账号？<button onClick={() => setIsLogin(false)} className="text-indigo-600 hover:text-purple-600 font-semibold ml-1 transition-colors">立即注册</button></p>
             ) : (
               <p>已有账号？<button onClick={() => setIsLogin(true)} className="text-indigo-600 hover:text-purple-600 font-semibold ml-1 transition-colors">立即登录</button></p>
             )}
@@ -1,15 +1,65 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
 import type {
   LoginRequest,
   RegisterRequest,
   LoginResponse,
   User,
+  CreateUserRequest,
+  CreatedUser,
+  UpdateUserProfileRequest,
+  UpdateMyProfileRequest,
+  PasswordPolicy,
+  ChangePasswordRequest,
+  PasswordResetResult,
+  LoginThrottle,
+  LoginResult,
+  TwoFactorSetup,
+  TwoFactorEnabled,
+  RegistrationSettings,
+  InviteCode,
+  CreateInviteCodeRequest,
+  Role,
+  Department,
+  CreateDepartmentRequest,
+  UpdateDepartmentRequest,
   ActionType,
+  MergeActionTypesResult,
+  ConfigIssue,
+  UpdateActionTypeRequest,
   CheckIn,
   CheckInRequest,
+  CheckInResponse,
   TimeRule,
+  Holiday,
+  Leave,
+  SystemConfig,
+  ImportCheckInsRequest,
+  ImportReport,
+  ProvisionUsersRequest,
+  ProvisionReport,
+  ConfigFileFormat,
+  ImportCompanyConfigRequest,
+  ConfigImportReport,
   CheckInStatistics,
   PaginatedCheckIns,
+  AttendanceDashboard,
+  PresenceOverview,
+  CheckInChange,
+  RealtimeStatus,
+  ExportCheckInsRequest,
+  ExportAttendanceRequest,
+  ExportTimesheetRequest,
+  PdfReportRequest,
+  TimesheetColumn,
+  ExportResult,
+  CreateBackupRequest,
+  RestoreBackupRequest,
+  BackupResult,
+  RestoreResult,
+  BackupProgress,
+  AuditLogQuery,
+  PaginatedAuditLog,
 } from '../types';
 import { useCacheStore } from '../store/cacheStore';
 
@@ -43,22 +93,63 @@ const cachedInvoke = async <T>(
   return data;
 };
 
+// 本机的设备标识，首次使用时生成并保存
+const getDeviceId = () => {
+  let deviceId = localStorage.getItem('device-id');
+  if (!deviceId) {
+    deviceId = crypto.randomUUID();
+    localStorage.setItem('device-id', deviceId);
+  }
+  return deviceId;
+};
+
 // Auth APIs
 export const authAPI = {
   login: (credentials: LoginRequest) => 
-    invoke<LoginResponse>('login', { credentials }),
+    invoke<LoginResult>('login', { credentials: { device_id: getDeviceId(), ...credentials } }),
+
+  verifyLoginCode: (code: string) =>
+    invoke<LoginResponse>('verify_login_code', { code }),
   
   register: (data: RegisterRequest) => 
     invoke<User>('register', { data }),
+
+  getRegistrationSettings: () =>
+    invoke<RegistrationSettings>('get_registration_settings'),
   
   getCurrentUser: (userId: number) => 
     invoke<User>('get_current_user', { userId }),
+
+  logout: () =>
+    invoke('logout'),
+
+  // 员工自助修改联系方式
+  updateMyProfile: (request: UpdateMyProfileRequest) =>
+    invoke<User>('update_my_profile', { request }),
+
+  changePassword: (request: ChangePasswordRequest) =>
+    invoke<User>('change_password', { request }),
+
+  getPasswordPolicy: () =>
+    invoke<PasswordPolicy>('get_password_policy'),
+
+  beginTwoFactorSetup: () =>
+    invoke<TwoFactorSetup>('begin_two_factor_setup'),
+
+  enableTwoFactor: (code: string) =>
+    invoke<TwoFactorEnabled>('enable_two_factor', { code }),
+
+  disableTwoFactor: (password: string) =>
+    invoke<User>('disable_two_factor', { password }),
+
+  regenerateRecoveryCodes: (code: string) =>
+    invoke<string[]>('regenerate_recovery_codes', { code }),
 };
 
 // Check-in APIs
 export const checkinAPI = {
   createCheckIn: (request: CheckInRequest) => 
-    invoke<CheckIn>('create_check_in', { request }),
+    invoke<CheckInResponse>('create_check_in', { request }),
   
   createManualCheckIn: (request: any) => 
     invoke<CheckIn>('create_manual_check_in', { request }),
@@ -82,14 +173,90 @@ export const adminAPI = {
   getAllUsers: () => 
     cachedInvoke<User[]>('get_all_users', undefined, 'users'),
   
-  updateUserAdminStatus: (userId: number, isAdmin: boolean) => {
+  // 不能修改自己的角色
+  setUserRole: (userId: number, role: Role) => {
     useCacheStore.getState().invalidateUsers();
-    return invoke('update_user_admin_status', { userId, isAdmin });
+    return invoke('set_user_role', { userId, role });
   },
   
-  deleteUser: (userId: number) => {
+  // 停用代替删除，历史打卡仍保留在报表中
+  deactivateUser: (userId: number, reason?: string) => {
+    useCacheStore.getState().invalidateUsers();
+    return invoke('deactivate_user', { userId, reason });
+  },
+
+  restoreUser: (userId: number) => {
+    useCacheStore.getState().invalidateUsers();
+    return invoke('restore_user', { userId });
+  },
+
+  getLoginLockouts: () =>
+    invoke<LoginThrottle[]>('get_login_lockouts'),
+
+  unlockLogin: (throttleId: number) =>
+    invoke('unlock_login', { throttleId }),
+
+  getInviteCodes: () =>
+    invoke<InviteCode[]>('get_invite_codes'),
+
+  createInviteCode: (request: CreateInviteCodeRequest) =>
+    invoke<InviteCode>('create_invite_code', { request }),
+
+  revokeInviteCode: (inviteId: number) =>
+    invoke('revoke_invite_code', { inviteId }),
+
+  approveRegistration: (userId: number) => {
+    useCacheStore.getState().invalidateUsers();
+    return invoke<User>('approve_registration', { userId });
+  },
+
+  rejectRegistration: (userId: number) => {
     useCacheStore.getState().invalidateUsers();
-    return invoke('delete_user', { userId });
+    return invoke<User>('reject_registration', { userId });
+  },
+
+  resetUserTwoFactor: (userId: number) => {
+    useCacheStore.getState().invalidateUsers();
+    return invoke('reset_user_two_factor', { userId });
+  },
+
+  resetUserPassword: (userId: number) => {
+    useCacheStore.getState().invalidateUsers();
+    return invoke<PasswordResetResult>('reset_user_password', { userId });
+  },
+
+  // Profiles
+  getUserProfile: (userId: number) =>
+    invoke<User>('get_user_profile', { userId }),
+
+  createUser: (request: CreateUserRequest) => {
+    useCacheStore.getState().invalidateUsers();
+    return invoke<CreatedUser>('create_user', { request });
+  },
+
+  updateUserProfile: (request: UpdateUserProfileRequest) => {
+    useCacheStore.getState().invalidateUsers();
+    return invoke<User>('update_user_profile', { request });
+  },
+
+  // Departments
+  getDepartments: () =>
+    invoke<Department[]>('get_departments'),
+
+  createDepartment: (request: CreateDepartmentRequest) =>
+    invoke<Department>('create_department', { request }),
+
+  updateDepartment: (request: UpdateDepartmentRequest) =>
+    invoke('update_department', { request }),
+
+  // 有成员、下属团队或部门排班的部门不能删除
+  deleteDepartment: (departmentId: number) =>
+    invoke('delete_department', { departmentId }),
+
+  // departmentId 为 null 时移出部门
+  setUserDepartment: (userId: number, departmentId: number | null) => {
+    useCacheStore.getState().invalidateUsers();
+    return invoke('set_user_department', { userId, departmentId });
   },
   
   // Action types
@@ -101,7 +268,7 @@ export const adminAPI = {
     return invoke<ActionType>('create_action_type', { request });
   },
   
-  updateActionType: (request: any) => {
+  updateActionType: (request: UpdateActionTypeRequest) => {
     useCacheStore.getState().invalidateActionTypes();
     return invoke('update_action_type', { request });
   },
@@ -110,6 +277,27 @@ export const adminAPI = {
     useCacheStore.getState().invalidateActionTypes();
     return invoke('delete_action_type', { actionTypeId });
   },
+
+  // 被引用的类型不能删除，只能归档
+  archiveActionType: (actionTypeId: number) => {
+    const cache = useCacheStore.getState();
+    cache.invalidateActionTypes();
+    cache.invalidateTimeRules();
+    return invoke('archive_action_type', { actionTypeId });
+  },
+
+  restoreActionType: (actionTypeId: number) => {
+    useCacheStore.getState().invalidateActionTypes();
+    return invoke('restore_action_type', { actionTypeId });
+  },
+
+  // 将 source 合并到 target，改写历史打卡后删除 source
+  mergeActionTypes: (sourceId: number, targetId: number) => {
+    const cache = useCacheStore.getState();
+    cache.invalidateActionTypes();
+    cache.invalidateTimeRules();
+    return invoke<MergeActionTypesResult>('merge_action_types', { sourceId, targetId });
+  },
   
   // Time rules
   getAllTimeRules: () => 
@@ -129,6 +317,73 @@ export const adminAPI = {
     useCacheStore.getState().invalidateTimeRules();
     return invoke('delete_time_rule', { ruleId });
   },
+
+  // 检查打卡类型与时间规则的一致性
+  validateConfiguration: () =>
+    invoke<ConfigIssue[]>('validate_configuration'),
+
+  archiveTimeRule: (ruleId: number) => {
+    useCacheStore.getState().invalidateTimeRules();
+    return invoke('archive_time_rule', { ruleId });
+  },
+
+  restoreTimeRule: (ruleId: number) => {
+    useCacheStore.getState().invalidateTimeRules();
+    return invoke('restore_time_rule', { ruleId });
+  },
+
+  // Holidays
+  getHolidays: (year?: number) =>
+    invoke<Holiday[]>('get_holidays', { year }),
+
+  createHoliday: (request: { holiday_date: string; name: string; is_workday?: boolean }) =>
+    invoke<Holiday>('create_holiday', { request }),
+
+  deleteHoliday: (holidayId: number) =>
+    invoke('delete_holiday', { holidayId }),
+
+  // Leaves
+  getLeaves: (startDate: string, endDate: string, userId?: number) =>
+    invoke<Leave[]>('get_leaves', { userId, startDate, endDate }),
+
+  createLeave: (request: {
+    user_id: number;
+    start_date: string;
+    end_date: string;
+    leave_type: string;
+    note?: string;
+  }) => invoke<Leave>('create_leave', { request }),
+
+  deleteLeave: (leaveId: number) =>
+    invoke('delete_leave', { leaveId }),
+
+  // System config
+  getSystemConfigs: () =>
+    invoke<SystemConfig[]>('get_system_configs'),
+
+  updateSystemConfig: (request: { config_key: string; config_value: string; config_desc?: string }) =>
+    invoke<SystemConfig>('update_system_config', { request }),
+
+  // 历史打卡导入（先 dry_run 校验，再正式导入）
+  importCheckIns: (request: ImportCheckInsRequest) =>
+    invoke<ImportReport>('import_check_ins', { request }),
+
+  // 批量开户（生成初始密码，可同时导出账号密码表）
+  provisionUsers: (request: ProvisionUsersRequest) =>
+    invoke<ProvisionReport>('provision_users', { request }),
+
+  // 公司配置导出 / 导入（导入先 dry_run 预览差异）
+  exportCompanyConfig: (path: string, format: ConfigFileFormat) =>
+    invoke('export_company_config', { request: { path, format } }),
+
+  importCompanyConfig: (request: ImportCompanyConfigRequest) => {
+    const cache = useCacheStore.getState();
+    if (!request.dry_run) {
+      cache.invalidateActionTypes();
+      cache.invalidateTimeRules();
+    }
+    return invoke<ConfigImportReport>('import_company_config', { request });
+  },
 };
 
 // Statistics APIs
@@ -136,6 +391,9 @@ export const statisticsAPI = {
   getUserStatistics: (userId: number, startDate: string, endDate: string) => 
     invoke<CheckInStatistics>('get_user_statistics', { userId, startDate, endDate }),
   
+  getAttendanceDashboard: (startDate: string, endDate: string, departmentId?: number) =>
+    invoke<AttendanceDashboard>('get_attendance_dashboard', { startDate, endDate, departmentId }),
+
   getAllCheckIns: (startDate?: string, endDate?: string) => 
     invoke<CheckIn[]>('get_all_check_ins', { startDate, endDate }),
   
@@ -144,13 +402,77 @@ export const statisticsAPI = {
     endDate: string | undefined,
     page: number,
     pageSize: number,
-    userId?: number
+    userId?: number,
+    departmentId?: number
   ) => 
     invoke<PaginatedCheckIns>('get_paginated_check_ins', { 
       startDate, 
       endDate, 
       page, 
       pageSize,
-      userId 
+      userId,
+      departmentId
     }),
 };
+
+// Presence APIs
+export const presenceAPI = {
+  getCurrentPresence: () =>
+    invoke<PresenceOverview>('get_current_presence'),
+
+  // 返回取消监听的函数
+  onCheckInChanged: (handler: (change: CheckInChange) => void) =>
+    listen<CheckInChange>('check-in-changed', (event) => handler(event.payload)),
+
+  onRealtimeStatus: (handler: (status: RealtimeStatus) => void) =>
+    listen<RealtimeStatus>('realtime-status', (event) => handler(event.payload)),
+};
+
+// Export APIs（path 由 dialog.save 选择）
+export const exportAPI = {
+  exportCheckIns: (request: ExportCheckInsRequest) =>
+    invoke<ExportResult>('export_check_ins', { request }),
+
+  exportAttendanceReport: (request: ExportAttendanceRequest) =>
+    invoke<ExportResult>('export_attendance_report', { request }),
+
+  getTimesheetTemplate: () =>
+    invoke<TimesheetColumn[]>('get_timesheet_template'),
+
+  saveTimesheetTemplate: (template: TimesheetColumn[]) =>
+    invoke<TimesheetColumn[]>('save_timesheet_template', { template }),
+
+  exportMonthlyTimesheet: (request: ExportTimesheetRequest) =>
+    invoke<ExportResult>('export_monthly_timesheet', { request }),
+
+  // PDF 报表（中文字体可通过系统配置 pdf_font_path 指定）
+  exportEmployeePdfReport: (request: PdfReportRequest) =>
+    invoke<ExportResult>('export_employee_pdf_report', { request }),
+
+  exportTeamPdfReport: (request: PdfReportRequest) =>
+    invoke<ExportResult>('export_team_pdf_report', { request }),
+
+  // 已写入的行数
+  onProgress: (handler: (rowCount: number) => void) =>
+    listen<number>('export-progress', (event) => handler(event.payload)),
+};
+
+// Backup APIs（备份文件为带校验的 zip 压缩包）
+export const backupAPI = {
+  createBackup: (request: CreateBackupRequest) =>
+    invoke<BackupResult>('create_backup', { request }),
+
+  restoreBackup: (request: RestoreBackupRequest) => {
+    useCacheStore.getState().clearCache();
+    return invoke<RestoreResult>('restore_backup', { request });
+  },
+
+  onProgress: (handler: (progress: BackupProgress) => void) =>
+    listen<BackupProgress>('backup-progress', (event) => handler(event.payload)),
+};
+
+// Audit APIs（只读，供合规审查）
+export const auditAPI = {
+  getAuditLog: (query: AuditLogQuery) =>
+    invoke<PaginatedAuditLog>('get_audit_log', { query }),
+};
@@ -1,12 +1,114 @@
 // Common types
+// employee 只看自己；manager 查看并审批所负责部门的成员；hr 修改考勤但不能改配置；super_admin 管理全部
+export type Role = 'employee' | 'manager' | 'hr' | 'super_admin';
+
+// 自助注册的账号审批通过前不能登录
+export type ApprovalStatus = 'pending' | 'approved' | 'rejected';
+
 export interface User {
   id: number;
   username: string;
   full_name: string;
-  is_admin: boolean;
+  is_admin: boolean;  // 兼容旧版本：仅超级管理员为 true
+  role: Role;
+  must_change_password: boolean;  // 新开户、重置或密码过期的账号登录后须修改密码
+  password_changed_at: string | null;
+  temporary_password_expires_at: string | null;  // 管理员重置的临时密码过期时间
+  totp_enabled: boolean;  // 已启用两步验证
+  two_factor_setup_required: boolean;  // 公司要求管理员启用两步验证，启用前不能进行其他操作
+  approval_status: ApprovalStatus;
+  is_active: boolean;  // 停用的账号不能登录，历史记录保留
+  deactivated_at: string | null;
+  deactivation_reason: string | null;
+  department_id: number | null;
+  employee_no: string | null;
+  hire_date: string | null;         // 入职日期之前不计缺勤
+  termination_date: string | null;  // 离职日期之后不计缺勤
+  email: string | null;
+  phone: string | null;
+  default_schedule_rule_id: number | null;  // 上班/下班规则，代替部门和全公司排班
   created_at: string;
 }
 
+export interface CreateUserRequest {
+  username: string;
+  full_name: string;
+  role?: Role;
+  department_id?: number;
+  employee_no?: string;
+  hire_date?: string;
+  termination_date?: string;
+  email?: string;
+  phone?: string;
+  default_schedule_rule_id?: number;
+}
+
+export interface CreatedUser {
+  user: User;
+  initial_password: string;  // 只显示一次，首次登录须修改
+}
+
+// 不传表示不修改，传 null 表示清空
+export interface UpdateUserProfileRequest {
+  user_id: number;
+  full_name?: string;
+  employee_no?: string | null;
+  hire_date?: string | null;
+  termination_date?: string | null;
+  email?: string | null;
+  phone?: string | null;
+  default_schedule_rule_id?: number | null;
+}
+
+// 员工自助可修改的字段
+export interface UpdateMyProfileRequest {
+  email?: string | null;
+  phone?: string | null;
+}
+
+export interface PasswordPolicy {
+  min_length: number;
+  require_uppercase: boolean;
+  require_lowercase: boolean;
+  require_digit: boolean;
+  require_symbol: boolean;
+  max_age_days: number;  // 0 表示不过期
+  temporary_password_hours: number;
+}
+
+export interface ChangePasswordRequest {
+  old_password: string;
+  new_password: string;
+}
+
+export interface PasswordResetResult {
+  user_id: number;
+  username: string;
+  temporary_password: string;  // 只显示一次，登录后须修改
+  expires_at: string;
+}
+
+export interface Department {
+  id: number;
+  name: string;
+  parent_id: number | null;  // 有上级部门时为团队
+  manager_id: number | null;  // 部门负责人（团队经理）
+  created_at: string;
+}
+
+export interface CreateDepartmentRequest {
+  name: string;
+  parent_id?: number;
+  manager_id?: number;
+}
+
+export interface UpdateDepartmentRequest {
+  id: number;
+  name?: string;
+  parent_id?: number | null;  // null 表示改为顶级部门
+  manager_id?: number | null;  // null 表示取消负责人
+}
+
 export interface ActionType {
   id: number;
   name: string;
@@ -16,7 +118,9 @@ export interface ActionType {
   action_role: number; // 1=进程开始, 2=进程结束, 3=事件开始, 4=事件结束
   requires_pair: boolean;
   pair_action_id: number | null;
+  is_paid_break: boolean;  // 仅用于临时事件：带薪休息不扣除工作时长
   is_active: boolean;
+  archived_at: string | null;  // 已归档：停用且保留历史引用
   created_at: string;
 }
 
@@ -28,13 +132,23 @@ export interface CheckIn {
   status: 'ongoing' | 'completed' | 'overtime';
   pair_check_in_id: number | null;
   duration_minutes: number | null;
+  net_minutes: number | null;
   note: string | null;
   is_late: boolean;
   is_early_leave: boolean;
+  is_overtime_break: boolean;
+  overtime_minutes: number | null;
+  overtime_category: OvertimeCategory | null;
   is_manual: boolean;
   created_at: string;
 }
 
+export type OvertimeCategory = 'workday' | 'rest_day' | 'holiday';
+
+export interface CheckInResponse extends CheckIn {
+  warnings: string[];
+}
+
 export interface TimeRule {
   id: number;
   rule_name: string;
@@ -42,20 +156,118 @@ export interface TimeRule {
   expected_start_time?: string;      // 仅用于主进程（上班/下班）
   expected_end_time?: string;        // 仅用于主进程（上班/下班）
   max_duration_minutes?: number;     // 仅用于临时事件（上厕所/午餐等）
+  daily_max_total_minutes?: number;  // 仅用于临时事件：每日累计时长上限
+  daily_max_count?: number;          // 仅用于临时事件：每日次数上限
+  rest_days?: number[];              // 仅用于主进程：每周休息日（1=周一 … 7=周日）
   timezone: string;
   is_active: boolean;
+  archived_at: string | null;  // 已归档：停用且保留历史引用
+  department_id: number | null;  // 部门排班；为空时适用于全公司
+  created_at: string;
+}
+
+export interface UpdateActionTypeRequest {
+  id: number;
+  name?: string;
+  action_role?: number;
+  requires_pair?: boolean;
+  pair_action_id?: number | null;  // null 表示取消配对
+  button_text?: string;
+  button_color?: string;
+  display_order?: number;
+  is_paid_break?: boolean;
+  is_active?: boolean;
+}
+
+export interface ConfigIssue {
+  entity: 'action_type' | 'time_rule';
+  id: number;
+  name: string;
+  message: string;
+}
+
+export interface MergeActionTypesResult {
+  moved_check_ins: number;
+  moved_rules: number;
+  repointed_action_types: number;
+}
+
+export interface Holiday {
+  id: number;
+  holiday_date: string;
+  name: string;
+  is_workday: boolean;  // 调休上班日
+  created_at: string;
+}
+
+export interface Leave {
+  id: number;
+  user_id: number;
+  start_date: string;
+  end_date: string;
+  leave_type: string;  // 例如：年假、病假、事假
+  note: string | null;
   created_at: string;
 }
 
+export interface SystemConfig {
+  id: number;
+  config_key: string;
+  config_value: string;
+  config_desc: string | null;
+  created_at: string | null;
+  updated_at: string | null;
+}
+
 export interface LoginRequest {
   username: string;
   password: string;
+  device_id?: string;  // 按设备限制登录失败次数
+}
+
+// 登录失败计数，达到次数后临时锁定
+export interface LoginThrottle {
+  id: number;
+  scope: 'user' | 'device';
+  key: string;
+  failed_count: number;
+  last_failed_at: string | null;
+  locked_until: string | null;
+  created_at: string;
 }
 
 export interface RegisterRequest {
   username: string;
   password: string;
   full_name: string;
+  email?: string;        // 开放注册限制邮箱域名时必填
+  invite_code?: string;
+}
+
+// open 开放注册；invite_only 须邀请码；closed 只能由管理员创建账号
+export interface RegistrationSettings {
+  mode: 'open' | 'invite_only' | 'closed';
+  email_domains: string[];
+  requires_approval: boolean;  // 凭邀请码注册的账号无须审批
+}
+
+export interface InviteCode {
+  id: number;
+  code: string;
+  department_id: number | null;  // 注册的员工加入该部门
+  max_uses: number | null;       // 为空表示有效期内不限次数
+  use_count: number;
+  expires_at: string | null;
+  revoked: boolean;
+  created_by: number | null;
+  created_at: string;
+}
+
+// 使用次数和有效天数至少填一项
+export interface CreateInviteCodeRequest {
+  department_id?: number;
+  max_uses?: number;
+  expires_in_days?: number;
 }
 
 export interface LoginResponse {
@@ -63,12 +275,45 @@ export interface LoginResponse {
   token: string;
 }
 
+// 启用两步验证的账号密码正确后还须调用 verifyLoginCode
+export type LoginResult =
+  | ({ status: 'signed_in' } & LoginResponse)
+  | { status: 'two_factor_required' };
+
+export interface TwoFactorSetup {
+  secret: string;
+  provisioning_uri: string;
+  qr_svg: string;
+}
+
+export interface TwoFactorEnabled {
+  user: User;
+  recovery_codes: string[];  // 只显示一次
+}
+
 export interface CheckInRequest {
   user_id: number;
   action_type_id: number;
 }
 
-export interface CheckInStatistics {
+export interface AttendanceSummary {
+  scheduled_days: number;
+  attended_days: number;
+  absent_days: number;
+  leave_days: number;
+  late_days: number;
+  early_leave_days: number;
+  late_minutes: number;
+  early_leave_minutes: number;
+  net_work_minutes: number;
+  break_minutes: number;
+  overtime_minutes: number;
+  workday_overtime_minutes: number;
+  rest_day_overtime_minutes: number;
+  holiday_overtime_minutes: number;
+}
+
+export interface CheckInStatistics extends AttendanceSummary {
   total_days: number;
   late_count: number;
   on_time_count: number;
@@ -76,6 +321,68 @@ export interface CheckInStatistics {
   average_work_minutes: number;
 }
 
+export type DayStatus =
+  | 'present'
+  | 'late'
+  | 'early_leave'
+  | 'absent'
+  | 'leave'
+  | 'holiday'
+  | 'rest_day'
+  | 'upcoming'
+  | 'not_employed';  // 入职前或离职后
+
+export interface UserAttendanceRow {
+  user_id: number;
+  username: string;
+  full_name: string;
+  is_active: boolean;
+  statuses: DayStatus[];  // 与 dates 一一对应
+  totals: AttendanceSummary;
+}
+
+export interface AttendanceDashboard {
+  start_date: string;
+  end_date: string;
+  dates: string[];
+  rows: UserAttendanceRow[];
+}
+
+export type PresenceState = 'working' | 'on_break';
+
+export interface UserPresence {
+  user_id: number;
+  username: string;
+  full_name: string;
+  state: PresenceState;
+  clocked_in_at: string;
+  clocked_in_minutes: number;
+  break_action_type_id: number | null;
+  break_name: string | null;
+  break_started_at: string | null;
+  break_minutes: number | null;
+  is_break_overdue: boolean;
+}
+
+export interface PresenceOverview {
+  generated_at: string;
+  working_count: number;
+  on_break_count: number;
+  users: UserPresence[];
+}
+
+// Realtime 推送的打卡记录变更
+export interface RealtimeStatus {
+  connected: boolean;
+  // 连接失败、断开或推送数据无法解析的原因
+  error: string | null;
+}
+
+// 只通知打卡记录有变化，需要通过接口重新加载
+export interface CheckInChange {
+  event_type: 'INSERT' | 'UPDATE';
+}
+
 export interface PaginatedCheckIns {
   data: CheckIn[];
   total: number;
@@ -83,3 +390,263 @@ export interface PaginatedCheckIns {
   page_size: number;
   total_pages: number;
 }
+
+// 导出
+export type ExportFormat = 'csv' | 'xlsx';
+export type ExportLocale = 'zh' | 'en';
+
+export type CheckInExportColumn =
+  | 'id'
+  | 'username'
+  | 'full_name'
+  | 'action_name'
+  | 'check_time'
+  | 'status'
+  | 'duration_minutes'
+  | 'net_minutes'
+  | 'is_late'
+  | 'is_early_leave'
+  | 'is_overtime_break'
+  | 'overtime_minutes'
+  | 'overtime_category'
+  | 'is_manual'
+  | 'note';
+
+export type AttendanceExportColumn =
+  | 'user_id'
+  | 'username'
+  | 'full_name'
+  | 'scheduled_days'
+  | 'attended_days'
+  | 'absent_days'
+  | 'leave_days'
+  | 'late_days'
+  | 'late_minutes'
+  | 'early_leave_days'
+  | 'early_leave_minutes'
+  | 'net_work_minutes'
+  | 'break_minutes'
+  | 'overtime_minutes'
+  | 'workday_overtime_minutes'
+  | 'rest_day_overtime_minutes'
+  | 'holiday_overtime_minutes'
+  | 'period'
+  | 'net_work_hours'
+  | 'overtime_hours'
+  | 'workday_overtime_hours'
+  | 'rest_day_overtime_hours'
+  | 'holiday_overtime_hours';
+
+// 工资表模板列，header 为空时使用默认表头
+export interface TimesheetColumn {
+  column: AttendanceExportColumn;
+  header: string | null;
+}
+
+export interface ExportCheckInsRequest {
+  path: string;
+  format: ExportFormat;
+  locale?: ExportLocale;
+  columns?: CheckInExportColumn[];
+  start_date?: string;
+  end_date?: string;
+  user_id?: number;
+  department_id?: number;  // 包含下属团队
+}
+
+export interface ExportAttendanceRequest {
+  path: string;
+  format: ExportFormat;
+  locale?: ExportLocale;
+  columns?: AttendanceExportColumn[];
+  start_date: string;
+  end_date: string;
+  department_id?: number;
+}
+
+export interface ExportTimesheetRequest {
+  path: string;
+  format: ExportFormat;
+  locale?: ExportLocale;
+  year: number;
+  month: number;  // 1-12
+  template?: TimesheetColumn[];
+  department_id?: number;
+}
+
+export interface PdfReportRequest {
+  path: string;
+  year: number;
+  month: number;  // 1-12
+  user_id?: number;  // 员工报表必填
+  department_id?: number;  // 仅用于团队报表
+  locale?: ExportLocale;
+}
+
+export interface ExportResult {
+  path: string;
+  row_count: number;
+}
+
+// 历史打卡导入
+export interface ImportCheckInsRequest {
+  content: string;  // CSV：username, action, timestamp, note
+  dry_run: boolean;
+  batch_size?: number;
+}
+
+export interface ImportRowError {
+  line: number;
+  username: string;
+  message: string;
+}
+
+export interface ImportReport {
+  dry_run: boolean;
+  total_rows: number;
+  valid_rows: number;
+  imported_rows: number;
+  errors: ImportRowError[];
+  batch_error: string | null;
+  audit_error: string | null;  // 记录已写入，仅审计日志写入失败
+}
+
+export interface CredentialsSheet {
+  path: string;
+  format: ExportFormat;
+  locale?: ExportLocale;
+}
+
+export interface ProvisionUsersRequest {
+  content: string;  // CSV：username, full_name, is_admin；或 JSON 数组
+  format: 'csv' | 'json';
+  dry_run: boolean;
+  password_length?: number;
+  credentials?: CredentialsSheet;
+}
+
+export interface ProvisionedCredential {
+  user_id: number;
+  username: string;
+  full_name: string;
+  initial_password: string;
+}
+
+export interface ProvisionReport {
+  dry_run: boolean;
+  total_rows: number;
+  valid_rows: number;
+  created: ProvisionedCredential[];
+  errors: ImportRowError[];
+  credentials_path: string | null;
+  credentials_error: string | null;
+  audit_error: string | null;  // 账号已创建，仅审计日志写入失败
+}
+
+// 公司配置导出 / 导入
+export type ConfigFileFormat = 'json' | 'toml';
+
+export interface ImportCompanyConfigRequest {
+  content: string;
+  format: ConfigFileFormat;
+  dry_run: boolean;
+}
+
+export interface ConfigFieldChange {
+  field: string;
+  current: unknown;
+  imported: unknown;
+}
+
+export interface ConfigChange {
+  section: 'action_type' | 'time_rule' | 'holiday' | 'setting';
+  name: string;
+  kind: 'create' | 'update' | 'unchanged';
+  fields: ConfigFieldChange[];
+}
+
+export interface ConfigImportReport {
+  dry_run: boolean;
+  changes: ConfigChange[];
+  issues: ConfigIssue[];
+  errors: string[];
+  applied: {
+    action_types: number;
+    time_rules: number;
+    holidays: number;
+    settings: number;
+  } | null;
+  audit_error: string | null;  // 配置已写入，仅审计日志写入失败
+}
+
+// 本地备份与恢复
+export interface CreateBackupRequest {
+  path: string;
+  include_secrets?: boolean;  // 密码哈希和两步验证密钥，默认不包含
+}
+
+export type RestoreTarget =
+  | { type: 'supabase' }  // 仅限空项目
+  | { type: 'sqlite'; path: string };
+
+export interface RestoreBackupRequest {
+  path: string;
+  target: RestoreTarget;
+}
+
+export interface BackupTable {
+  name: string;
+  rows: number;
+  sha256: string;
+  columns: string[];
+}
+
+export interface BackupResult {
+  path: string;
+  tables: BackupTable[];
+}
+
+export interface RestoreResult {
+  tables: BackupTable[];
+  users_without_password: number;
+  audit_error: string | null;  // 数据已恢复，仅审计日志写入失败
+}
+
+export interface BackupProgress {
+  phase: 'backup' | 'verify' | 'restore';
+  table: string;
+  rows: number;
+  total: number;
+}
+
+// 审计日志
+export interface AuditLogEntry {
+  id: number;
+  actor_id: number | null;
+  actor_name: string | null;
+  action: string;  // 命令名，例如 update_check_in
+  target_table: string;
+  target_id: number | null;
+  before_data: Record<string, unknown> | null;
+  after_data: Record<string, unknown> | null;
+  created_at: string;
+}
+
+export interface AuditLogQuery {
+  actor_id?: number;
+  action?: string;
+  target_table?: string;
+  target_id?: number;
+  start_date?: string;
+  end_date?: string;
+  page: number;
+  page_size: number;  // 最大 200
+}
+
+export interface PaginatedAuditLog {
+  data: AuditLogEntry[];
+  total: number;
+  page: number;
+  page_size: number;
+  total_pages: number;
+}
@@ -0,0 +1,23 @@
+-- 临时事件每日额度：累计时长上限、次数上限
+ALTER TABLE time_rules
+    ADD COLUMN IF NOT EXISTS daily_max_total_minutes INTEGER,
+    ADD COLUMN IF NOT EXISTS daily_max_count INTEGER;
+
+-- 临时事件超时/超额使用独立标记，不再占用 is_late
+ALTER TABLE check_ins
+    ADD COLUMN IF NOT EXISTS is_overtime_break BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- 迁移历史数据：回座记录上的 is_late 实际表示超时
+UPDATE check_ins c
+SET is_overtime_break = TRUE,
+    is_late = FALSE
+FROM action_types a
+WHERE c.action_type_id = a.id
+  AND a.action_role = 4
+  AND c.is_late = TRUE;
+
+UPDATE check_ins s
+SET is_overtime_break = TRUE
+FROM check_ins e
+WHERE s.pair_check_in_id = e.id
+  AND e.is_overtime_break = TRUE;
@@ -0,0 +1,34 @@
+-- 主进程规则的每周休息日（1=周一 … 7=周日）
+ALTER TABLE time_rules
+    ADD COLUMN IF NOT EXISTS rest_days INTEGER[];
+
+-- 节假日日历；is_workday = TRUE 表示调休上班日
+CREATE TABLE IF NOT EXISTS holidays (
+    id SERIAL PRIMARY KEY,
+    holiday_date DATE NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    is_workday BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT NOW()
+);
+
+-- 加班记录在下班记录上
+ALTER TABLE check_ins
+    ADD COLUMN IF NOT EXISTS overtime_minutes INTEGER,
+    ADD COLUMN IF NOT EXISTS overtime_category TEXT
+        CHECK (overtime_category IN ('workday', 'rest_day', 'holiday'));
+
+-- 加班取整规则
+-- 建唯一索引前清除重复的配置项，每个键保留最新写入的一条
+DELETE FROM system_config older
+USING system_config newer
+WHERE older.config_key = newer.config_key
+  AND older.id < newer.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS system_config_config_key_idx
+    ON system_config (config_key);
+
+INSERT INTO system_config (config_key, config_value, config_desc) VALUES
+    ('overtime_rounding_minutes', '30', '加班取整单位（分钟）'),
+    ('overtime_rounding_mode', 'floor', '加班取整方式：floor / nearest / ceil'),
+    ('overtime_min_minutes', '30', '不足该时长不计加班（分钟）')
+ON CONFLICT (config_key) DO NOTHING;
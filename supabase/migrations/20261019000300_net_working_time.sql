@@ -0,0 +1,7 @@
+-- 临时事件是否带薪（带薪休息不从工作时长中扣除）
+ALTER TABLE action_types
+    ADD COLUMN IF NOT EXISTS is_paid_break BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- 下班记录上的净工作时长（扣除不带薪的临时事件）
+ALTER TABLE check_ins
+    ADD COLUMN IF NOT EXISTS net_minutes INTEGER;
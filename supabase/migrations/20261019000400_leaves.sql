@@ -0,0 +1,16 @@
+-- 请假记录（请假日不计为缺勤）
+CREATE TABLE IF NOT EXISTS leaves (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    leave_type VARCHAR(50) NOT NULL,
+    note TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT leaves_date_range CHECK (end_date >= start_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_leaves_user_dates ON leaves(user_id, start_date, end_date);
+
+-- 团队看板按时间范围拉取全部打卡记录
+CREATE INDEX IF NOT EXISTS idx_check_ins_check_time ON check_ins(check_time);
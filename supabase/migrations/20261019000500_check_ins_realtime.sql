@@ -0,0 +1,15 @@
+-- 将打卡记录加入 Realtime 发布，管理端可实时接收新增/更新
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_publication_tables
+        WHERE pubname = 'supabase_realtime'
+          AND schemaname = 'public'
+          AND tablename = 'check_ins'
+    ) THEN
+        ALTER PUBLICATION supabase_realtime ADD TABLE check_ins;
+    END IF;
+END $$;
+
+-- 在岗查询按状态过滤进行中的记录
+CREATE INDEX IF NOT EXISTS idx_check_ins_status ON check_ins(status);
@@ -0,0 +1,2 @@
+-- 批量开户的账号首次登录须修改初始密码
+ALTER TABLE users ADD COLUMN IF NOT EXISTS must_change_password BOOLEAN NOT NULL DEFAULT FALSE;
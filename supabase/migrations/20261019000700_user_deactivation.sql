@@ -0,0 +1,4 @@
+-- 停用用户代替物理删除：停用后不能登录，历史打卡仍保留在报表中
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_active BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deactivated_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deactivation_reason TEXT;
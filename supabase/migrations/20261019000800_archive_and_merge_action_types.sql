@@ -0,0 +1,61 @@
+-- 被引用的打卡类型和时间规则不能删除，改为归档（停用并记录归档时间）
+ALTER TABLE action_types ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE time_rules ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE;
+
+-- 将打卡类型 source 合并到 target：在同一事务中改写历史打卡、配对关系和时间规则，最后删除 source
+CREATE OR REPLACE FUNCTION merge_action_types(source_id INTEGER, target_id INTEGER)
+RETURNS JSON
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    source_role INTEGER;
+    target_role INTEGER;
+    target_has_rule BOOLEAN;
+    moved_check_ins INTEGER;
+    moved_rules INTEGER;
+    repointed_action_types INTEGER;
+BEGIN
+    IF source_id = target_id THEN
+        RAISE EXCEPTION '不能将打卡类型合并到自身';
+    END IF;
+
+    SELECT action_role INTO source_role FROM action_types WHERE id = source_id FOR UPDATE;
+    SELECT action_role INTO target_role FROM action_types WHERE id = target_id FOR UPDATE;
+    IF source_role IS NULL OR target_role IS NULL THEN
+        RAISE EXCEPTION '打卡类型不存在';
+    END IF;
+    IF source_role <> target_role THEN
+        RAISE EXCEPTION '只能合并角色相同的打卡类型';
+    END IF;
+
+    UPDATE check_ins SET action_type_id = target_id WHERE action_type_id = source_id;
+    GET DIAGNOSTICS moved_check_ins = ROW_COUNT;
+
+    UPDATE action_types SET pair_action_id = target_id
+    WHERE pair_action_id = source_id AND id <> target_id;
+    GET DIAGNOSTICS repointed_action_types = ROW_COUNT;
+
+    UPDATE action_types SET pair_action_id = NULL
+    WHERE id = target_id AND pair_action_id = source_id;
+
+    -- target 已有生效规则时，迁移过来的规则归档，避免同一类型出现两条生效规则
+    SELECT EXISTS (
+        SELECT 1 FROM time_rules WHERE action_type_id = target_id AND is_active
+    ) INTO target_has_rule;
+
+    UPDATE time_rules
+    SET action_type_id = target_id,
+        is_active = is_active AND NOT target_has_rule,
+        archived_at = CASE WHEN target_has_rule THEN COALESCE(archived_at, NOW()) ELSE archived_at END
+    WHERE action_type_id = source_id;
+    GET DIAGNOSTICS moved_rules = ROW_COUNT;
+
+    DELETE FROM action_types WHERE id = source_id;
+
+    RETURN json_build_object(
+        'moved_check_ins', moved_check_ins,
+        'moved_rules', moved_rules,
+        'repointed_action_types', repointed_action_types
+    );
+END;
+$$;
@@ -0,0 +1,124 @@
+-- 导入公司配置文档：在同一事务中按名称匹配打卡类型、按类型和名称匹配时间规则，
+-- 配对关系和规则引用按名称重新映射为本库的 ID；只新增或更新，不删除
+CREATE OR REPLACE FUNCTION import_company_config(config JSON)
+RETURNS JSON
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    item JSON;
+    setting RECORD;
+    type_id INTEGER;
+    rule_id INTEGER;
+    action_type_count INTEGER := 0;
+    time_rule_count INTEGER := 0;
+    holiday_count INTEGER := 0;
+    setting_count INTEGER := 0;
+BEGIN
+    FOR item IN SELECT * FROM json_array_elements(COALESCE(config->'action_types', '[]'::JSON)) LOOP
+        SELECT id INTO type_id FROM action_types
+        WHERE lower(trim(name)) = lower(trim(item->>'name')) AND archived_at IS NULL
+        ORDER BY id LIMIT 1;
+
+        IF type_id IS NULL THEN
+            INSERT INTO action_types (
+                name, button_text, button_color, display_order, action_role,
+                requires_pair, is_paid_break, is_active
+            ) VALUES (
+                item->>'name', item->>'button_text', item->>'button_color',
+                (item->>'display_order')::INTEGER, (item->>'action_role')::INTEGER,
+                (item->>'requires_pair')::BOOLEAN,
+                COALESCE((item->>'is_paid_break')::BOOLEAN, FALSE),
+                COALESCE((item->>'is_active')::BOOLEAN, TRUE)
+            );
+        ELSE
+            UPDATE action_types SET
+                name = item->>'name',
+                button_text = item->>'button_text',
+                button_color = item->>'button_color',
+                display_order = (item->>'display_order')::INTEGER,
+                action_role = (item->>'action_role')::INTEGER,
+                requires_pair = (item->>'requires_pair')::BOOLEAN,
+                is_paid_break = COALESCE((item->>'is_paid_break')::BOOLEAN, FALSE),
+                is_active = COALESCE((item->>'is_active')::BOOLEAN, TRUE)
+            WHERE id = type_id;
+        END IF;
+        action_type_count := action_type_count + 1;
+    END LOOP;
+
+    -- 全部类型写入后再映射配对 ID，允许引用文档中排在后面的类型
+    FOR item IN SELECT * FROM json_array_elements(COALESCE(config->'action_types', '[]'::JSON)) LOOP
+        UPDATE action_types SET pair_action_id = (
+            SELECT id FROM action_types
+            WHERE lower(trim(name)) = lower(trim(item->>'pair_action')) AND archived_at IS NULL
+            ORDER BY id LIMIT 1
+        )
+        WHERE id = (
+            SELECT id FROM action_types
+            WHERE lower(trim(name)) = lower(trim(item->>'name')) AND archived_at IS NULL
+            ORDER BY id LIMIT 1
+        );
+    END LOOP;
+
+    FOR item IN SELECT * FROM json_array_elements(COALESCE(config->'time_rules', '[]'::JSON)) LOOP
+        SELECT id INTO type_id FROM action_types
+        WHERE lower(trim(name)) = lower(trim(item->>'action_type')) AND archived_at IS NULL
+        ORDER BY id LIMIT 1;
+        IF type_id IS NULL THEN
+            RAISE EXCEPTION '时间规则 % 适用的打卡类型 % 不存在', item->>'rule_name', item->>'action_type';
+        END IF;
+
+        SELECT id INTO rule_id FROM time_rules
+        WHERE action_type_id = type_id AND rule_name = item->>'rule_name' AND archived_at IS NULL
+        ORDER BY id LIMIT 1;
+
+        IF rule_id IS NULL THEN
+            INSERT INTO time_rules (rule_name, action_type_id, timezone, is_active)
+            VALUES (item->>'rule_name', type_id, COALESCE(item->>'timezone', 'Asia/Phnom_Penh'), TRUE)
+            RETURNING id INTO rule_id;
+        END IF;
+
+        UPDATE time_rules SET
+            rule_name = item->>'rule_name',
+            expected_start_time = (item->>'expected_start_time')::TIME,
+            expected_end_time = (item->>'expected_end_time')::TIME,
+            max_duration_minutes = (item->>'max_duration_minutes')::INTEGER,
+            daily_max_total_minutes = (item->>'daily_max_total_minutes')::INTEGER,
+            daily_max_count = (item->>'daily_max_count')::INTEGER,
+            rest_days = CASE
+                WHEN json_typeof(item->'rest_days') = 'array'
+                THEN ARRAY(SELECT json_array_elements_text(item->'rest_days')::INTEGER)
+            END,
+            timezone = COALESCE(item->>'timezone', 'Asia/Phnom_Penh'),
+            is_active = COALESCE((item->>'is_active')::BOOLEAN, TRUE)
+        WHERE id = rule_id;
+        time_rule_count := time_rule_count + 1;
+    END LOOP;
+
+    FOR item IN SELECT * FROM json_array_elements(COALESCE(config->'holidays', '[]'::JSON)) LOOP
+        INSERT INTO holidays (holiday_date, name, is_workday)
+        VALUES (
+            (item->>'holiday_date')::DATE,
+            item->>'name',
+            COALESCE((item->>'is_workday')::BOOLEAN, FALSE)
+        )
+        ON CONFLICT (holiday_date) DO UPDATE
+        SET name = EXCLUDED.name, is_workday = EXCLUDED.is_workday;
+        holiday_count := holiday_count + 1;
+    END LOOP;
+
+    FOR setting IN SELECT * FROM json_each_text(COALESCE(config->'settings', '{}'::JSON)) LOOP
+        INSERT INTO system_config (config_key, config_value, updated_at)
+        VALUES (setting.key, setting.value, NOW())
+        ON CONFLICT (config_key) DO UPDATE
+        SET config_value = EXCLUDED.config_value, updated_at = NOW();
+        setting_count := setting_count + 1;
+    END LOOP;
+
+    RETURN json_build_object(
+        'action_types', action_type_count,
+        'time_rules', time_rule_count,
+        'holidays', holiday_count,
+        'settings', setting_count
+    );
+END;
+$$;
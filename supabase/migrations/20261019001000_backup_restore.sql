@@ -0,0 +1,28 @@
+-- 从备份恢复时保留了原有 ID，恢复完成后把各表的自增序列推进到当前最大 ID 之后，
+-- 否则之后新增的记录会与恢复的记录主键冲突
+CREATE OR REPLACE FUNCTION reset_id_sequences()
+RETURNS INTEGER
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    table_name TEXT;
+    sequence_name TEXT;
+    max_id BIGINT;
+    reset_count INTEGER := 0;
+BEGIN
+    FOREACH table_name IN ARRAY ARRAY[
+        'users', 'action_types', 'time_rules', 'holidays', 'leaves', 'system_config', 'check_ins'
+    ] LOOP
+        sequence_name := pg_get_serial_sequence(table_name, 'id');
+        IF sequence_name IS NULL THEN
+            CONTINUE;
+        END IF;
+
+        EXECUTE format('SELECT COALESCE(MAX(id), 0) FROM %I', table_name) INTO max_id;
+        PERFORM setval(sequence_name, max_id + 1, false);
+        reset_count := reset_count + 1;
+    END LOOP;
+
+    RETURN reset_count;
+END;
+$$;
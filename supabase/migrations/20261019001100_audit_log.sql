@@ -0,0 +1,64 @@
+-- 审计日志：记录管理与打卡操作的执行人、目标及变更前后的数据，只允许追加
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    actor_id INTEGER,
+    actor_name VARCHAR(100),
+    action VARCHAR(100) NOT NULL,
+    target_table VARCHAR(50) NOT NULL,
+    target_id INTEGER,
+    before_data JSONB,
+    after_data JSONB,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log(created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor ON audit_log(actor_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log(target_table, target_id);
+
+CREATE OR REPLACE FUNCTION reject_audit_log_change()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+BEGIN
+    RAISE EXCEPTION '审计日志只允许追加，不能修改或删除';
+END;
+$$;
+
+DROP TRIGGER IF EXISTS audit_log_append_only ON audit_log;
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION reject_audit_log_change();
+
+DROP TRIGGER IF EXISTS audit_log_no_truncate ON audit_log;
+CREATE TRIGGER audit_log_no_truncate
+    BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT EXECUTE FUNCTION reject_audit_log_change();
+
+-- 审计日志随备份一起恢复，恢复后同样需要推进序列
+CREATE OR REPLACE FUNCTION reset_id_sequences()
+RETURNS INTEGER
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    table_name TEXT;
+    sequence_name TEXT;
+    max_id BIGINT;
+    reset_count INTEGER := 0;
+BEGIN
+    FOREACH table_name IN ARRAY ARRAY[
+        'users', 'action_types', 'time_rules', 'holidays', 'leaves', 'system_config', 'check_ins',
+        'audit_log'
+    ] LOOP
+        sequence_name := pg_get_serial_sequence(table_name, 'id');
+        IF sequence_name IS NULL THEN
+            CONTINUE;
+        END IF;
+
+        EXECUTE format('SELECT COALESCE(MAX(id), 0) FROM %I', table_name) INTO max_id;
+        PERFORM setval(sequence_name, max_id + 1, false);
+        reset_count := reset_count + 1;
+    END LOOP;
+
+    RETURN reset_count;
+END;
+$$;
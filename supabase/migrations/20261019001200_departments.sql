@@ -0,0 +1,236 @@
+-- 部门与团队：团队是有上级部门的部门，每个部门可指定一名负责人
+CREATE TABLE IF NOT EXISTS departments (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    parent_id INTEGER REFERENCES departments(id),
+    manager_id INTEGER REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT departments_not_own_parent CHECK (parent_id IS NULL OR parent_id <> id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_departments_parent ON departments(parent_id);
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS department_id INTEGER REFERENCES departments(id);
+CREATE INDEX IF NOT EXISTS idx_users_department ON users(department_id);
+
+-- 部门排班：department_id 为空的规则适用于全公司，部门规则优先于上级部门和公司规则
+ALTER TABLE time_rules ADD COLUMN IF NOT EXISTS department_id INTEGER REFERENCES departments(id);
+CREATE INDEX IF NOT EXISTS idx_time_rules_department ON time_rules(department_id);
+
+-- 公司配置文档只包含全公司规则，导入时不能覆盖同名的部门规则
+CREATE OR REPLACE FUNCTION import_company_config(config JSON)
+RETURNS JSON
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    item JSON;
+    setting RECORD;
+    type_id INTEGER;
+    rule_id INTEGER;
+    action_type_count INTEGER := 0;
+    time_rule_count INTEGER := 0;
+    holiday_count INTEGER := 0;
+    setting_count INTEGER := 0;
+BEGIN
+    FOR item IN SELECT * FROM json_array_elements(COALESCE(config->'action_types', '[]'::JSON)) LOOP
+        SELECT id INTO type_id FROM action_types
+        WHERE lower(trim(name)) = lower(trim(item->>'name')) AND archived_at IS NULL
+        ORDER BY id LIMIT 1;
+
+        IF type_id IS NULL THEN
+            INSERT INTO action_types (
+                name, button_text, button_color, display_order, action_role,
+                requires_pair, is_paid_break, is_active
+            ) VALUES (
+                item->>'name', item->>'button_text', item->>'button_color',
+                (item->>'display_order')::INTEGER, (item->>'action_role')::INTEGER,
+                (item->>'requires_pair')::BOOLEAN,
+                COALESCE((item->>'is_paid_break')::BOOLEAN, FALSE),
+                COALESCE((item->>'is_active')::BOOLEAN, TRUE)
+            );
+        ELSE
+            UPDATE action_types SET
+                name = item->>'name',
+                button_text = item->>'button_text',
+                button_color = item->>'button_color',
+                display_order = (item->>'display_order')::INTEGER,
+                action_role = (item->>'action_role')::INTEGER,
+                requires_pair = (item->>'requires_pair')::BOOLEAN,
+                is_paid_break = COALESCE((item->>'is_paid_break')::BOOLEAN, FALSE),
+                is_active = COALESCE((item->>'is_active')::BOOLEAN, TRUE)
+            WHERE id = type_id;
+        END IF;
+        action_type_count := action_type_count + 1;
+    END LOOP;
+
+    -- 全部类型写入后再映射配对 ID，允许引用文档中排在后面的类型
+    FOR item IN SELECT * FROM json_array_elements(COALESCE(config->'action_types', '[]'::JSON)) LOOP
+        UPDATE action_types SET pair_action_id = (
+            SELECT id FROM action_types
+            WHERE lower(trim(name)) = lower(trim(item->>'pair_action')) AND archived_at IS NULL
+            ORDER BY id LIMIT 1
+        )
+        WHERE id = (
+            SELECT id FROM action_types
+            WHERE lower(trim(name)) = lower(trim(item->>'name')) AND archived_at IS NULL
+            ORDER BY id LIMIT 1
+        );
+    END LOOP;
+
+    FOR item IN SELECT * FROM json_array_elements(COALESCE(config->'time_rules', '[]'::JSON)) LOOP
+        SELECT id INTO type_id FROM action_types
+        WHERE lower(trim(name)) = lower(trim(item->>'action_type')) AND archived_at IS NULL
+        ORDER BY id LIMIT 1;
+        IF type_id IS NULL THEN
+            RAISE EXCEPTION '时间规则 % 适用的打卡类型 % 不存在', item->>'rule_name', item->>'action_type';
+        END IF;
+
+        SELECT id INTO rule_id FROM time_rules
+        WHERE action_type_id = type_id AND rule_name = item->>'rule_name' AND archived_at IS NULL
+          AND department_id IS NULL
+        ORDER BY id LIMIT 1;
+
+        IF rule_id IS NULL THEN
+            INSERT INTO time_rules (rule_name, action_type_id, timezone, is_active)
+            VALUES (item->>'rule_name', type_id, COALESCE(item->>'timezone', 'Asia/Phnom_Penh'), TRUE)
+            RETURNING id INTO rule_id;
+        END IF;
+
+        UPDATE time_rules SET
+            rule_name = item->>'rule_name',
+            expected_start_time = (item->>'expected_start_time')::TIME,
+            expected_end_time = (item->>'expected_end_time')::TIME,
+            max_duration_minutes = (item->>'max_duration_minutes')::INTEGER,
+            daily_max_total_minutes = (item->>'daily_max_total_minutes')::INTEGER,
+            daily_max_count = (item->>'daily_max_count')::INTEGER,
+            rest_days = CASE
+                WHEN json_typeof(item->'rest_days') = 'array'
+                THEN ARRAY(SELECT json_array_elements_text(item->'rest_days')::INTEGER)
+            END,
+            timezone = COALESCE(item->>'timezone', 'Asia/Phnom_Penh'),
+            is_active = COALESCE((item->>'is_active')::BOOLEAN, TRUE)
+        WHERE id = rule_id;
+        time_rule_count := time_rule_count + 1;
+    END LOOP;
+
+    FOR item IN SELECT * FROM json_array_elements(COALESCE(config->'holidays', '[]'::JSON)) LOOP
+        INSERT INTO holidays (holiday_date, name, is_workday)
+        VALUES (
+            (item->>'holiday_date')::DATE,
+            item->>'name',
+            COALESCE((item->>'is_workday')::BOOLEAN, FALSE)
+        )
+        ON CONFLICT (holiday_date) DO UPDATE
+        SET name = EXCLUDED.name, is_workday = EXCLUDED.is_workday;
+        holiday_count := holiday_count + 1;
+    END LOOP;
+
+    FOR setting IN SELECT * FROM json_each_text(COALESCE(config->'settings', '{}'::JSON)) LOOP
+        INSERT INTO system_config (config_key, config_value, updated_at)
+        VALUES (setting.key, setting.value, NOW())
+        ON CONFLICT (config_key) DO UPDATE
+        SET config_value = EXCLUDED.config_value, updated_at = NOW();
+        setting_count := setting_count + 1;
+    END LOOP;
+
+    RETURN json_build_object(
+        'action_types', action_type_count,
+        'time_rules', time_rule_count,
+        'holidays', holiday_count,
+        'settings', setting_count
+    );
+END;
+$$;
+
+-- 合并打卡类型时按部门判断目标类型是否已有生效规则
+CREATE OR REPLACE FUNCTION merge_action_types(source_id INTEGER, target_id INTEGER)
+RETURNS JSON
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    source_role INTEGER;
+    target_role INTEGER;
+    moved_check_ins INTEGER;
+    moved_rules INTEGER;
+    repointed_action_types INTEGER;
+BEGIN
+    IF source_id = target_id THEN
+        RAISE EXCEPTION '不能将打卡类型合并到自身';
+    END IF;
+
+    SELECT action_role INTO source_role FROM action_types WHERE id = source_id FOR UPDATE;
+    SELECT action_role INTO target_role FROM action_types WHERE id = target_id FOR UPDATE;
+    IF source_role IS NULL OR target_role IS NULL THEN
+        RAISE EXCEPTION '打卡类型不存在';
+    END IF;
+    IF source_role <> target_role THEN
+        RAISE EXCEPTION '只能合并角色相同的打卡类型';
+    END IF;
+
+    UPDATE check_ins SET action_type_id = target_id WHERE action_type_id = source_id;
+    GET DIAGNOSTICS moved_check_ins = ROW_COUNT;
+
+    UPDATE action_types SET pair_action_id = target_id
+    WHERE pair_action_id = source_id AND id <> target_id;
+    GET DIAGNOSTICS repointed_action_types = ROW_COUNT;
+
+    UPDATE action_types SET pair_action_id = NULL
+    WHERE id = target_id AND pair_action_id = source_id;
+
+    -- target 在同一部门（或全公司）已有生效规则时，迁移过来的规则归档，避免同一范围出现两条生效规则
+    UPDATE time_rules AS moved
+    SET action_type_id = target_id,
+        is_active = moved.is_active AND NOT EXISTS (
+            SELECT 1 FROM time_rules AS existing
+            WHERE existing.action_type_id = target_id AND existing.is_active
+              AND existing.department_id IS NOT DISTINCT FROM moved.department_id
+        ),
+        archived_at = CASE
+            WHEN EXISTS (
+                SELECT 1 FROM time_rules AS existing
+                WHERE existing.action_type_id = target_id AND existing.is_active
+                  AND existing.department_id IS NOT DISTINCT FROM moved.department_id
+            ) THEN COALESCE(moved.archived_at, NOW())
+            ELSE moved.archived_at
+        END
+    WHERE moved.action_type_id = source_id;
+    GET DIAGNOSTICS moved_rules = ROW_COUNT;
+
+    DELETE FROM action_types WHERE id = source_id;
+
+    RETURN json_build_object(
+        'moved_check_ins', moved_check_ins,
+        'moved_rules', moved_rules,
+        'repointed_action_types', repointed_action_types
+    );
+END;
+$$;
+
+-- 部门随备份一起恢复
+CREATE OR REPLACE FUNCTION reset_id_sequences()
+RETURNS INTEGER
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    table_name TEXT;
+    sequence_name TEXT;
+    max_id BIGINT;
+    reset_count INTEGER := 0;
+BEGIN
+    FOREACH table_name IN ARRAY ARRAY[
+        'departments', 'users', 'action_types', 'time_rules', 'holidays', 'leaves', 'system_config', 'check_ins',
+        'audit_log'
+    ] LOOP
+        sequence_name := pg_get_serial_sequence(table_name, 'id');
+        IF sequence_name IS NULL THEN
+            CONTINUE;
+        END IF;
+
+        EXECUTE format('SELECT COALESCE(MAX(id), 0) FROM %I', table_name) INTO max_id;
+        PERFORM setval(sequence_name, max_id + 1, false);
+        reset_count := reset_count + 1;
+    END LOOP;
+
+    RETURN reset_count;
+END;
+$$;
@@ -0,0 +1,11 @@
+-- 用户角色：员工、团队经理、人事、超级管理员，代替 is_admin 的全有或全无
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL DEFAULT 'employee'
+    CHECK (role IN ('employee', 'manager', 'hr', 'super_admin'));
+
+-- 原有管理员升级为超级管理员
+UPDATE users SET role = 'super_admin' WHERE is_admin AND role = 'employee';
+
+CREATE INDEX IF NOT EXISTS idx_users_role ON users(role);
+
+COMMENT ON COLUMN users.is_admin IS '兼容旧版本：仅超级管理员为 true，由应用与 role 同步写入';
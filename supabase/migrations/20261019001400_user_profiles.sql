@@ -0,0 +1,17 @@
+-- 员工档案：工号、入职/离职日期、联系方式和默认排班
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS employee_no VARCHAR(50),
+    ADD COLUMN IF NOT EXISTS hire_date DATE,
+    ADD COLUMN IF NOT EXISTS termination_date DATE,
+    ADD COLUMN IF NOT EXISTS email VARCHAR(255),
+    ADD COLUMN IF NOT EXISTS phone VARCHAR(50),
+    -- 上班/下班规则，代替部门和全公司的排班
+    ADD COLUMN IF NOT EXISTS default_schedule_rule_id INTEGER REFERENCES time_rules(id) ON DELETE SET NULL;
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_employment_dates_check;
+ALTER TABLE users
+    ADD CONSTRAINT users_employment_dates_check
+    CHECK (termination_date IS NULL OR hire_date IS NULL OR termination_date >= hire_date);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_employee_no ON users(employee_no) WHERE employee_no IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_users_default_schedule ON users(default_schedule_rule_id);
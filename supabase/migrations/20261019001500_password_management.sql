@@ -0,0 +1,15 @@
+-- 密码管理：修改时间用于定期强制更换，临时密码有有效期
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS password_changed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    ADD COLUMN IF NOT EXISTS temporary_password_expires_at TIMESTAMP WITH TIME ZONE;
+
+-- 密码策略，0 表示不限制
+INSERT INTO system_config (config_key, config_value, config_desc) VALUES
+    ('password_min_length', '8', '密码最少字符数'),
+    ('password_require_uppercase', 'false', '密码须包含大写字母'),
+    ('password_require_lowercase', 'false', '密码须包含小写字母'),
+    ('password_require_digit', 'true', '密码须包含数字'),
+    ('password_require_symbol', 'false', '密码须包含符号'),
+    ('password_max_age_days', '0', '密码有效天数，到期后登录须修改（0 表示不过期）'),
+    ('temporary_password_hours', '72', '管理员重置的临时密码有效小时数')
+ON CONFLICT (config_key) DO NOTHING;
@@ -0,0 +1,17 @@
+-- 登录失败计数：按用户名和设备分别记录，连续失败时按指数退避，超过次数临时锁定
+CREATE TABLE IF NOT EXISTS login_throttles (
+    id SERIAL PRIMARY KEY,
+    scope VARCHAR(10) NOT NULL CHECK (scope IN ('user', 'device')),
+    key VARCHAR(255) NOT NULL,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMP WITH TIME ZONE,
+    locked_until TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (scope, key)
+);
+
+INSERT INTO system_config (config_key, config_value, config_desc) VALUES
+    ('login_max_failures', '5', '连续登录失败多少次后临时锁定'),
+    ('login_lockout_minutes', '15', '登录锁定分钟数'),
+    ('login_backoff_seconds', '1', '登录失败后的初始等待秒数，每次失败翻倍')
+ON CONFLICT (config_key) DO NOTHING;
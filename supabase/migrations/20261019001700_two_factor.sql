@@ -0,0 +1,10 @@
+-- 两步验证（TOTP）：启用前 totp_secret 为待确认的密钥，恢复码只保存哈希
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS totp_secret TEXT,
+    ADD COLUMN IF NOT EXISTS totp_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS totp_recovery_codes JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS totp_last_step BIGINT;
+
+INSERT INTO system_config (config_key, config_value, config_desc) VALUES
+    ('require_admin_two_factor', 'false', '超级管理员账号必须启用两步验证')
+ON CONFLICT (config_key) DO NOTHING;
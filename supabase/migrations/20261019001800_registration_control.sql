@@ -0,0 +1,48 @@
+-- 自助注册审批：待审批和已拒绝的账号不能登录，管理员创建的账号默认已通过
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS approval_status VARCHAR(20) NOT NULL DEFAULT 'approved'
+        CHECK (approval_status IN ('pending', 'approved', 'rejected'));
+
+-- 邀请码：一次性或到期失效，注册的员工自动加入绑定的部门
+CREATE TABLE IF NOT EXISTS invite_codes (
+    id SERIAL PRIMARY KEY,
+    code VARCHAR(32) NOT NULL UNIQUE,
+    department_id INTEGER REFERENCES departments(id),
+    max_uses INTEGER CHECK (max_uses IS NULL OR max_uses > 0),
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    revoked BOOLEAN NOT NULL DEFAULT FALSE,
+    created_by INTEGER REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT invite_codes_limited CHECK (max_uses IS NOT NULL OR expires_at IS NOT NULL)
+);
+
+-- 在同一条语句中检查并占用邀请码，并发注册时不会超过使用次数
+CREATE OR REPLACE FUNCTION consume_invite_code(invite_code TEXT)
+RETURNS JSON
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    consumed invite_codes;
+BEGIN
+    UPDATE invite_codes
+    SET use_count = use_count + 1
+    WHERE code = invite_code
+      AND NOT revoked
+      AND (expires_at IS NULL OR expires_at > NOW())
+      AND (max_uses IS NULL OR use_count < max_uses)
+    RETURNING * INTO consumed;
+
+    IF NOT FOUND THEN
+        RETURN NULL;
+    END IF;
+    RETURN row_to_json(consumed);
+END;
+$$;
+
+-- open：任何人可注册；invite_only：须填写邀请码；closed：只能由管理员创建账号
+INSERT INTO system_config (config_key, config_value, config_desc) VALUES
+    ('registration_mode', 'open', '自助注册方式：open 开放注册，invite_only 须邀请码，closed 关闭注册'),
+    ('registration_email_domains', '', '开放注册时允许的邮箱域名，逗号分隔，留空不限制'),
+    ('registration_requires_approval', 'false', '开放注册的账号须管理员审批后才能登录')
+ON CONFLICT (config_key) DO NOTHING;
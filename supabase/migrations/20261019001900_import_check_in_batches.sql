@@ -0,0 +1,41 @@
+-- 导入历史打卡记录的一个批次：在同一事务中插入记录并回填配对 ID，
+-- 失败时整批回滚，不会留下已完成但未配对的开始记录。
+-- pair_offset 为配对记录在本批次中的位置（从 0 开始）
+CREATE OR REPLACE FUNCTION import_check_in_batch(records JSON)
+RETURNS INTEGER
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    item JSON;
+    new_id INTEGER;
+    ids INTEGER[] := ARRAY[]::INTEGER[];
+    idx INTEGER;
+BEGIN
+    FOR item IN SELECT * FROM json_array_elements(records) LOOP
+        INSERT INTO check_ins (
+            user_id, action_type_id, check_time, status, duration_minutes, net_minutes, note,
+            is_late, is_early_leave, is_overtime_break, overtime_minutes, overtime_category, is_manual
+        )
+        SELECT
+            r.user_id, r.action_type_id, r.check_time, r.status, r.duration_minutes, r.net_minutes, r.note,
+            r.is_late, r.is_early_leave, r.is_overtime_break, r.overtime_minutes, r.overtime_category, r.is_manual
+        FROM json_populate_record(NULL::check_ins, item) r
+        RETURNING id INTO new_id;
+        ids := ids || new_id;
+    END LOOP;
+
+    FOR idx IN 1 .. COALESCE(array_length(ids, 1), 0) LOOP
+        item := records->(idx - 1);
+        IF item->>'pair_offset' IS NULL THEN
+            CONTINUE;
+        END IF;
+        IF (item->>'pair_offset')::INTEGER NOT BETWEEN 0 AND array_length(ids, 1) - 1 THEN
+            RAISE EXCEPTION '配对记录不在同一批次内';
+        END IF;
+        UPDATE check_ins SET pair_check_in_id = ids[(item->>'pair_offset')::INTEGER + 1]
+        WHERE id = ids[idx];
+    END LOOP;
+
+    RETURN COALESCE(array_length(ids, 1), 0);
+END;
+$$;
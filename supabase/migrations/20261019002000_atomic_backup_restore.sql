@@ -0,0 +1,118 @@
+-- 恢复备份时先把各表数据暂存到这里，再由 apply_backup_restore 在一个事务中写入，
+-- 中途失败不会留下只恢复了一部分的项目
+CREATE TABLE IF NOT EXISTS backup_restore_rows (
+    table_name TEXT NOT NULL,
+    row_no INTEGER NOT NULL,
+    row_data JSONB NOT NULL,
+    PRIMARY KEY (table_name, row_no)
+);
+
+-- 开始一次恢复：确认目标项目为空并清除上次未完成恢复留下的暂存数据，返回清除的行数
+CREATE OR REPLACE FUNCTION begin_backup_restore()
+RETURNS INTEGER
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    cleared INTEGER;
+BEGIN
+    IF EXISTS (SELECT 1 FROM users) OR EXISTS (SELECT 1 FROM check_ins) THEN
+        RAISE EXCEPTION '目标项目已有用户或打卡数据，只能恢复到空项目';
+    END IF;
+
+    DELETE FROM backup_restore_rows;
+    GET DIAGNOSTICS cleared = ROW_COUNT;
+    RETURN cleared;
+END;
+$$;
+
+-- 把暂存的数据写入各表。tables 按恢复顺序排列，格式为 [{"name": "...", "deferred": ["..."]}]，
+-- deferred 中的列引用的行可能还未写入，在所有表写入后再补上。
+-- 任何一步失败整体回滚，暂存数据保留，可直接重新开始恢复。返回每张表写入的行数
+CREATE OR REPLACE FUNCTION apply_backup_restore(tables JSON)
+RETURNS JSON
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    entry JSON;
+    target TEXT;
+    conflict_key TEXT;
+    deferred TEXT[];
+    column_list TEXT;
+    select_list TEXT;
+    update_list TEXT;
+    restored INTEGER;
+    counts JSONB := '{}'::JSONB;
+BEGIN
+    IF EXISTS (SELECT 1 FROM users) OR EXISTS (SELECT 1 FROM check_ins) THEN
+        RAISE EXCEPTION '目标项目已有用户或打卡数据，只能恢复到空项目';
+    END IF;
+
+    FOR entry IN SELECT * FROM json_array_elements(tables) LOOP
+        target := entry->>'name';
+        IF target NOT IN (
+            'departments', 'action_types', 'time_rules', 'users', 'holidays', 'leaves', 'system_config',
+            'check_ins', 'audit_log'
+        ) THEN
+            RAISE EXCEPTION '未知的数据表: %', target;
+        END IF;
+        -- 系统配置按键名合并，保留迁移脚本写入的默认项
+        conflict_key := CASE WHEN target = 'system_config' THEN 'config_key' ELSE 'id' END;
+        deferred := ARRAY(SELECT json_array_elements_text(COALESCE(entry->'deferred', '[]'::JSON)));
+
+        -- 只写入目标表存在且备份中出现过的列
+        SELECT
+            string_agg(quote_ident(c.column_name), ', ' ORDER BY c.ordinal_position),
+            string_agg('r.' || quote_ident(c.column_name), ', ' ORDER BY c.ordinal_position),
+            string_agg(format('%I = EXCLUDED.%I', c.column_name, c.column_name), ', ' ORDER BY c.ordinal_position)
+        INTO column_list, select_list, update_list
+        FROM information_schema.columns c
+        WHERE c.table_schema = 'public'
+          AND c.table_name = target
+          AND c.column_name <> ALL(deferred)
+          AND EXISTS (
+              SELECT 1 FROM backup_restore_rows s
+              WHERE s.table_name = target AND s.row_data ? c.column_name
+          );
+        IF column_list IS NULL THEN
+            counts := counts || jsonb_build_object(target, 0);
+            CONTINUE;
+        END IF;
+
+        EXECUTE format(
+            'INSERT INTO %I (%s) SELECT %s FROM backup_restore_rows s, jsonb_populate_record(NULL::%I, s.row_data) r '
+            'WHERE s.table_name = %L ORDER BY s.row_no ON CONFLICT (%I) DO UPDATE SET %s',
+            target, column_list, select_list, target, target, conflict_key, update_list
+        );
+        GET DIAGNOSTICS restored = ROW_COUNT;
+        counts := counts || jsonb_build_object(target, restored);
+    END LOOP;
+
+    -- 所有表写入后再补上引用列
+    FOR entry IN SELECT * FROM json_array_elements(tables) LOOP
+        target := entry->>'name';
+        conflict_key := CASE WHEN target = 'system_config' THEN 'config_key' ELSE 'id' END;
+        deferred := ARRAY(SELECT json_array_elements_text(COALESCE(entry->'deferred', '[]'::JSON)));
+
+        SELECT string_agg(format('%I = r.%I', c.column_name, c.column_name), ', ')
+        INTO update_list
+        FROM information_schema.columns c
+        WHERE c.table_schema = 'public'
+          AND c.table_name = target
+          AND c.column_name = ANY(deferred);
+        IF update_list IS NULL THEN
+            CONTINUE;
+        END IF;
+
+        EXECUTE format(
+            'UPDATE %I t SET %s FROM backup_restore_rows s, jsonb_populate_record(NULL::%I, s.row_data) r '
+            'WHERE s.table_name = %L AND t.%I = r.%I',
+            target, update_list, target, target, conflict_key, conflict_key
+        );
+    END LOOP;
+
+    PERFORM reset_id_sequences();
+    DELETE FROM backup_restore_rows;
+
+    RETURN counts::JSON;
+END;
+$$;